@@ -1,16 +1,52 @@
-use crate::config::{ZenohConfig, TRANSFORM_PUB_TOPIC};
+use crate::config::{
+    ClientConfig, LengthUnit, ZenohConfig, HEARTBEAT_PUB_TOPIC, TRANSFORM_HISTORY_PUB_TOPIC,
+    TRANSFORM_PUB_TOPIC,
+};
 use crate::error::CommsError;
-use schiebung::types::TransformType;
+use crate::server::LookupRecord;
+use schiebung::types::{StampedIsometry, TransformType};
+use schiebung::{BufferTree, TransformUpdate};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Client for publishing new transforms to the server
+///
+/// If the zenoh session gets closed, or a publish or query on it fails, the
+/// session is reopened with backoff (see [`ClientConfig::max_attempts`] and
+/// [`ClientConfig::initial_delay`]) and the call retried once before giving
+/// up, so long-lived clients survive a dropped session.
 pub struct TransformClient {
-    session: zenoh::Session,
+    /// Shared with the tasks of [`start_repeating`](Self::start_repeating),
+    /// so they publish on the reopened session after a reconnect.
+    connection: Arc<Connection>,
+    request_timeout: Duration,
+    /// Id of the next transform request, echoed back by the server.
+    next_request_id: AtomicU64,
+    /// See [`client_id`](TransformClient::client_id).
+    client_id: String,
+    /// See [`ClientConfig::length_unit`].
+    length_unit: LengthUnit,
+}
+
+/// The zenoh session of a [`TransformClient`] and what it takes to reopen it.
+struct Connection {
+    session: RwLock<zenoh::Session>,
+    /// Held while reopening the session so concurrent calls reconnect once.
+    reconnect_lock: tokio::sync::Mutex<()>,
+    zenoh_config: ZenohConfig,
+    max_attempts: u32,
+    initial_delay: Duration,
+    /// When the last server heartbeat arrived, see
+    /// [`server_alive`](TransformClient::server_alive).
+    last_heartbeat: Arc<Mutex<Option<Instant>>>,
 }
 
 impl TransformClient {
     /// Create a new transform publisher with default zenoh config (peer mode, multicast discovery).
     pub async fn new() -> Result<Self, CommsError> {
-        Self::with_config(ZenohConfig::default()).await
+        Self::with_client_config(ClientConfig::default()).await
     }
 
     /// Create a new transform publisher with an explicit zenoh config.
@@ -18,69 +54,357 @@ impl TransformClient {
     /// Use this to pin the client to known endpoints (e.g. for tests or deployments
     /// where multicast discovery is unavailable).
     pub async fn with_config(config: ZenohConfig) -> Result<Self, CommsError> {
-        let zenoh_config = config.to_zenoh_config()?;
+        Self::with_client_config(ClientConfig {
+            zenoh: config,
+            ..ClientConfig::default()
+        })
+        .await
+    }
 
-        let session = zenoh::open(zenoh_config)
-            .await
-            .map_err(|e| CommsError::Zenoh(format!("Failed to open zenoh session: {}", e)))?;
+    /// Create a new transform publisher from a full [`ClientConfig`].
+    pub async fn with_client_config(config: ClientConfig) -> Result<Self, CommsError> {
+        let session = open_session(&config.zenoh).await?;
+        let client_id = config
+            .client_id
+            .clone()
+            .unwrap_or_else(|| session.zid().to_string());
+        let last_heartbeat = Arc::new(Mutex::new(None));
+        watch_heartbeat(&session, &last_heartbeat).await?;
+
+        Ok(TransformClient {
+            connection: Arc::new(Connection {
+                session: RwLock::new(session),
+                reconnect_lock: tokio::sync::Mutex::new(()),
+                zenoh_config: config.zenoh.clone(),
+                max_attempts: config.max_attempts.max(1),
+                initial_delay: Duration::from_secs_f64(config.initial_delay.max(0.0)),
+                last_heartbeat,
+            }),
+            request_timeout: config.request_timeout(),
+            next_request_id: AtomicU64::new(1),
+            client_id,
+            length_unit: config.length_unit,
+        })
+    }
+
+    /// Id sent with every transform request, see [`ClientConfig::client_id`].
+    ///
+    /// Fixed for the lifetime of the client, including across reconnects.
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// Whether a server heartbeat arrived within the last `within`.
+    ///
+    /// Servers publish one every
+    /// [`DEFAULT_HEARTBEAT_INTERVAL`](crate::server::DEFAULT_HEARTBEAT_INTERVAL)
+    /// unless configured otherwise, so pick `within` as a few intervals.
+    /// `false` until the first heartbeat is received. Lets callers fail fast
+    /// instead of waiting for a request to time out against a dead server.
+    pub fn server_alive(&self, within: Duration) -> bool {
+        self.connection
+            .last_heartbeat
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_some_and(|last| last.elapsed() <= within)
+    }
+
+    /// Whether the zenoh session is currently open.
+    ///
+    /// A closed session is reopened by the next call that needs it.
+    pub fn is_connected(&self) -> bool {
+        !self.connection.current_session().is_closed()
+    }
 
-        Ok(TransformClient { session })
+    /// Close the zenoh session.
+    ///
+    /// Any later call reopens it; use this to release the connection while
+    /// keeping the client around.
+    pub async fn close(&self) -> Result<(), CommsError> {
+        self.connection
+            .current_session()
+            .close()
+            .await
+            .map_err(|e| CommsError::Zenoh(format!("Failed to close zenoh session: {}", e)))
     }
 
     /// Send a new transform to the server
+    ///
+    /// The translation is in [`ClientConfig::length_unit`] and is converted
+    /// to meters before it is published.
     pub async fn send_transform(
         &self,
         from: &str,
         to: &str,
-        stamped_isometry: schiebung::types::StampedIsometry,
+        stamped_isometry: StampedIsometry,
         kind: TransformType,
     ) -> Result<(), CommsError> {
-        let transform_kind = kind.into();
+        let stamped_isometry = &self.in_meters(stamped_isometry);
+        self.connection
+            .with_session(|session| async move {
+                put_transform(&session, from, to, stamped_isometry, kind).await
+            })
+            .await
+    }
 
-        let payload = crate::serializers::serialize_new_transform(
-            from,
-            to,
-            &stamped_isometry,
-            transform_kind,
-        )?;
+    /// `stamped_isometry` with its translation converted from
+    /// [`ClientConfig::length_unit`] to meters.
+    fn in_meters(&self, mut stamped_isometry: StampedIsometry) -> StampedIsometry {
+        stamped_isometry.isometry.translation.vector *= self.length_unit.meters_per_unit();
+        stamped_isometry
+    }
 
-        self.session
-            .put(TRANSFORM_PUB_TOPIC, zenoh::bytes::ZBytes::from(payload))
+    /// Republish a transform at a fixed rate until the returned handle is
+    /// stopped or dropped.
+    ///
+    /// Each publish carries `transform`'s pose restamped with the current
+    /// wall-clock time, so a dynamic edge keeps growing a valid history on
+    /// the server. Intended for demos and load tests. Must be called from
+    /// within a Tokio runtime; publish errors are logged and do not stop the
+    /// loop.
+    ///
+    /// # Errors
+    ///
+    /// [`CommsError::Config`] if `rate_hz` is not a positive, finite number.
+    pub fn start_repeating(
+        &self,
+        from: &str,
+        to: &str,
+        transform: StampedIsometry,
+        kind: TransformType,
+        rate_hz: f64,
+    ) -> Result<RepeatHandle, CommsError> {
+        if !(rate_hz.is_finite() && rate_hz > 0.0) {
+            return Err(CommsError::Config(format!(
+                "Publish rate must be positive, got {} Hz",
+                rate_hz
+            )));
+        }
+
+        let connection = self.connection.clone();
+        let (from, to) = (from.to_string(), to.to_string());
+        let transform = self.in_meters(transform);
+        let period = Duration::from_secs_f64(1.0 / rate_hz);
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let stamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as i64)
+                    .unwrap_or(0);
+                let sample = StampedIsometry {
+                    isometry: transform.isometry,
+                    stamp,
+                };
+                let (from, to, sample) = (&from, &to, &sample);
+                let result = connection
+                    .with_session(|session| async move {
+                        put_transform(&session, from, to, sample, kind).await
+                    })
+                    .await;
+                if let Err(e) = result {
+                    log::error!("Failed to republish transform {} -> {}: {}", from, to, e);
+                }
+            }
+        });
+
+        Ok(RepeatHandle { task })
+    }
+
+    /// Call `callback` for every transform published with
+    /// [`send_transform`](Self::send_transform) by any client.
+    ///
+    /// With a `pattern`, only transforms whose `to` frame matches it are
+    /// passed on. The pattern is a glob over the whole frame name: `*`
+    /// matches any run of characters (including `/`) and `?` any single
+    /// character, so `arm/*` mirrors everything below `arm/`. Transforms
+    /// stop arriving when the returned handle is stopped or dropped. Must be
+    /// called from within a Tokio runtime; malformed messages are logged and
+    /// skipped.
+    pub async fn subscribe_transforms<F>(
+        &self,
+        pattern: Option<&str>,
+        callback: F,
+    ) -> Result<SubscriptionHandle, CommsError>
+    where
+        F: Fn(TransformUpdate) + Send + 'static,
+    {
+        let subscriber = self.declare_transform_subscriber().await?;
+
+        let pattern = pattern.map(str::to_string);
+        let task = tokio::spawn(async move {
+            while let Ok(sample) = subscriber.recv_async().await {
+                let data = sample.payload().to_bytes();
+                match crate::serializers::deserialize_new_transform(&data) {
+                    Ok((from, to, stamped_isometry, kind)) => {
+                        if pattern.as_deref().is_none_or(|p| glob_match(p, &to)) {
+                            callback(TransformUpdate::new(
+                                from,
+                                to,
+                                stamped_isometry,
+                                kind.into(),
+                            ));
+                        }
+                    }
+                    Err(e) => log::error!("Failed to decode published transform: {}", e),
+                }
+            }
+        });
+
+        Ok(SubscriptionHandle { task })
+    }
+
+    /// Stream of every transform published with
+    /// [`send_transform`](Self::send_transform) by any client.
+    ///
+    /// The async counterpart of
+    /// [`subscribe_transforms`](Self::subscribe_transforms): consume it with
+    /// `StreamExt::next` instead of a callback. Dropping the stream
+    /// undeclares the subscriber. Malformed messages are logged and skipped.
+    pub async fn transform_stream(
+        &self,
+    ) -> Result<impl futures::Stream<Item = TransformUpdate>, CommsError> {
+        let subscriber = self.declare_transform_subscriber().await?;
+
+        Ok(futures::stream::unfold(
+            subscriber,
+            |subscriber| async move {
+                loop {
+                    let sample = subscriber.recv_async().await.ok()?;
+                    let data = sample.payload().to_bytes();
+                    match crate::serializers::deserialize_new_transform(&data) {
+                        Ok((from, to, stamped_isometry, kind)) => {
+                            let update =
+                                TransformUpdate::new(from, to, stamped_isometry, kind.into());
+                            return Some((update, subscriber));
+                        }
+                        Err(e) => log::error!("Failed to decode published transform: {}", e),
+                    }
+                }
+            },
+        ))
+    }
+
+    async fn declare_transform_subscriber(
+        &self,
+    ) -> Result<
+        zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<zenoh::sample::Sample>>,
+        CommsError,
+    > {
+        self.connection
+            .with_session(|session| async move {
+                session
+                    .declare_subscriber(TRANSFORM_PUB_TOPIC)
+                    .await
+                    .map_err(|e| CommsError::Zenoh(format!("Failed to declare subscriber: {}", e)))
+            })
+            .await
+    }
+
+    /// Send a batch of samples for one edge to the server in a single message
+    ///
+    /// Unlike [`send_transform`](Self::send_transform) this carries enough
+    /// history for the server to interpolate a dynamic edge between samples.
+    /// Translations are converted from [`ClientConfig::length_unit`] as there.
+    pub async fn send_transform_history(
+        &self,
+        from: &str,
+        to: &str,
+        samples: &[StampedIsometry],
+        kind: TransformType,
+    ) -> Result<(), CommsError> {
+        let samples: Vec<StampedIsometry> = samples
+            .iter()
+            .map(|sample| self.in_meters(sample.clone()))
+            .collect();
+        let payload =
+            &crate::serializers::serialize_transform_history(from, to, &samples, kind.into())?;
+
+        self.connection
+            .with_session(|session| async move {
+                session
+                    .put(
+                        TRANSFORM_HISTORY_PUB_TOPIC,
+                        zenoh::bytes::ZBytes::from(payload.clone()),
+                    )
+                    .await
+                    .map_err(|e| CommsError::Zenoh(e.to_string()))
+            })
             .await
-            .map_err(|e| CommsError::Zenoh(e.to_string()))?;
+    }
 
+    /// Send the newest sample of every edge in `buffer` to the server
+    ///
+    /// Useful to bootstrap a server from a locally built [`BufferTree`] (e.g.
+    /// one loaded from a URDF). Each edge keeps its [`TransformType`]; edges
+    /// without samples are skipped. The buffer already holds meters, so
+    /// [`ClientConfig::length_unit`] does not apply.
+    pub async fn send_buffer(&self, buffer: &BufferTree) -> Result<(), CommsError> {
+        for edge in buffer.iter_edges() {
+            if let Some(latest) = edge.latest() {
+                let edge = &edge;
+                self.connection
+                    .with_session(|session| async move {
+                        put_transform(&session, edge.from, edge.to, latest, edge.kind).await
+                    })
+                    .await?;
+            }
+        }
         Ok(())
     }
 
     /// Request a transform from the server
     /// Time is in nanoseconds since Unix epoch
+    ///
+    /// Fails with [`CommsError::Lookup`] if the server could not compute the
+    /// transform (the [`LookupErrorKind`](crate::LookupErrorKind) says why),
+    /// or with [`CommsError::Timeout`] if no reply arrives within the
+    /// configured request timeout. Each request carries its own id, which
+    /// the server echoes; a reply to a different request fails with
+    /// [`CommsError::ResponseIdMismatch`].
     pub async fn request_transform(
         &self,
         from: &str,
         to: &str,
         time: i64,
-    ) -> Result<schiebung::types::StampedIsometry, CommsError> {
-        let request_data = crate::serializers::serialize_transform_request(from, to, time)?;
+    ) -> Result<StampedIsometry, CommsError> {
+        let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let request_data =
+            &crate::serializers::serialize_transform_request(id, &self.client_id, from, to, time)?;
 
         let replies = self
-            .session
-            .get(crate::config::TRANSFORM_QUERY_TOPIC)
-            .payload(zenoh::bytes::ZBytes::from(request_data))
-            .await
-            .map_err(|e| CommsError::Zenoh(format!("Failed to send query: {}", e)))?;
+            .connection
+            .with_session(|session| async move {
+                session
+                    .get(crate::config::TRANSFORM_QUERY_TOPIC)
+                    .payload(zenoh::bytes::ZBytes::from(request_data.clone()))
+                    .timeout(self.request_timeout)
+                    .await
+                    .map_err(|e| CommsError::Zenoh(format!("Failed to send query: {}", e)))
+            })
+            .await?;
 
         // Wait for first reply
-        while let Ok(reply) = replies.recv_async().await {
+        let first_reply = tokio::time::timeout(self.request_timeout, replies.recv_async())
+            .await
+            .map_err(|_| CommsError::Timeout(self.request_timeout))?;
+        if let Ok(reply) = first_reply {
             match reply.result() {
                 Ok(sample) => {
                     let response_data = sample.payload().to_bytes();
-                    match crate::serializers::deserialize_transform_response(&response_data)? {
+                    let (response_id, result) =
+                        crate::serializers::deserialize_transform_response(&response_data)?;
+                    if response_id != id {
+                        return Err(CommsError::ResponseIdMismatch {
+                            expected: id,
+                            actual: response_id,
+                        });
+                    }
+                    match result {
                         Ok(stamped_isometry) => return Ok(stamped_isometry),
-                        Err(error_message) => {
-                            return Err(CommsError::Zenoh(format!(
-                                "Transform request failed: {}",
-                                error_message
-                            )));
+                        Err((kind, message)) => {
+                            return Err(CommsError::Lookup { kind, message });
                         }
                     }
                 }
@@ -92,4 +416,305 @@ impl TransformClient {
 
         Err(CommsError::NoResponse)
     }
+
+    /// Like [`request_transform`](Self::request_transform), but retries on
+    /// failure with exponential backoff.
+    ///
+    /// Makes up to [`ClientConfig::max_attempts`] tries, waiting
+    /// [`ClientConfig::initial_delay`] before the first retry and doubling
+    /// the wait after each further failure. Useful right after startup, when
+    /// the server may not be reachable yet or the transform not yet published.
+    /// Returns the last error if every attempt fails.
+    pub async fn request_transform_retry(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+    ) -> Result<StampedIsometry, CommsError> {
+        let mut delay = self.connection.initial_delay;
+        let mut attempt = 1;
+        loop {
+            match self.request_transform(from, to, time).await {
+                Ok(stamped_isometry) => return Ok(stamped_isometry),
+                Err(e) if attempt >= self.connection.max_attempts => return Err(e),
+                Err(e) => {
+                    log::debug!(
+                        "Transform request {} -> {} failed (attempt {}/{}): {}",
+                        from,
+                        to,
+                        attempt,
+                        self.connection.max_attempts,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Request the server's transform graph as a Graphviz DOT string
+    ///
+    /// This is the same output as [`BufferTree::visualize`](schiebung::BufferTree::visualize)
+    /// on the server, so the graph can be inspected without shared filesystem access.
+    pub async fn request_visualization(&self) -> Result<String, CommsError> {
+        let response_data = self.query(crate::config::VISUALIZE_QUERY_TOPIC).await?;
+        crate::serializers::deserialize_visualization_response(&response_data)
+    }
+
+    /// Request the server's log of recently answered transform requests
+    ///
+    /// Returns up to the server's configured capacity of records, oldest
+    /// first, each with the requested frames, time and outcome. Handy for
+    /// seeing which lookups a running system is failing on.
+    pub async fn request_recent_lookups(&self) -> Result<Vec<LookupRecord>, CommsError> {
+        let response_data = self
+            .query(crate::config::RECENT_LOOKUPS_QUERY_TOPIC)
+            .await?;
+        crate::serializers::deserialize_recent_lookups_response(&response_data)
+    }
+
+    /// Remove every frame and edge from the server's buffer
+    ///
+    /// Fails with [`CommsError::Zenoh`] if the server was not started with
+    /// [`TransformServer::with_remote_clear`](crate::server::TransformServer::with_remote_clear).
+    pub async fn clear_server(&self) -> Result<(), CommsError> {
+        self.query(crate::config::CLEAR_QUERY_TOPIC).await?;
+        Ok(())
+    }
+
+    /// Send an empty query on `topic` and return the payload of the first reply
+    async fn query(&self, topic: &str) -> Result<Vec<u8>, CommsError> {
+        let replies = self
+            .connection
+            .with_session(|session| async move {
+                session
+                    .get(topic)
+                    .timeout(self.request_timeout)
+                    .await
+                    .map_err(|e| CommsError::Zenoh(format!("Failed to send query: {}", e)))
+            })
+            .await?;
+
+        let first_reply = tokio::time::timeout(self.request_timeout, replies.recv_async())
+            .await
+            .map_err(|_| CommsError::Timeout(self.request_timeout))?;
+        if let Ok(reply) = first_reply {
+            return match reply.result() {
+                Ok(sample) => Ok(sample.payload().to_bytes().into_owned()),
+                Err(e) => Err(CommsError::Zenoh(format!(
+                    "Query error: {}",
+                    String::from_utf8_lossy(&e.payload().to_bytes())
+                ))),
+            };
+        }
+
+        Err(CommsError::NoResponse)
+    }
+}
+
+impl Connection {
+    fn current_session(&self) -> zenoh::Session {
+        self.session
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// The open session, reopening it with exponential backoff first if it
+    /// was closed. Returns the last error if every attempt fails.
+    async fn session(&self) -> Result<zenoh::Session, CommsError> {
+        let session = self.current_session();
+        if !session.is_closed() {
+            return Ok(session);
+        }
+
+        let _guard = self.reconnect_lock.lock().await;
+        // Another call may have reconnected while we waited for the lock.
+        let session = self.current_session();
+        if !session.is_closed() {
+            return Ok(session);
+        }
+
+        let mut delay = self.initial_delay;
+        let mut attempt = 1;
+        loop {
+            match open_session(&self.zenoh_config).await {
+                Ok(session) => {
+                    log::info!("Reopened zenoh session (attempt {})", attempt);
+                    watch_heartbeat(&session, &self.last_heartbeat).await?;
+                    *self
+                        .session
+                        .write()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()) = session.clone();
+                    return Ok(session);
+                }
+                Err(e) if attempt >= self.max_attempts => return Err(e),
+                Err(e) => {
+                    log::debug!(
+                        "Reopening zenoh session failed (attempt {}/{}): {}",
+                        attempt,
+                        self.max_attempts,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Run `op` on the open session. If it fails with [`CommsError::Zenoh`],
+    /// the session is closed and reopened through
+    /// [`session`](Connection::session) and `op` retried once, so a session
+    /// that broke without being closed is replaced too.
+    async fn with_session<T, F, Fut>(&self, op: F) -> Result<T, CommsError>
+    where
+        F: Fn(zenoh::Session) -> Fut,
+        Fut: Future<Output = Result<T, CommsError>>,
+    {
+        let session = self.session().await?;
+        match op(session.clone()).await {
+            Err(CommsError::Zenoh(e)) => {
+                log::warn!("Zenoh call failed, reopening session: {}", e);
+                if let Err(e) = session.close().await {
+                    log::debug!("Closing the failed zenoh session failed: {}", e);
+                }
+                op(self.session().await?).await
+            }
+            result => result,
+        }
+    }
+}
+
+/// Record the arrival time of every server heartbeat on `session` in
+/// `last_heartbeat`, for as long as the session stays open.
+async fn watch_heartbeat(
+    session: &zenoh::Session,
+    last_heartbeat: &Arc<Mutex<Option<Instant>>>,
+) -> Result<(), CommsError> {
+    let last_heartbeat = last_heartbeat.clone();
+    session
+        .declare_subscriber(HEARTBEAT_PUB_TOPIC)
+        .callback(move |_| {
+            *last_heartbeat
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Instant::now());
+        })
+        .background()
+        .await
+        .map_err(|e| CommsError::Zenoh(format!("Failed to declare subscriber: {}", e)))
+}
+
+/// Handle to a transform being republished by
+/// [`TransformClient::start_repeating`]. Publishing stops when the handle is
+/// stopped or dropped.
+pub struct RepeatHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl RepeatHandle {
+    /// Stop publishing.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for RepeatHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Handle to a subscription created by
+/// [`TransformClient::subscribe_transforms`]. The callback stops being
+/// called when the handle is stopped or dropped.
+pub struct SubscriptionHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl SubscriptionHandle {
+    /// Stop receiving transforms.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Match `name` against a glob `pattern` where `*` matches any run of
+/// characters and `?` matches exactly one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in the pattern and the name index it was
+    // tried against, to backtrack to when a later character mismatches.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+async fn open_session(config: &ZenohConfig) -> Result<zenoh::Session, CommsError> {
+    zenoh::open(config.to_zenoh_config()?)
+        .await
+        .map_err(|e| CommsError::Zenoh(format!("Failed to open zenoh session: {}", e)))
+}
+
+async fn put_transform(
+    session: &zenoh::Session,
+    from: &str,
+    to: &str,
+    stamped_isometry: &StampedIsometry,
+    kind: TransformType,
+) -> Result<(), CommsError> {
+    let payload =
+        crate::serializers::serialize_new_transform(from, to, stamped_isometry, kind.into())?;
+
+    session
+        .put(TRANSFORM_PUB_TOPIC, zenoh::bytes::ZBytes::from(payload))
+        .await
+        .map_err(|e| CommsError::Zenoh(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("arm/*", "arm/link_1"));
+        assert!(glob_match("arm/*", "arm/"));
+        assert!(glob_match("*", "anything/at/all"));
+        assert!(glob_match("arm/link_?", "arm/link_2"));
+        assert!(glob_match("*/tool", "robot/arm/tool"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("arm/*", "base_link"));
+        assert!(!glob_match("arm/link_?", "arm/link_10"));
+        assert!(!glob_match("*/tool", "robot/arm/tool0"));
+        assert!(glob_match("base_link", "base_link"));
+        assert!(!glob_match("base_link", "base_link2"));
+    }
 }
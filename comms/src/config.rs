@@ -1,5 +1,10 @@
 pub const TRANSFORM_PUB_TOPIC: &str = "schiebung/transforms/new";
+pub const TRANSFORM_HISTORY_PUB_TOPIC: &str = "schiebung/transforms/history";
 pub const TRANSFORM_QUERY_TOPIC: &str = "schiebung/transforms/get";
+pub const VISUALIZE_QUERY_TOPIC: &str = "schiebung/transforms/visualize";
+pub const RECENT_LOOKUPS_QUERY_TOPIC: &str = "schiebung/transforms/recent_lookups";
+pub const CLEAR_QUERY_TOPIC: &str = "schiebung/transforms/clear";
+pub const HEARTBEAT_PUB_TOPIC: &str = "schiebung/server/heartbeat";
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct ZenohConfig {
@@ -78,6 +83,88 @@ impl ZenohConfig {
     }
 }
 
+/// Configuration for a [`TransformClient`](crate::TransformClient)
+///
+/// Kept separate from the server's settings so both ends can be configured
+/// independently.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ClientConfig {
+    /// Zenoh session settings used by the client.
+    #[serde(default)]
+    pub zenoh: ZenohConfig,
+    /// Maximum time in seconds to wait for a reply to a query.
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: f64,
+    /// Total number of tries `request_transform_retry` makes before giving up.
+    /// Also bounds the attempts to reopen a closed session.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay in seconds before the first retry or reconnect attempt; doubled
+    /// after every failure.
+    #[serde(default = "default_initial_delay")]
+    pub initial_delay: f64,
+    /// Sent with every transform request so the server can rate limit
+    /// clients separately. Defaults to the client's zenoh session id.
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Unit of the translations passed to the client's send methods. They
+    /// are converted to meters before they are published.
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+}
+
+/// Unit of the translations a [`TransformClient`](crate::TransformClient)
+/// is given, see [`ClientConfig::length_unit`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthUnit {
+    #[default]
+    Meters,
+    Millimeters,
+}
+
+impl LengthUnit {
+    /// Length of one unit in meters.
+    pub fn meters_per_unit(self) -> f64 {
+        match self {
+            LengthUnit::Meters => 1.0,
+            LengthUnit::Millimeters => 1e-3,
+        }
+    }
+}
+
+fn default_request_timeout() -> f64 {
+    10.0
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+fn default_initial_delay() -> f64 {
+    0.1
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            zenoh: ZenohConfig::default(),
+            request_timeout: default_request_timeout(),
+            max_attempts: default_max_attempts(),
+            initial_delay: default_initial_delay(),
+            client_id: None,
+            length_unit: LengthUnit::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// The request timeout as a [`Duration`](std::time::Duration).
+    pub fn request_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f64(self.request_timeout)
+    }
+}
+
 fn json_string_array(items: &[String]) -> String {
     let escaped: Vec<String> = items
         .iter()
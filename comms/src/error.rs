@@ -19,6 +19,18 @@ pub enum CommsError {
     #[error("Response ID mismatch: expected {expected}, got {actual}")]
     ResponseIdMismatch { expected: u64, actual: u64 },
 
+    #[error("Protocol version mismatch: expected {expected}, got {actual}")]
+    ProtocolVersionMismatch { expected: u16, actual: u16 },
+
+    #[error("Request timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("Transform request failed ({kind:?}): {message}")]
+    Lookup {
+        kind: LookupErrorKind,
+        message: String,
+    },
+
     #[error("No response received for transform request")]
     NoResponse,
 
@@ -29,6 +41,25 @@ pub enum CommsError {
     Utf8(#[from] std::str::Utf8Error),
 }
 
+/// Why the server could not answer a transform request, carried in
+/// [`CommsError::Lookup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupErrorKind {
+    /// One of the requested frames is not known to the server.
+    UnknownFrame,
+    /// Both frames are known, but no usable path connects them.
+    NoPath,
+    /// The requested time is older than the buffered history.
+    LookupInPast,
+    /// The requested time is newer than the newest sample.
+    LookupInFuture,
+    /// The client sent more requests than the server's rate limit allows
+    /// (see [`TransformServer::with_rate_limit`](crate::server::TransformServer::with_rate_limit)).
+    RateLimited,
+    /// Any other failure.
+    Other,
+}
+
 impl From<String> for CommsError {
     fn from(s: String) -> Self {
         CommsError::Zenoh(s)
@@ -12,9 +12,11 @@ pub mod error;
 pub mod serializers;
 pub mod server;
 
-pub use client::TransformClient;
-pub use config::ZenohConfig;
-pub use error::CommsError;
+pub use client::{RepeatHandle, SubscriptionHandle, TransformClient};
+pub use config::{ClientConfig, LengthUnit, ZenohConfig};
+pub use error::{CommsError, LookupErrorKind};
+pub use serializers::PROTOCOL_VERSION;
+pub use server::LookupRecord;
 
 // Type conversion helpers
 impl From<schiebung::types::TransformType> for messages_capnp::TransformKind {
@@ -27,6 +29,19 @@ impl From<schiebung::types::TransformType> for messages_capnp::TransformKind {
     }
 }
 
+impl From<LookupErrorKind> for messages_capnp::TransformErrorKind {
+    fn from(kind: LookupErrorKind) -> Self {
+        match kind {
+            LookupErrorKind::UnknownFrame => Self::UnknownFrame,
+            LookupErrorKind::NoPath => Self::NoPath,
+            LookupErrorKind::LookupInPast => Self::LookupInPast,
+            LookupErrorKind::LookupInFuture => Self::LookupInFuture,
+            LookupErrorKind::RateLimited => Self::RateLimited,
+            LookupErrorKind::Other => Self::Other,
+        }
+    }
+}
+
 impl From<messages_capnp::TransformKind> for schiebung::types::TransformType {
     fn from(tk: messages_capnp::TransformKind) -> Self {
         use schiebung::types::TransformType;
@@ -36,3 +51,19 @@ impl From<messages_capnp::TransformKind> for schiebung::types::TransformType {
         }
     }
 }
+
+impl From<messages_capnp::TransformErrorKind> for LookupErrorKind {
+    /// `none` only appears on successful responses; on a failed one it is
+    /// treated as [`LookupErrorKind::Other`].
+    fn from(kind: messages_capnp::TransformErrorKind) -> Self {
+        match kind {
+            messages_capnp::TransformErrorKind::UnknownFrame => LookupErrorKind::UnknownFrame,
+            messages_capnp::TransformErrorKind::NoPath => LookupErrorKind::NoPath,
+            messages_capnp::TransformErrorKind::LookupInPast => LookupErrorKind::LookupInPast,
+            messages_capnp::TransformErrorKind::LookupInFuture => LookupErrorKind::LookupInFuture,
+            messages_capnp::TransformErrorKind::RateLimited => LookupErrorKind::RateLimited,
+            messages_capnp::TransformErrorKind::None
+            | messages_capnp::TransformErrorKind::Other => LookupErrorKind::Other,
+        }
+    }
+}
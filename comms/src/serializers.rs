@@ -1,10 +1,59 @@
-use crate::error::CommsError;
-use crate::messages_capnp::{self, new_transform, transform_request, transform_response};
+use crate::error::{CommsError, LookupErrorKind};
+use crate::messages_capnp::{
+    self, new_transform, recent_lookups_response, transform_history, transform_request,
+    transform_response, visualization_response,
+};
+use crate::server::LookupRecord;
 use schiebung::types::StampedIsometry;
 
+// Every message carrying a pose stores the translation as `[x, y, z]` and the
+// rotation quaternion as `[x, y, z, w]`, the order of
+// `StampedIsometry::translation` and `StampedIsometry::rotation`. All of them
+// go through `write_floats` and `read_floats`, so that order is defined here
+// only.
 const TRANSLATION_SIZE: u32 = 3;
 const ROTATION_SIZE: u32 = 4;
 
+/// Wire-format version written into, and required of, every message.
+///
+/// Bump this whenever a schema change would make old and new peers misread
+/// each other. Messages from peers that predate the field read as version 0.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Reject a message written with a different [`PROTOCOL_VERSION`].
+fn check_protocol_version(actual: u16) -> Result<(), CommsError> {
+    if actual != PROTOCOL_VERSION {
+        return Err(CommsError::ProtocolVersionMismatch {
+            expected: PROTOCOL_VERSION,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// A decoded transform response: the id of the request it answers, and
+/// the transform or why the lookup failed.
+pub type TransformResponse = (u64, Result<StampedIsometry, (LookupErrorKind, String)>);
+
+/// Copy `values` into a freshly initialized list of the same length.
+fn write_floats(mut list: capnp::primitive_list::Builder<'_, f64>, values: &[f64]) {
+    for (i, &val) in values.iter().enumerate() {
+        list.set(i as u32, val);
+    }
+}
+
+/// Read a list that must hold exactly `N` values.
+fn read_floats<const N: usize>(
+    list: capnp::primitive_list::Reader<'_, f64>,
+) -> Result<[f64; N], CommsError> {
+    if list.len() as usize != N {
+        return Err(
+            capnp::Error::failed(format!("Expected {} values, got {}", N, list.len())).into(),
+        );
+    }
+    Ok(std::array::from_fn(|i| list.get(i as u32)))
+}
+
 /// Serialize a new transform with StampedIsometry
 pub fn serialize_new_transform(
     from: &str,
@@ -15,25 +64,19 @@ pub fn serialize_new_transform(
     let mut message = capnp::message::Builder::new_default();
     let mut transform = message.init_root::<new_transform::Builder>();
 
+    transform.set_protocol_version(PROTOCOL_VERSION);
     transform.set_from(from);
     transform.set_to(to);
     transform.set_time_ns(stamped_isometry.stamp());
 
-    let translation = stamped_isometry.translation();
-    {
-        let mut trans = transform.reborrow().init_translation(TRANSLATION_SIZE);
-        for (i, &val) in translation.iter().enumerate() {
-            trans.set(i as u32, val);
-        }
-    }
-
-    let rotation = stamped_isometry.rotation();
-    {
-        let mut rot = transform.reborrow().init_rotation(ROTATION_SIZE);
-        for (i, &val) in rotation.iter().enumerate() {
-            rot.set(i as u32, val);
-        }
-    }
+    write_floats(
+        transform.reborrow().init_translation(TRANSLATION_SIZE),
+        &stamped_isometry.translation(),
+    );
+    write_floats(
+        transform.reborrow().init_rotation(ROTATION_SIZE),
+        &stamped_isometry.rotation(),
+    );
 
     transform.set_kind(kind);
 
@@ -53,22 +96,36 @@ pub fn deserialize_new_transform(
         messages_capnp::TransformKind,
     ),
     CommsError,
+> {
+    deserialize_new_transform_checked(data, None)
+}
+
+/// Like [`deserialize_new_transform`], but with a `quaternion_tolerance`
+/// reject a rotation whose norm is off by more than that instead of
+/// normalizing it (see [`StampedIsometry::try_new`]).
+pub fn deserialize_new_transform_checked(
+    data: &[u8],
+    quaternion_tolerance: Option<f64>,
+) -> Result<
+    (
+        String,
+        String,
+        StampedIsometry,
+        messages_capnp::TransformKind,
+    ),
+    CommsError,
 > {
     let reader =
         capnp::serialize::read_message(&mut &data[..], capnp::message::ReaderOptions::new())?;
     let transform = reader.get_root::<new_transform::Reader>()?;
-
-    let translation = {
-        let trans = transform.get_translation()?;
-        [trans.get(0), trans.get(1), trans.get(2)]
-    };
-
-    let rotation = {
-        let rot = transform.get_rotation()?;
-        [rot.get(0), rot.get(1), rot.get(2), rot.get(3)]
-    };
-
-    let stamped_isometry = StampedIsometry::new(translation, rotation, transform.get_time_ns());
+    check_protocol_version(transform.get_protocol_version())?;
+
+    let stamped_isometry = to_stamped_isometry(
+        read_floats(transform.get_translation()?)?,
+        read_floats(transform.get_rotation()?)?,
+        transform.get_time_ns(),
+        quaternion_tolerance,
+    )?;
     let kind = transform.get_kind()?;
 
     Ok((
@@ -79,12 +136,131 @@ pub fn deserialize_new_transform(
     ))
 }
 
+/// Serialize a batch of samples for a single edge
+pub fn serialize_transform_history(
+    from: &str,
+    to: &str,
+    samples: &[StampedIsometry],
+    kind: messages_capnp::TransformKind,
+) -> Result<Vec<u8>, CommsError> {
+    let mut message = capnp::message::Builder::new_default();
+    let mut history = message.init_root::<transform_history::Builder>();
+
+    history.set_protocol_version(PROTOCOL_VERSION);
+    history.set_from(from);
+    history.set_to(to);
+    history.set_kind(kind);
+
+    {
+        let mut list = history.reborrow().init_samples(samples.len() as u32);
+        for (i, stamped_isometry) in samples.iter().enumerate() {
+            let mut sample = list.reborrow().get(i as u32);
+            sample.set_time_ns(stamped_isometry.stamp());
+
+            write_floats(
+                sample.reborrow().init_translation(TRANSLATION_SIZE),
+                &stamped_isometry.translation(),
+            );
+            write_floats(
+                sample.reborrow().init_rotation(ROTATION_SIZE),
+                &stamped_isometry.rotation(),
+            );
+        }
+    }
+
+    let mut buffer = Vec::new();
+    capnp::serialize::write_message(&mut buffer, &message)?;
+    Ok(buffer)
+}
+
+/// Deserialize a batch of samples for a single edge
+pub fn deserialize_transform_history(
+    data: &[u8],
+) -> Result<
+    (
+        String,
+        String,
+        Vec<StampedIsometry>,
+        messages_capnp::TransformKind,
+    ),
+    CommsError,
+> {
+    deserialize_transform_history_checked(data, None)
+}
+
+/// Like [`deserialize_transform_history`], but with a `quaternion_tolerance`
+/// reject the batch if any sample's rotation norm is off by more than that.
+pub fn deserialize_transform_history_checked(
+    data: &[u8],
+    quaternion_tolerance: Option<f64>,
+) -> Result<
+    (
+        String,
+        String,
+        Vec<StampedIsometry>,
+        messages_capnp::TransformKind,
+    ),
+    CommsError,
+> {
+    let reader =
+        capnp::serialize::read_message(&mut &data[..], capnp::message::ReaderOptions::new())?;
+    let history = reader.get_root::<transform_history::Reader>()?;
+    check_protocol_version(history.get_protocol_version())?;
+
+    let mut samples = Vec::new();
+    for sample in history.get_samples()?.iter() {
+        samples.push(to_stamped_isometry(
+            read_floats(sample.get_translation()?)?,
+            read_floats(sample.get_rotation()?)?,
+            sample.get_time_ns(),
+            quaternion_tolerance,
+        )?);
+    }
+
+    Ok((
+        history.get_from()?.to_str()?.to_string(),
+        history.get_to()?.to_str()?.to_string(),
+        samples,
+        history.get_kind()?,
+    ))
+}
+
+/// Build a [`StampedIsometry`], normalizing the rotation unless a
+/// `quaternion_tolerance` asks for it to be checked instead.
+fn to_stamped_isometry(
+    translation: [f64; 3],
+    rotation: [f64; 4],
+    stamp_ns: i64,
+    quaternion_tolerance: Option<f64>,
+) -> Result<StampedIsometry, CommsError> {
+    match quaternion_tolerance {
+        Some(tolerance) => Ok(StampedIsometry::try_new(
+            translation,
+            rotation,
+            stamp_ns,
+            tolerance,
+        )?),
+        None => Ok(StampedIsometry::new(translation, rotation, stamp_ns)),
+    }
+}
+
 /// Serialize a transform request
-/// Time is in nanoseconds since Unix epoch
-pub fn serialize_transform_request(from: &str, to: &str, time: i64) -> Result<Vec<u8>, CommsError> {
+/// Time is in nanoseconds since Unix epoch. The server echoes `id` in its
+/// response, so a client can match responses to concurrent requests, and
+/// rate limits requests per `client_id`.
+pub fn serialize_transform_request(
+    id: u64,
+    client_id: &str,
+    from: &str,
+    to: &str,
+    time: i64,
+) -> Result<Vec<u8>, CommsError> {
     let mut message = capnp::message::Builder::new_default();
     let mut request = message.init_root::<transform_request::Builder>();
 
+    request.set_protocol_version(PROTOCOL_VERSION);
+    request.set_id(id);
+    request.set_client_id(client_id);
     request.set_from(from);
     request.set_to(to);
     request.set_time_ns(time);
@@ -94,13 +270,18 @@ pub fn serialize_transform_request(from: &str, to: &str, time: i64) -> Result<Ve
     Ok(buffer)
 }
 
-/// Deserialize a transform request
-pub fn deserialize_transform_request(data: &[u8]) -> Result<(String, String, i64), CommsError> {
+/// Deserialize a transform request into `(id, client_id, from, to, time)`
+pub fn deserialize_transform_request(
+    data: &[u8],
+) -> Result<(u64, String, String, String, i64), CommsError> {
     let reader =
         capnp::serialize::read_message(&mut &data[..], capnp::message::ReaderOptions::new())?;
     let request = reader.get_root::<transform_request::Reader>()?;
+    check_protocol_version(request.get_protocol_version())?;
 
     Ok((
+        request.get_id(),
+        request.get_client_id()?.to_str()?.to_string(),
         request.get_from()?.to_str()?.to_string(),
         request.get_to()?.to_str()?.to_string(),
         request.get_time_ns(),
@@ -108,67 +289,147 @@ pub fn deserialize_transform_request(data: &[u8]) -> Result<(String, String, i64
 }
 
 /// Serialize a transform response with StampedIsometry
+/// `id` is the id of the request being answered.
 pub fn serialize_transform_response(
+    id: u64,
     stamped_isometry: &StampedIsometry,
-    success: bool,
-    error_message: &str,
+    error: Option<(LookupErrorKind, &str)>,
 ) -> Result<Vec<u8>, CommsError> {
     let mut message = capnp::message::Builder::new_default();
     let mut response = message.init_root::<transform_response::Builder>();
 
+    response.set_protocol_version(PROTOCOL_VERSION);
+    response.set_id(id);
     response.set_time_ns(stamped_isometry.stamp());
-    response.set_success(success);
-    response.set_error_message(error_message);
-
-    let translation = stamped_isometry.translation();
-    {
-        let mut trans = response.reborrow().init_translation(TRANSLATION_SIZE);
-        for (i, &val) in translation.iter().enumerate() {
-            trans.set(i as u32, val);
+    match error {
+        None => {
+            response.set_success(true);
+            response.set_error_message("");
+            response.set_error_kind(messages_capnp::TransformErrorKind::None);
         }
-    }
-
-    let rotation = stamped_isometry.rotation();
-    {
-        let mut rot = response.reborrow().init_rotation(ROTATION_SIZE);
-        for (i, &val) in rotation.iter().enumerate() {
-            rot.set(i as u32, val);
+        Some((kind, error_message)) => {
+            response.set_success(false);
+            response.set_error_message(error_message);
+            response.set_error_kind(kind.into());
         }
     }
 
+    write_floats(
+        response.reborrow().init_translation(TRANSLATION_SIZE),
+        &stamped_isometry.translation(),
+    );
+    write_floats(
+        response.reborrow().init_rotation(ROTATION_SIZE),
+        &stamped_isometry.rotation(),
+    );
+
     let mut buffer = Vec::new();
     capnp::serialize::write_message(&mut buffer, &message)?;
     Ok(buffer)
 }
 
-/// Deserialize a transform response into Result<StampedIsometry, String>
-/// Returns Ok(StampedIsometry) on success, or Err(error_message) on failure
-pub fn deserialize_transform_response(
-    data: &[u8],
-) -> Result<Result<StampedIsometry, String>, CommsError> {
+/// Deserialize a transform response into the request id and
+/// Result<StampedIsometry, (LookupErrorKind, String)>
+/// The result is Ok(StampedIsometry) on success, or Err((kind, error_message)) on failure
+pub fn deserialize_transform_response(data: &[u8]) -> Result<TransformResponse, CommsError> {
     let reader =
         capnp::serialize::read_message(&mut &data[..], capnp::message::ReaderOptions::new())?;
     let response = reader.get_root::<transform_response::Reader>()?;
+    check_protocol_version(response.get_protocol_version())?;
 
     let success = response.get_success();
 
-    if success {
-        let translation = {
-            let trans = response.get_translation()?;
-            [trans.get(0), trans.get(1), trans.get(2)]
-        };
-
-        let rotation = {
-            let rot = response.get_rotation()?;
-            [rot.get(0), rot.get(1), rot.get(2), rot.get(3)]
-        };
-
-        let stamped_isometry = StampedIsometry::new(translation, rotation, response.get_time_ns());
-        Ok(Ok(stamped_isometry))
+    let result = if success {
+        Ok(StampedIsometry::new(
+            read_floats::<3>(response.get_translation()?)?,
+            read_floats::<4>(response.get_rotation()?)?,
+            response.get_time_ns(),
+        ))
     } else {
         let error_message = response.get_error_message()?.to_str()?.to_string();
-        Ok(Err(error_message))
+        Err((response.get_error_kind()?.into(), error_message))
+    };
+    Ok((response.get_id(), result))
+}
+
+/// Serialize a visualization response carrying the DOT string
+pub fn serialize_visualization_response(dot: &str) -> Result<Vec<u8>, CommsError> {
+    let mut message = capnp::message::Builder::new_default();
+    let mut response = message.init_root::<visualization_response::Builder>();
+
+    response.set_protocol_version(PROTOCOL_VERSION);
+    response.set_dot(dot);
+
+    let mut buffer = Vec::new();
+    capnp::serialize::write_message(&mut buffer, &message)?;
+    Ok(buffer)
+}
+
+/// Deserialize a visualization response into the DOT string
+pub fn deserialize_visualization_response(data: &[u8]) -> Result<String, CommsError> {
+    let reader =
+        capnp::serialize::read_message(&mut &data[..], capnp::message::ReaderOptions::new())?;
+    let response = reader.get_root::<visualization_response::Reader>()?;
+    check_protocol_version(response.get_protocol_version())?;
+
+    Ok(response.get_dot()?.to_str()?.to_string())
+}
+
+/// Serialize a recent lookups response, keeping the order of `records`
+pub fn serialize_recent_lookups_response(records: &[LookupRecord]) -> Result<Vec<u8>, CommsError> {
+    let mut message = capnp::message::Builder::new_default();
+    let mut response = message.init_root::<recent_lookups_response::Builder>();
+
+    response.set_protocol_version(PROTOCOL_VERSION);
+    let mut list = response.init_records(records.len() as u32);
+    for (i, record) in records.iter().enumerate() {
+        let mut entry = list.reborrow().get(i as u32);
+        entry.set_from(&record.from);
+        entry.set_to(&record.to);
+        entry.set_time_ns(record.time);
+        match &record.error {
+            None => {
+                entry.set_error_kind(messages_capnp::TransformErrorKind::None);
+                entry.set_error_message("");
+            }
+            Some((kind, error_message)) => {
+                entry.set_error_kind((*kind).into());
+                entry.set_error_message(error_message);
+            }
+        }
     }
+
+    let mut buffer = Vec::new();
+    capnp::serialize::write_message(&mut buffer, &message)?;
+    Ok(buffer)
+}
+
+/// Deserialize a recent lookups response into its records, oldest first
+pub fn deserialize_recent_lookups_response(data: &[u8]) -> Result<Vec<LookupRecord>, CommsError> {
+    let reader =
+        capnp::serialize::read_message(&mut &data[..], capnp::message::ReaderOptions::new())?;
+    let response = reader.get_root::<recent_lookups_response::Reader>()?;
+    check_protocol_version(response.get_protocol_version())?;
+
+    response
+        .get_records()?
+        .iter()
+        .map(|entry| {
+            let error = match entry.get_error_kind()? {
+                messages_capnp::TransformErrorKind::None => None,
+                kind => Some((
+                    kind.into(),
+                    entry.get_error_message()?.to_str()?.to_string(),
+                )),
+            };
+            Ok(LookupRecord {
+                from: entry.get_from()?.to_str()?.to_string(),
+                to: entry.get_to()?.to_str()?.to_string(),
+                time: entry.get_time_ns(),
+                error,
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -181,8 +442,9 @@ mod tests {
         // Test successful response (42 nanoseconds)
         let stamped_iso = StampedIsometry::new([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 42);
 
-        let serialized = serialize_transform_response(&stamped_iso, true, "").unwrap();
-        let deserialized = deserialize_transform_response(&serialized).unwrap();
+        let serialized = serialize_transform_response(7, &stamped_iso, None).unwrap();
+        let (id, deserialized) = deserialize_transform_response(&serialized).unwrap();
+        assert_eq!(id, 7);
 
         match deserialized {
             Ok(result) => {
@@ -192,7 +454,7 @@ mod tests {
                 let rot = result.rotation();
                 assert_eq!(rot, [0.0, 0.0, 0.0, 1.0]);
             }
-            Err(e) => panic!("Expected success, got error: {}", e),
+            Err((_, e)) => panic!("Expected success, got error: {}", e),
         }
     }
 
@@ -201,12 +463,190 @@ mod tests {
         // Test error response
         let dummy = StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
 
-        let serialized = serialize_transform_response(&dummy, false, "test error").unwrap();
-        let deserialized = deserialize_transform_response(&serialized).unwrap();
+        let serialized =
+            serialize_transform_response(8, &dummy, Some((LookupErrorKind::NoPath, "test error")))
+                .unwrap();
+        let (id, deserialized) = deserialize_transform_response(&serialized).unwrap();
+        assert_eq!(id, 8);
 
         match deserialized {
             Ok(_) => panic!("Expected error, got success"),
-            Err(e) => assert_eq!(e, "test error"),
+            Err((kind, e)) => {
+                assert_eq!(kind, LookupErrorKind::NoPath);
+                assert_eq!(e, "test error");
+            }
+        }
+    }
+
+    #[test]
+    fn test_transform_history_roundtrip() {
+        let samples = vec![
+            StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            StampedIsometry::new([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 1_000_000_000),
+        ];
+
+        let serialized = serialize_transform_history(
+            "world",
+            "robot",
+            &samples,
+            messages_capnp::TransformKind::Dynamic,
+        )
+        .unwrap();
+        let (from, to, deserialized, kind) = deserialize_transform_history(&serialized).unwrap();
+
+        assert_eq!(from, "world");
+        assert_eq!(to, "robot");
+        assert_eq!(kind, messages_capnp::TransformKind::Dynamic);
+        assert_eq!(deserialized.len(), 2);
+        assert_eq!(deserialized[1].stamp(), 1_000_000_000);
+        assert_eq!(deserialized[1].translation(), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_visualization_response_roundtrip() {
+        let dot = "digraph {\n    0 [label=\"world\"]\n}";
+
+        let serialized = serialize_visualization_response(dot).unwrap();
+        let deserialized = deserialize_visualization_response(&serialized).unwrap();
+
+        assert_eq!(deserialized, dot);
+    }
+
+    #[test]
+    fn test_recent_lookups_response_roundtrip() {
+        let records = vec![
+            LookupRecord {
+                from: "world".to_string(),
+                to: "robot".to_string(),
+                time: 42,
+                error: None,
+            },
+            LookupRecord {
+                from: "world".to_string(),
+                to: "camera".to_string(),
+                time: 0,
+                error: Some((
+                    LookupErrorKind::UnknownFrame,
+                    "Frame 'camera' does not exist".to_string(),
+                )),
+            },
+        ];
+
+        let serialized = serialize_recent_lookups_response(&records).unwrap();
+        let deserialized = deserialize_recent_lookups_response(&serialized).unwrap();
+
+        assert_eq!(deserialized, records);
+    }
+
+    #[test]
+    fn test_new_transform_roundtrip_keeps_quaternion_order() {
+        // An eighth turn about z: only z and w are non-zero, so swapping
+        // the scalar to the front would show up as a different rotation.
+        let sin = std::f64::consts::FRAC_PI_8.sin();
+        let rotation = [0.0, 0.0, sin, std::f64::consts::FRAC_PI_8.cos()];
+        let stamped_iso = StampedIsometry::new([1.0, -2.0, 3.5], rotation, 1_234);
+
+        let serialized = serialize_new_transform(
+            "world",
+            "robot",
+            &stamped_iso,
+            messages_capnp::TransformKind::Static,
+        )
+        .unwrap();
+        let (from, to, deserialized, kind) = deserialize_new_transform(&serialized).unwrap();
+
+        assert_eq!((from.as_str(), to.as_str()), ("world", "robot"));
+        assert_eq!(kind, messages_capnp::TransformKind::Static);
+        assert_eq!(deserialized.stamp(), 1_234);
+        assert_eq!(deserialized.translation(), [1.0, -2.0, 3.5]);
+        for (got, expected) in deserialized.rotation().iter().zip(rotation) {
+            assert!((got - expected).abs() < 1e-12);
         }
     }
+
+    #[test]
+    fn test_short_pose_list_is_rejected() {
+        let mut message = capnp::message::Builder::new_default();
+        let mut transform = message.init_root::<new_transform::Builder>();
+        transform.set_protocol_version(PROTOCOL_VERSION);
+        transform.set_from("world");
+        transform.set_to("robot");
+        write_floats(transform.reborrow().init_translation(3), &[1.0, 2.0, 3.0]);
+        // A rotation without its w component.
+        write_floats(transform.reborrow().init_rotation(3), &[0.0, 0.0, 0.0]);
+        let mut serialized = Vec::new();
+        capnp::serialize::write_message(&mut serialized, &message).unwrap();
+
+        assert!(matches!(
+            deserialize_new_transform(&serialized),
+            Err(CommsError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_strict_deserialization_rejects_scaled_quaternion() {
+        let stamped_iso = StampedIsometry::new([5.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 0);
+        let mut serialized = serialize_new_transform(
+            "world",
+            "robot",
+            &stamped_iso,
+            messages_capnp::TransformKind::Dynamic,
+        )
+        .unwrap();
+        // Scale w from 1.0 to 2.0 on the wire, as a buggy publisher might.
+        let one = 1.0f64.to_le_bytes();
+        let pos = serialized
+            .windows(8)
+            .rposition(|w| w == one)
+            .expect("w component in payload");
+        serialized[pos..pos + 8].copy_from_slice(&2.0f64.to_le_bytes());
+
+        // Lenient by default: silently normalized.
+        let (_, _, lenient, _) = deserialize_new_transform(&serialized).unwrap();
+        assert_eq!(lenient.rotation(), [0.0, 0.0, 0.0, 1.0]);
+
+        assert!(matches!(
+            deserialize_new_transform_checked(&serialized, Some(1e-6)),
+            Err(CommsError::Buffer(schiebung::TfError::InvalidTransform(_)))
+        ));
+    }
+
+    #[test]
+    fn test_protocol_version_mismatch_is_rejected() {
+        let mut message = capnp::message::Builder::new_default();
+        let mut request = message.init_root::<transform_request::Builder>();
+        request.set_protocol_version(PROTOCOL_VERSION + 1);
+        request.set_id(3);
+        request.set_from("world");
+        request.set_to("robot");
+        let mut serialized = Vec::new();
+        capnp::serialize::write_message(&mut serialized, &message).unwrap();
+
+        let err = deserialize_transform_request(&serialized).unwrap_err();
+        assert!(matches!(
+            err,
+            CommsError::ProtocolVersionMismatch { expected, actual }
+                if expected == PROTOCOL_VERSION && actual == PROTOCOL_VERSION + 1
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Protocol version mismatch: expected {}, got {}",
+                PROTOCOL_VERSION,
+                PROTOCOL_VERSION + 1
+            )
+        );
+
+        // A peer from before the field existed reads as version 0.
+        let mut message = capnp::message::Builder::new_default();
+        message
+            .init_root::<visualization_response::Builder>()
+            .set_dot("digraph {}");
+        let mut serialized = Vec::new();
+        capnp::serialize::write_message(&mut serialized, &message).unwrap();
+        assert!(matches!(
+            deserialize_visualization_response(&serialized),
+            Err(CommsError::ProtocolVersionMismatch { actual: 0, .. })
+        ));
+    }
 }
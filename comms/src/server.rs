@@ -1,14 +1,97 @@
-use crate::config::{ZenohConfig, TRANSFORM_PUB_TOPIC};
-use crate::error::CommsError;
+use crate::config::{
+    ZenohConfig, HEARTBEAT_PUB_TOPIC, TRANSFORM_HISTORY_PUB_TOPIC, TRANSFORM_PUB_TOPIC,
+};
+use crate::error::{CommsError, LookupErrorKind};
 use log::{debug, error, info, warn};
-use schiebung::{types::StampedIsometry, BufferTree, TransformUpdate};
-use std::sync::{Arc, RwLock};
+use schiebung::{
+    types::{secs_to_nanos, StampedIsometry},
+    BufferTree, SharedBufferTree, TfError, TransformType, TransformUpdate,
+};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// What the server does with a dynamic sample whose stamp lies further in
+/// the past than the newest sample on its edge.
+///
+/// See [`TransformServer::with_time_jump_detection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeJumpPolicy {
+    /// Treat the sample as the start of a new timeline: drop the edge's
+    /// history and insert it.
+    Reset,
+    /// Drop the sample and keep the existing history.
+    Reject,
+}
+
+/// Running totals of the traffic a [`TransformServer`] has handled, see
+/// [`TransformServer::stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ServerStats {
+    /// Transform and visualization queries answered, including failed lookups.
+    pub requests_served: u64,
+    /// Transform queries answered with an error, including rate limited ones.
+    pub lookups_failed: u64,
+    /// Samples stored in the buffer from published transforms and histories.
+    pub transforms_ingested: u64,
+}
+
+/// A transform request the server answered, see
+/// [`TransformServer::recent_lookups`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupRecord {
+    pub from: String,
+    pub to: String,
+    /// Requested time in nanoseconds.
+    pub time: i64,
+    /// `None` if the lookup succeeded, otherwise why it failed.
+    pub error: Option<(LookupErrorKind, String)>,
+}
+
+/// Default number of requests kept by [`TransformServer::recent_lookups`].
+pub const DEFAULT_RECENT_LOOKUP_CAPACITY: usize = 64;
+
+/// Default period of the heartbeat a running server publishes, see
+/// [`TransformServer::with_heartbeat_interval`].
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+struct StatsCounters {
+    requests_served: AtomicU64,
+    lookups_failed: AtomicU64,
+    transforms_ingested: AtomicU64,
+}
 
 /// Server regarding Schiebung transforms
 #[derive(Clone)]
 pub struct TransformServer {
-    buffer: Arc<RwLock<BufferTree>>,
+    buffer: SharedBufferTree,
     session: zenoh::Session,
+    /// Failed transform queries per `(from, to)` pair, shown in the
+    /// visualization so failing lookups stand out.
+    lookup_failures: Arc<Mutex<HashMap<(String, String), u64>>>,
+    stats: Arc<StatsCounters>,
+    /// Last answered transform queries, oldest first, see
+    /// [`with_recent_lookup_capacity`](TransformServer::with_recent_lookup_capacity).
+    recent_lookups: Arc<Mutex<VecDeque<LookupRecord>>>,
+    recent_lookup_capacity: usize,
+    /// See [`with_quaternion_tolerance`](TransformServer::with_quaternion_tolerance).
+    quaternion_tolerance: Option<f64>,
+    /// Backward jump threshold in nanoseconds, see
+    /// [`with_time_jump_detection`](TransformServer::with_time_jump_detection).
+    time_jump: Option<(i64, TimeJumpPolicy)>,
+    /// See [`with_remote_clear`](TransformServer::with_remote_clear).
+    allow_remote_clear: bool,
+    /// Requests allowed per client and period, see
+    /// [`with_rate_limit`](TransformServer::with_rate_limit).
+    rate_limit: Option<(u32, Duration)>,
+    /// Start and request count of each client's current rate limit window.
+    rate_limit_windows: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+    /// See [`with_heartbeat_interval`](TransformServer::with_heartbeat_interval).
+    heartbeat_interval: Option<Duration>,
 }
 
 impl TransformServer {
@@ -22,7 +105,7 @@ impl TransformServer {
     /// Use this to pin the server to known endpoints (e.g. for tests or deployments
     /// where multicast discovery is unavailable).
     pub async fn with_config(config: ZenohConfig) -> Result<Self, CommsError> {
-        let buffer = Arc::new(RwLock::new(BufferTree::new()));
+        let buffer = SharedBufferTree::new(BufferTree::new());
 
         let zenoh_config = config.to_zenoh_config()?;
 
@@ -31,21 +114,163 @@ impl TransformServer {
             .map_err(|e| CommsError::Zenoh(format!("Failed to open zenoh session: {}", e)))?;
         info!("Zenoh session established in {} mode", config.mode);
 
-        Ok(Self { buffer, session })
+        Ok(Self {
+            buffer,
+            session,
+            lookup_failures: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(StatsCounters::default()),
+            recent_lookups: Arc::new(Mutex::new(VecDeque::with_capacity(
+                DEFAULT_RECENT_LOOKUP_CAPACITY,
+            ))),
+            recent_lookup_capacity: DEFAULT_RECENT_LOOKUP_CAPACITY,
+            quaternion_tolerance: None,
+            time_jump: None,
+            allow_remote_clear: false,
+            rate_limit: None,
+            rate_limit_windows: Arc::new(Mutex::new(HashMap::new())),
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+        })
+    }
+
+    /// Reject incoming transforms whose rotation quaternion norm differs
+    /// from 1 by more than `tolerance`, instead of silently normalizing them.
+    ///
+    /// Off by default. Rejected messages are logged and not inserted, which
+    /// makes a publisher sending garbage rotations visible.
+    pub fn with_quaternion_tolerance(mut self, tolerance: f64) -> Self {
+        self.quaternion_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Detect dynamic samples that go back in time by more than
+    /// `threshold_secs` relative to the newest sample on their edge.
+    ///
+    /// Off by default, in which case such samples are appended as-is and
+    /// corrupt interpolation on that edge. This typically happens when a
+    /// simulation is reset or a bag is played in a loop. Every detected jump
+    /// is logged as a warning and handled according to `policy`. Samples
+    /// that go back by less than `threshold_secs` are dropped, so the edge's
+    /// history always stays sorted.
+    pub fn with_time_jump_detection(mut self, threshold_secs: f64, policy: TimeJumpPolicy) -> Self {
+        self.time_jump = Some((secs_to_nanos(threshold_secs), policy));
+        self
+    }
+
+    /// Let clients empty the buffer with
+    /// [`TransformClient::clear_server`](crate::TransformClient::clear_server).
+    ///
+    /// Off by default, so a stray client cannot wipe a production server;
+    /// clear requests are then answered with an error. Meant for operators
+    /// who need to flush a corrupted tree without restarting the server.
+    pub fn with_remote_clear(mut self, allow: bool) -> Self {
+        self.allow_remote_clear = allow;
+        self
+    }
+
+    /// Answer at most `max_requests` transform queries per client in every
+    /// `period_secs` window.
+    ///
+    /// Off by default. Clients are told apart by the
+    /// [`client_id`](crate::TransformClient::client_id) in their requests.
+    /// Requests over the limit are answered with a
+    /// [`LookupErrorKind::RateLimited`] error without touching the buffer,
+    /// so a runaway client cannot starve the others.
+    pub fn with_rate_limit(mut self, max_requests: u32, period_secs: f64) -> Self {
+        self.rate_limit = Some((max_requests, Duration::from_secs_f64(period_secs.max(0.0))));
+        self
+    }
+
+    /// Keep the last `capacity` transform queries for
+    /// [`recent_lookups`](TransformServer::recent_lookups) instead of
+    /// [`DEFAULT_RECENT_LOOKUP_CAPACITY`]. Zero disables the log.
+    pub fn with_recent_lookup_capacity(mut self, capacity: usize) -> Self {
+        self.recent_lookup_capacity = capacity;
+        self
+    }
+
+    /// Publish the heartbeat every `interval_secs` instead of every
+    /// [`DEFAULT_HEARTBEAT_INTERVAL`]. Zero or less turns it off.
+    ///
+    /// While running, the server publishes an empty sample on
+    /// [`HEARTBEAT_PUB_TOPIC`] at this rate so clients can tell it is still
+    /// alive with [`TransformClient::server_alive`](crate::TransformClient::server_alive).
+    pub fn with_heartbeat_interval(mut self, interval_secs: f64) -> Self {
+        self.heartbeat_interval =
+            (interval_secs > 0.0).then(|| Duration::from_secs_f64(interval_secs));
+        self
     }
 
-    /// Get a reference to the underlying buffer tree
-    pub fn buffer(&self) -> Arc<RwLock<BufferTree>> {
+    /// Get a handle to the underlying buffer tree
+    ///
+    /// The buffer sits behind a tokio lock, so the request handlers wait for
+    /// it without blocking the runtime. Use `read().await`/`write().await` from
+    /// async code and `blocking_read()`/`blocking_write()` elsewhere.
+    pub fn buffer(&self) -> SharedBufferTree {
         self.buffer.clone()
     }
 
-    /// Run the transform server
+    /// Number of failed transform queries so far, per `(from, to)` pair.
+    pub fn lookup_failures(&self) -> HashMap<(String, String), u64> {
+        self.lookup_failures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// The most recently answered transform queries with their outcome,
+    /// oldest first. Clients can fetch the same list with
+    /// [`TransformClient::request_recent_lookups`](crate::TransformClient::request_recent_lookups).
+    pub fn recent_lookups(&self) -> Vec<LookupRecord> {
+        self.recent_lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Snapshot of the server's request and ingest counters.
+    pub fn stats(&self) -> ServerStats {
+        ServerStats {
+            requests_served: self.stats.requests_served.load(Ordering::Relaxed),
+            lookups_failed: self.stats.lookups_failed.load(Ordering::Relaxed),
+            transforms_ingested: self.stats.transforms_ingested.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Run the transform server until Ctrl+C
     ///
     /// The server processes incoming transforms in an unbounded loop. While this means
     /// messages could theoretically accumulate faster than they can be processed, in practice
     /// transform updates are infrequent enough that this is not a concern. If backpressure
     /// becomes necessary in the future, consider adding a bounded channel with monitoring.
     pub async fn run(&self) -> Result<(), CommsError> {
+        self.run_until(async {
+            tokio::signal::ctrl_c()
+                .await
+                .expect("Failed to listen for Ctrl+C");
+            info!("Shutdown signal received");
+        })
+        .await
+    }
+
+    /// Run the transform server until `shutdown` fires
+    ///
+    /// Same as [`run`](TransformServer::run), but stops when a value is sent
+    /// on the paired sender, or the sender is dropped, instead of on Ctrl+C.
+    /// Meant for embedding the server in a larger application or a test.
+    pub async fn run_with_shutdown(
+        &self,
+        shutdown: oneshot::Receiver<()>,
+    ) -> Result<(), CommsError> {
+        self.run_until(async {
+            let _ = shutdown.await;
+            info!("Shutdown signal received");
+        })
+        .await
+    }
+
+    async fn run_until(&self, shutdown: impl Future<Output = ()>) -> Result<(), CommsError> {
         info!("Starting schiebung server...");
 
         let subscriber = self
@@ -56,6 +281,14 @@ impl TransformServer {
 
         info!("Subscribed to topic: {}", TRANSFORM_PUB_TOPIC);
 
+        let history_subscriber = self
+            .session
+            .declare_subscriber(TRANSFORM_HISTORY_PUB_TOPIC)
+            .await
+            .map_err(|e| CommsError::Zenoh(format!("Failed to declare subscriber: {}", e)))?;
+
+        info!("Subscribed to topic: {}", TRANSFORM_HISTORY_PUB_TOPIC);
+
         let queryable = self
             .session
             .declare_queryable(crate::config::TRANSFORM_QUERY_TOPIC)
@@ -66,21 +299,60 @@ impl TransformServer {
             "Queryable registered: {}",
             crate::config::TRANSFORM_QUERY_TOPIC
         );
+
+        let visualize_queryable = self
+            .session
+            .declare_queryable(crate::config::VISUALIZE_QUERY_TOPIC)
+            .await
+            .map_err(|e| CommsError::Zenoh(format!("Failed to declare queryable: {}", e)))?;
+
+        info!(
+            "Queryable registered: {}",
+            crate::config::VISUALIZE_QUERY_TOPIC
+        );
+
+        let recent_lookups_queryable = self
+            .session
+            .declare_queryable(crate::config::RECENT_LOOKUPS_QUERY_TOPIC)
+            .await
+            .map_err(|e| CommsError::Zenoh(format!("Failed to declare queryable: {}", e)))?;
+
+        info!(
+            "Queryable registered: {}",
+            crate::config::RECENT_LOOKUPS_QUERY_TOPIC
+        );
+
+        let clear_queryable = self
+            .session
+            .declare_queryable(crate::config::CLEAR_QUERY_TOPIC)
+            .await
+            .map_err(|e| CommsError::Zenoh(format!("Failed to declare queryable: {}", e)))?;
+
+        info!("Queryable registered: {}", crate::config::CLEAR_QUERY_TOPIC);
         info!("Server is ready and processing requests");
 
-        let shutdown = async {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("Failed to listen for Ctrl+C");
-            info!("Shutdown signal received");
-        };
+        let heartbeat_task = self.heartbeat_interval.map(|period| {
+            let session = self.session.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(period);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = session.put(HEARTBEAT_PUB_TOPIC, Vec::<u8>::new()).await {
+                        error!("Failed to publish heartbeat: {}", e);
+                    }
+                }
+            })
+        });
 
         let server_sub = self.clone();
-        let subscriber_task = tokio::spawn(async move {
+        let mut subscriber_task = tokio::spawn(async move {
             loop {
                 match subscriber.recv_async().await {
                     Ok(sample) => {
-                        match server_sub.handle_new_transform(&sample.payload().to_bytes()) {
+                        match server_sub
+                            .handle_new_transform(&sample.payload().to_bytes())
+                            .await
+                        {
                             Ok(_) => debug!("Successfully processed new transform"),
                             Err(e) => error!("Error processing new transform: {}", e),
                         }
@@ -93,6 +365,27 @@ impl TransformServer {
             }
         });
 
+        let server_history = self.clone();
+        let mut history_task = tokio::spawn(async move {
+            loop {
+                match history_subscriber.recv_async().await {
+                    Ok(sample) => {
+                        match server_history
+                            .handle_transform_history(&sample.payload().to_bytes())
+                            .await
+                        {
+                            Ok(_) => debug!("Successfully processed transform history"),
+                            Err(e) => error!("Error processing transform history: {}", e),
+                        }
+                    }
+                    Err(e) => {
+                        error!("Error receiving sample: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
         let server_query = self.clone();
         let query_future = async move {
             loop {
@@ -100,7 +393,7 @@ impl TransformServer {
                     Ok(query) => {
                         let payload_data =
                             query.payload().map(|p| p.to_bytes()).unwrap_or_default();
-                        match server_query.handle_transform_query(&payload_data) {
+                        match server_query.handle_transform_query(&payload_data).await {
                             Ok(response_bytes) => {
                                 if let Err(e) = query
                                     .reply(crate::config::TRANSFORM_QUERY_TOPIC, response_bytes)
@@ -113,10 +406,11 @@ impl TransformServer {
                                 error!("Error handling transform query: {}", e);
                                 let dummy =
                                     StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+                                // The request could not be read, so its id is unknown.
                                 match crate::serializers::serialize_transform_response(
+                                    0,
                                     &dummy,
-                                    false,
-                                    &e.to_string(),
+                                    Some((LookupErrorKind::Other, &e.to_string())),
                                 ) {
                                     Ok(error_response) => {
                                         if let Err(e) = query
@@ -144,24 +438,116 @@ impl TransformServer {
             }
         };
 
+        let server_visualize = self.clone();
+        let visualize_future = async move {
+            loop {
+                match visualize_queryable.recv_async().await {
+                    Ok(query) => match server_visualize.handle_visualization_query().await {
+                        Ok(response_bytes) => {
+                            if let Err(e) = query
+                                .reply(crate::config::VISUALIZE_QUERY_TOPIC, response_bytes)
+                                .await
+                            {
+                                error!("Failed to send visualization response: {}", e);
+                            }
+                        }
+                        Err(e) => {
+                            error!("Error handling visualization query: {}", e);
+                        }
+                    },
+                    Err(e) => {
+                        error!("Error receiving visualization query: {}", e);
+                        break;
+                    }
+                }
+            }
+        };
+
+        let server_recent = self.clone();
+        let recent_lookups_future = async move {
+            loop {
+                match recent_lookups_queryable.recv_async().await {
+                    Ok(query) => match server_recent.handle_recent_lookups_query() {
+                        Ok(response_bytes) => {
+                            if let Err(e) = query
+                                .reply(crate::config::RECENT_LOOKUPS_QUERY_TOPIC, response_bytes)
+                                .await
+                            {
+                                error!("Failed to send recent lookups response: {}", e);
+                            }
+                        }
+                        Err(e) => {
+                            error!("Error handling recent lookups query: {}", e);
+                        }
+                    },
+                    Err(e) => {
+                        error!("Error receiving recent lookups query: {}", e);
+                        break;
+                    }
+                }
+            }
+        };
+
+        let server_clear = self.clone();
+        let clear_future = async move {
+            loop {
+                match clear_queryable.recv_async().await {
+                    Ok(query) => {
+                        let reply = match server_clear.handle_clear_query().await {
+                            Ok(()) => {
+                                query
+                                    .reply(crate::config::CLEAR_QUERY_TOPIC, Vec::new())
+                                    .await
+                            }
+                            Err(message) => query.reply_err(message).await,
+                        };
+                        if let Err(e) = reply {
+                            error!("Failed to send clear response: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Error receiving clear query: {}", e);
+                        break;
+                    }
+                }
+            }
+        };
+
         tokio::select! {
-            _ = subscriber_task => {
+            _ = &mut subscriber_task => {
                 warn!("Subscriber task terminated");
             },
+            _ = &mut history_task => {
+                warn!("History subscriber task terminated");
+            },
             _ = query_future => {
                 warn!("Query handler terminated");
             },
+            _ = visualize_future => {
+                warn!("Visualization handler terminated");
+            },
+            _ = recent_lookups_future => {
+                warn!("Recent lookups handler terminated");
+            },
+            _ = clear_future => {
+                warn!("Clear handler terminated");
+            },
             _ = shutdown => {
                 info!("Shutting down gracefully...");
             },
         }
+        subscriber_task.abort();
+        history_task.abort();
+        if let Some(heartbeat_task) = heartbeat_task {
+            heartbeat_task.abort();
+        }
 
         Ok(())
     }
 
-    fn handle_new_transform(&self, data: &[u8]) -> Result<(), CommsError> {
+    async fn handle_new_transform(&self, data: &[u8]) -> Result<(), CommsError> {
         let (from, to, stamped_isometry, kind) =
-            crate::serializers::deserialize_new_transform(data)?;
+            crate::serializers::deserialize_new_transform_checked(data, self.quaternion_tolerance)?;
 
         debug!(
             "Received new transform: {} -> {} at time {}",
@@ -172,13 +558,13 @@ impl TransformServer {
 
         let transform_type = kind.into();
 
-        // Handle rwlock poisoning by recovering the data
-        let mut buf = match self.buffer.write() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                warn!("Buffer rwlock was poisoned, recovering...");
-                poisoned.into_inner()
-            }
+        let mut buf = self.buffer.write().await;
+
+        let Some(stamped_isometry) = self
+            .filter_time_jumps(&mut buf, &from, &to, transform_type, vec![stamped_isometry])
+            .pop()
+        else {
+            return Ok(());
         };
 
         buf.update(&[TransformUpdate::new(
@@ -187,6 +573,9 @@ impl TransformServer {
             stamped_isometry,
             transform_type,
         )])?;
+        self.stats
+            .transforms_ingested
+            .fetch_add(1, Ordering::Relaxed);
         info!(
             "Stored transform: {} -> {} ({:?})",
             from, to, transform_type
@@ -195,36 +584,351 @@ impl TransformServer {
         Ok(())
     }
 
-    fn handle_transform_query(&self, data: &[u8]) -> Result<Vec<u8>, CommsError> {
-        let (from, to, time) = crate::serializers::deserialize_transform_request(data)?;
+    async fn handle_transform_history(&self, data: &[u8]) -> Result<(), CommsError> {
+        let (from, to, samples, kind) = crate::serializers::deserialize_transform_history_checked(
+            data,
+            self.quaternion_tolerance,
+        )?;
+
+        debug!(
+            "Received transform history: {} -> {} with {} samples",
+            from,
+            to,
+            samples.len()
+        );
+
+        let transform_type = kind.into();
+
+        let mut buf = self.buffer.write().await;
+
+        let updates: Vec<TransformUpdate> = self
+            .filter_time_jumps(&mut buf, &from, &to, transform_type, samples)
+            .into_iter()
+            .map(|sample| TransformUpdate::new(from.clone(), to.clone(), sample, transform_type))
+            .collect();
+
+        buf.update(&updates)?;
+        self.stats
+            .transforms_ingested
+            .fetch_add(updates.len() as u64, Ordering::Relaxed);
+        info!(
+            "Stored {} samples: {} -> {} ({:?})",
+            updates.len(),
+            from,
+            to,
+            transform_type
+        );
+
+        Ok(())
+    }
+
+    /// Apply the configured [`TimeJumpPolicy`] to incoming samples for
+    /// `from -> to`. Static samples and a disabled detection pass through.
+    fn filter_time_jumps(
+        &self,
+        buf: &mut BufferTree,
+        from: &str,
+        to: &str,
+        kind: TransformType,
+        samples: Vec<StampedIsometry>,
+    ) -> Vec<StampedIsometry> {
+        match self.time_jump {
+            Some((threshold, policy)) if matches!(kind, TransformType::Dynamic) => {
+                apply_time_jump_policy(buf, from, to, samples, threshold, policy)
+            }
+            _ => samples,
+        }
+    }
+
+    async fn handle_transform_query(&self, data: &[u8]) -> Result<Vec<u8>, CommsError> {
+        let (id, client_id, from, to, time) =
+            crate::serializers::deserialize_transform_request(data)?;
+        self.stats.requests_served.fetch_add(1, Ordering::Relaxed);
+
+        if !self.admit_request(&client_id) {
+            warn!("Rate limited transform query from client '{}'", client_id);
+            self.stats.lookups_failed.fetch_add(1, Ordering::Relaxed);
+            let dummy = StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], time);
+            return crate::serializers::serialize_transform_response(
+                id,
+                &dummy,
+                Some((
+                    LookupErrorKind::RateLimited,
+                    &format!("Client '{}' exceeded the request rate limit", client_id),
+                )),
+            );
+        }
 
         debug!(
             "Received transform query: {} -> {} at time {}",
             from, to, time
         );
 
-        // Handle rwlock poisoning by recovering the data
-        let buf = match self.buffer.read() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                warn!("Buffer rwlock was poisoned, recovering...");
-                poisoned.into_inner()
-            }
-        };
+        let buf = self.buffer.read().await;
+
+        let result = buf.lookup_transform(&from, &to, time).map_err(|e| {
+            let kind = match e {
+                TfError::UnknownFrame(_) => LookupErrorKind::UnknownFrame,
+                TfError::NoPath(_) | TfError::CouldNotFindTransform(_) => LookupErrorKind::NoPath,
+                TfError::AttemptedLookupInPast(_) => LookupErrorKind::LookupInPast,
+                TfError::AttemptedLookUpInFuture(_) => LookupErrorKind::LookupInFuture,
+                _ => LookupErrorKind::Other,
+            };
+            (kind, e.to_string())
+        });
 
-        match buf.lookup_transform(&from, &to, time) {
+        drop(buf);
+        self.record_lookup(LookupRecord {
+            from: from.clone(),
+            to: to.clone(),
+            time,
+            error: result.as_ref().err().cloned(),
+        });
+
+        match result {
             Ok(stamped_iso) => {
                 debug!("Found transform: {} -> {}", from, to);
-                crate::serializers::serialize_transform_response(&stamped_iso, true, "")
+                crate::serializers::serialize_transform_response(id, &stamped_iso, None)
             }
-            Err(e) => {
-                let error_msg = e.to_string();
-                error!("Transform lookup error: {}", error_msg);
+            Err((kind, error_msg)) => {
+                error!("Transform lookup error ({:?}): {}", kind, error_msg);
+                self.stats.lookups_failed.fetch_add(1, Ordering::Relaxed);
+                *self
+                    .lookup_failures
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .entry((from, to))
+                    .or_insert(0) += 1;
 
                 // Create a dummy StampedIsometry for error response
                 let dummy = StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], time);
-                crate::serializers::serialize_transform_response(&dummy, false, &error_msg)
+                crate::serializers::serialize_transform_response(
+                    id,
+                    &dummy,
+                    Some((kind, &error_msg)),
+                )
             }
         }
     }
+
+    /// Whether `client_id` may send another transform query under the rate
+    /// limit. Counts the query if so.
+    fn admit_request(&self, client_id: &str) -> bool {
+        let Some((max_requests, period)) = self.rate_limit else {
+            return true;
+        };
+        let now = Instant::now();
+        let mut windows = self
+            .rate_limit_windows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !windows.contains_key(client_id) {
+            // Forget clients whose window has run out, so the map only holds
+            // recently active ones.
+            windows.retain(|_, (start, _)| now.duration_since(*start) < period);
+        }
+        let (start, count) = windows.entry(client_id.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= period {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= max_requests {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    /// Append `record` to the recent lookups, evicting the oldest entry
+    /// once the capacity is reached.
+    fn record_lookup(&self, record: LookupRecord) {
+        if self.recent_lookup_capacity == 0 {
+            return;
+        }
+        let mut recent = self
+            .recent_lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while recent.len() >= self.recent_lookup_capacity {
+            recent.pop_front();
+        }
+        recent.push_back(record);
+    }
+
+    /// Empty the buffer if remote clearing is enabled, otherwise return the
+    /// message to reply with.
+    async fn handle_clear_query(&self) -> Result<(), String> {
+        if !self.allow_remote_clear {
+            warn!("Rejected clear request: remote clear is disabled");
+            return Err("Remote clear is disabled on this server".to_string());
+        }
+        self.buffer.write().await.clear();
+        warn!("Cleared the transform buffer on client request");
+        Ok(())
+    }
+
+    fn handle_recent_lookups_query(&self) -> Result<Vec<u8>, CommsError> {
+        debug!("Received recent lookups query");
+        crate::serializers::serialize_recent_lookups_response(&self.recent_lookups())
+    }
+
+    async fn handle_visualization_query(&self) -> Result<Vec<u8>, CommsError> {
+        debug!("Received visualization query");
+        self.stats.requests_served.fetch_add(1, Ordering::Relaxed);
+
+        let buf = self.buffer.read().await;
+
+        let dot = buf.visualize_with_lookup_failures(&self.lookup_failures());
+        crate::serializers::serialize_visualization_response(&dot)
+    }
+}
+
+/// Drop or reset around samples that jump back in time by more than
+/// `threshold` nanoseconds, comparing each sample against the newest stamp
+/// seen on the edge so far (in the buffer or earlier in `samples`).
+///
+/// On [`TimeJumpPolicy::Reset`] the edge's history is cleared and samples
+/// from before the jump in the same batch are discarded too, since they
+/// belong to the old timeline. Samples older than the newest stamp but
+/// within the threshold are always dropped, as the history only appends.
+/// Returns the samples to insert, in ascending stamp order.
+fn apply_time_jump_policy(
+    buf: &mut BufferTree,
+    from: &str,
+    to: &str,
+    samples: Vec<StampedIsometry>,
+    threshold: i64,
+    policy: TimeJumpPolicy,
+) -> Vec<StampedIsometry> {
+    let mut newest = buf.edge_latest_stamp(from, to);
+    let mut accepted = Vec::with_capacity(samples.len());
+    for sample in samples {
+        if let Some(latest) = newest {
+            if latest - sample.stamp > threshold {
+                warn!(
+                    "Time jumped backward on {} -> {}: sample at {} ns, newest at {} ns ({:?})",
+                    from, to, sample.stamp, latest, policy
+                );
+                match policy {
+                    TimeJumpPolicy::Reject => continue,
+                    TimeJumpPolicy::Reset => {
+                        buf.clear_edge_history(from, to);
+                        accepted.clear();
+                    }
+                }
+            } else if sample.stamp < latest {
+                debug!(
+                    "Dropping out-of-order sample on {} -> {}: sample at {} ns, newest at {} ns",
+                    from, to, sample.stamp, latest
+                );
+                continue;
+            }
+        }
+        newest = Some(sample.stamp);
+        accepted.push(sample);
+    }
+    accepted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schiebung::BufferConfig;
+
+    const THRESHOLD: i64 = 1_000_000_000;
+
+    fn sample(secs: f64) -> StampedIsometry {
+        StampedIsometry::from_secs([secs, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs)
+    }
+
+    fn buffer_until(secs: f64) -> BufferTree {
+        let mut buf = BufferTree::with_config(BufferConfig::default());
+        buf.update(&[
+            TransformUpdate::new("world", "robot", sample(secs - 1.0), TransformType::Dynamic),
+            TransformUpdate::new("world", "robot", sample(secs), TransformType::Dynamic),
+        ])
+        .unwrap();
+        buf
+    }
+
+    fn stamps(samples: &[StampedIsometry]) -> Vec<i64> {
+        samples.iter().map(|s| s.stamp).collect()
+    }
+
+    #[test]
+    fn test_backward_time_jump_reject() {
+        let mut buf = buffer_until(100.0);
+        let samples = vec![sample(100.5), sample(0.0), sample(99.5), sample(101.0)];
+
+        let accepted = apply_time_jump_policy(
+            &mut buf,
+            "world",
+            "robot",
+            samples,
+            THRESHOLD,
+            TimeJumpPolicy::Reject,
+        );
+
+        // The reset sample is rejected and 99.5, although within the
+        // threshold, is dropped rather than appended out of order.
+        assert_eq!(stamps(&accepted), vec![100_500_000_000, 101_000_000_000]);
+        assert_eq!(
+            buf.edge_latest_stamp("world", "robot"),
+            Some(100_000_000_000)
+        );
+
+        let updates: Vec<_> = accepted
+            .into_iter()
+            .map(|s| TransformUpdate::new("world", "robot", s, TransformType::Dynamic))
+            .collect();
+        buf.update(&updates).unwrap();
+        let sorted = buf
+            .with_edge_history("world", "robot", |history| {
+                history
+                    .iter()
+                    .zip(history.iter().skip(1))
+                    .all(|(a, b)| a.stamp <= b.stamp)
+            })
+            .unwrap();
+        assert!(sorted);
+    }
+
+    #[test]
+    fn test_backward_time_jump_reset() {
+        let mut buf = buffer_until(100.0);
+        let samples = vec![sample(100.5), sample(0.0), sample(1.0)];
+
+        let accepted = apply_time_jump_policy(
+            &mut buf,
+            "world",
+            "robot",
+            samples,
+            THRESHOLD,
+            TimeJumpPolicy::Reset,
+        );
+
+        assert_eq!(stamps(&accepted), vec![0, 1_000_000_000]);
+        assert_eq!(buf.edge_latest_stamp("world", "robot"), None);
+        let updates: Vec<_> = accepted
+            .into_iter()
+            .map(|s| TransformUpdate::new("world", "robot", s, TransformType::Dynamic))
+            .collect();
+        buf.update(&updates).unwrap();
+        let result = buf.lookup_transform("world", "robot", 500_000_000).unwrap();
+        assert!((result.translation()[0] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_jump_ignores_unknown_edge() {
+        let mut buf = BufferTree::with_config(BufferConfig::default());
+        let accepted = apply_time_jump_policy(
+            &mut buf,
+            "world",
+            "robot",
+            vec![sample(5.0), sample(6.0)],
+            THRESHOLD,
+            TimeJumpPolicy::Reject,
+        );
+        assert_eq!(stamps(&accepted), vec![5_000_000_000, 6_000_000_000]);
+    }
 }
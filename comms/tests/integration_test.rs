@@ -1,28 +1,67 @@
-use comms::{TransformClient, ZenohConfig};
+use comms::{ClientConfig, CommsError, LengthUnit, LookupErrorKind, TransformClient, ZenohConfig};
 use schiebung::types::{StampedIsometry, TransformType};
+use schiebung::{BufferTree, TransformUpdate};
 use std::time::Duration;
 
 /// Pin both ends to a localhost TCP endpoint and disable multicast scouting,
 /// so the test does not depend on UDP multicast (which CI runners and many
 /// corporate networks block).
+///
+/// Each test gets its own port so a server from a previous test that is
+/// still tearing down cannot answer the next test's queries.
 const TEST_ENDPOINT: &str = "tcp/127.0.0.1:17447";
+const VISUALIZE_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17448";
+const SEND_BUFFER_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17449";
+const HISTORY_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17450";
+const TIMEOUT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17451";
+const RETRY_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17452";
+const REPEAT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17453";
+const COLLISION_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17454";
+const LOOKUP_FAILURE_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17455";
+const SUBSCRIBE_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17456";
+const RECONNECT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17457";
+const LOOKUP_ERROR_KIND_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17458";
+const SHUTDOWN_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17460";
+const RECENT_LOOKUPS_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17461";
+const CLEAR_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17462";
+const CLEAR_DISABLED_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17463";
+const CONCURRENT_REQUESTS_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17464";
+const STREAM_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17465";
+const RATE_LIMIT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17466";
+const HEARTBEAT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17467";
+const LENGTH_UNIT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17468";
+const REPEAT_RECONNECT_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17469";
 
-fn server_config() -> ZenohConfig {
+fn server_config(endpoint: &str) -> ZenohConfig {
     ZenohConfig {
-        listen: vec![TEST_ENDPOINT.to_string()],
+        listen: vec![endpoint.to_string()],
         multicast_scouting: false,
         ..ZenohConfig::default()
     }
 }
 
-fn client_config() -> ZenohConfig {
+fn client_config(endpoint: &str) -> ZenohConfig {
     ZenohConfig {
-        connect: vec![TEST_ENDPOINT.to_string()],
+        connect: vec![endpoint.to_string()],
         multicast_scouting: false,
         ..ZenohConfig::default()
     }
 }
 
+/// Spawn a `TransformServer` listening on `endpoint` in the background.
+fn spawn_server(endpoint: &'static str) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        match comms::server::TransformServer::with_config(server_config(endpoint)).await {
+            Ok(server) => {
+                if let Err(e) = server.run().await {
+                    eprintln!("Server error: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to init server: {}", e),
+        }
+    })
+}
+
 /// Send a transform and poll for it via query, retrying because zenoh's
 /// best-effort `put` can race the publisher's view of the subscriber set.
 /// If the put landed before the subscriber was matched it is silently dropped,
@@ -76,21 +115,12 @@ async fn test_publish_and_query_transform() {
         .ok();
 
     // Spawn the server in the background
-    let server_handle = tokio::spawn(async {
-        match comms::server::TransformServer::with_config(server_config()).await {
-            Ok(server) => {
-                if let Err(e) = server.run().await {
-                    eprintln!("Server error: {}", e);
-                }
-            }
-            Err(e) => eprintln!("Failed to init server: {}", e),
-        }
-    });
+    let server_handle = spawn_server(TEST_ENDPOINT);
 
     // Give the server a moment to bind its listener.
     tokio::time::sleep(Duration::from_millis(200)).await;
 
-    let client = TransformClient::with_config(client_config())
+    let client = TransformClient::with_config(client_config(TEST_ENDPOINT))
         .await
         .expect("Failed to create client");
 
@@ -123,3 +153,954 @@ async fn test_publish_and_query_transform() {
 
     server_handle.abort();
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_request_visualization() {
+    env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Info)
+        .try_init()
+        .ok();
+
+    let server_handle = spawn_server(VISUALIZE_TEST_ENDPOINT);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(VISUALIZE_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    let t1 = StampedIsometry::new([0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0], 0);
+    publish_and_wait(&client, "world", "robot_base", t1, |r| {
+        (r.translation()[2] - 1.0).abs() < 1e-6
+    })
+    .await;
+
+    let dot = client
+        .request_visualization()
+        .await
+        .expect("Visualization query failed");
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("label=\"world\""));
+    assert!(dot.contains("label=\"robot_base\""));
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_buffer() {
+    env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Info)
+        .try_init()
+        .ok();
+
+    let server_handle = spawn_server(SEND_BUFFER_TEST_ENDPOINT);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(SEND_BUFFER_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    let mut buffer = BufferTree::new();
+    buffer
+        .update(&[
+            TransformUpdate::new(
+                "world",
+                "robot_base",
+                StampedIsometry::new([0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            ),
+            TransformUpdate::new(
+                "robot_base",
+                "tool",
+                StampedIsometry::new([0.5, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            ),
+        ])
+        .unwrap();
+
+    // Same retry rationale as `publish_and_wait`: early puts can be dropped
+    // before the server's subscriber is matched, so re-send until every edge
+    // is answerable.
+    let mut answered = false;
+    for _ in 0..20 {
+        client
+            .send_buffer(&buffer)
+            .await
+            .expect("send_buffer failed");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let base = client.request_transform("world", "robot_base", 0).await;
+        let tool = client.request_transform("robot_base", "tool", 0).await;
+        if let (Ok(base), Ok(tool)) = (base, tool) {
+            assert!((base.translation()[2] - 1.0).abs() < 1e-6);
+            assert!((tool.translation()[0] - 0.5).abs() < 1e-6);
+            answered = true;
+            break;
+        }
+    }
+    assert!(
+        answered,
+        "server never answered lookups for every sent edge"
+    );
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_transform_history_interpolates_on_server() {
+    env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Info)
+        .try_init()
+        .ok();
+
+    let server_handle = spawn_server(HISTORY_TEST_ENDPOINT);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(HISTORY_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    // Each attempt uses a later time window so batches that did land earlier
+    // keep the server's history ordered.
+    let mut interpolated = None;
+    for attempt in 0..20i64 {
+        let start = attempt * 2_000_000_000;
+        let samples = [
+            StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], start),
+            StampedIsometry::new([2.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], start + 1_000_000_000),
+        ];
+        client
+            .send_transform_history("world", "robot", &samples, TransformType::Dynamic)
+            .await
+            .expect("send_transform_history failed");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        if let Ok(r) = client
+            .request_transform("world", "robot", start + 500_000_000)
+            .await
+        {
+            interpolated = Some(r);
+            break;
+        }
+    }
+
+    let result = interpolated.expect("server never interpolated the sent history");
+    assert!((result.translation()[0] - 1.0).abs() < 1e-6);
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_config_request_timeout() {
+    let config: ClientConfig = serde_json::from_str(&format!(
+        r#"{{
+            "zenoh": {{ "connect": ["{}"], "multicast_scouting": false }},
+            "request_timeout": 0.5
+        }}"#,
+        TIMEOUT_TEST_ENDPOINT
+    ))
+    .expect("Failed to parse client config");
+    assert_eq!(config.request_timeout(), Duration::from_millis(500));
+
+    // A queryable that accepts transform queries but never answers them.
+    let silent = zenoh::open(
+        server_config(TIMEOUT_TEST_ENDPOINT)
+            .to_zenoh_config()
+            .unwrap(),
+    )
+    .await
+    .expect("Failed to open zenoh session");
+    let queryable = silent
+        .declare_queryable(comms::config::TRANSFORM_QUERY_TOPIC)
+        .await
+        .expect("Failed to declare queryable");
+    let held_queries = tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok(query) = queryable.recv_async().await {
+            held.push(query);
+        }
+    });
+
+    let client = TransformClient::with_client_config(config)
+        .await
+        .expect("Failed to create client");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let start = std::time::Instant::now();
+    let result = client.request_transform("world", "robot", 0).await;
+    let elapsed = start.elapsed();
+
+    assert!(result.is_err(), "expected the unanswered query to fail");
+    assert!(
+        elapsed < Duration::from_secs(3),
+        "request took {:?}, timeout was not applied",
+        elapsed
+    );
+
+    held_queries.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_request_transform_retry() {
+    env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Info)
+        .try_init()
+        .ok();
+
+    let server = comms::server::TransformServer::with_config(server_config(RETRY_TEST_ENDPOINT))
+        .await
+        .expect("Failed to init server");
+    let buffer = server.buffer();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_client_config(ClientConfig {
+        zenoh: client_config(RETRY_TEST_ENDPOINT),
+        max_attempts: 8,
+        initial_delay: 0.2,
+        ..ClientConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+
+    // Attempts go out at roughly 0s, 0.2s, 0.6s, 1.4s, ... so writing at
+    // 0.4s lets the first two fail before the transform exists.
+    let publisher = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        buffer
+            .write()
+            .await
+            .update(&[TransformUpdate::new(
+                "world",
+                "robot",
+                StampedIsometry::new([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            )])
+            .unwrap();
+    });
+
+    let result = client
+        .request_transform_retry("world", "robot", 0)
+        .await
+        .expect("transform should be returned once published");
+    assert_eq!(result.translation(), [1.0, 2.0, 3.0]);
+
+    publisher.await.unwrap();
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_repeat_handle_stops_on_drop() {
+    let server = comms::server::TransformServer::with_config(server_config(REPEAT_TEST_ENDPOINT))
+        .await
+        .expect("Failed to init server");
+    let buffer = server.buffer();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(REPEAT_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    let buffer = &buffer;
+    let sample_count = || async move {
+        buffer
+            .read()
+            .await
+            .iter_edges()
+            .find(|edge| edge.from == "world" && edge.to == "robot")
+            .map_or(0, |edge| edge.samples.len())
+    };
+
+    let handle = client
+        .start_repeating(
+            "world",
+            "robot",
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Dynamic,
+            50.0,
+        )
+        .expect("Failed to start repeating");
+
+    let mut received = false;
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if sample_count().await >= 3 {
+            received = true;
+            break;
+        }
+    }
+    assert!(received, "server never received the repeated transform");
+
+    drop(handle);
+    // Let anything already in flight land before taking the reference count.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let after_drop = sample_count().await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        sample_count().await,
+        after_drop,
+        "publishing continued after drop"
+    );
+
+    assert!(client
+        .start_repeating(
+            "world",
+            "robot",
+            StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Dynamic,
+            0.0,
+        )
+        .is_err());
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_server_endpoint_collision_is_an_error() {
+    let _first =
+        comms::server::TransformServer::with_config(server_config(COLLISION_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init first server");
+
+    // A second server cannot bind the same endpoint; construction must
+    // report that instead of panicking.
+    let second =
+        comms::server::TransformServer::with_config(server_config(COLLISION_TEST_ENDPOINT)).await;
+    assert!(matches!(second, Err(comms::CommsError::Zenoh(_))));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_failed_lookups_show_in_visualization() {
+    let server_handle = spawn_server(LOOKUP_FAILURE_TEST_ENDPOINT);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(LOOKUP_FAILURE_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    let t1 = StampedIsometry::new([0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0], 0);
+    publish_and_wait(&client, "world", "robot_base", t1, |r| {
+        (r.translation()[2] - 1.0).abs() < 1e-6
+    })
+    .await;
+
+    let dot = client
+        .request_visualization()
+        .await
+        .expect("Visualization query failed");
+    assert!(!dot.contains("failed lookups"));
+
+    assert!(client
+        .request_transform("world", "camera", 0)
+        .await
+        .is_err());
+
+    let dot = client
+        .request_visualization()
+        .await
+        .expect("Visualization query failed");
+    assert!(dot.contains("\"camera\" [label=\"camera\", style=dashed]"));
+    assert!(dot.contains("1 failed lookups"));
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_transforms_filters_by_glob() {
+    // The subscriber listens and the publisher connects to it directly; no
+    // server is involved.
+    let subscriber = TransformClient::with_config(server_config(SUBSCRIBE_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create subscriber");
+    let publisher = TransformClient::with_config(client_config(SUBSCRIBE_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create publisher");
+
+    let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let _subscription = subscriber
+        .subscribe_transforms(Some("arm/*"), move |update| {
+            sink.lock().unwrap().push(update.to);
+        })
+        .await
+        .expect("Failed to subscribe");
+
+    let t = StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+    // Publish until the matching frame arrives; early puts may be dropped
+    // before the subscriber is matched.
+    for _ in 0..50 {
+        for to in ["base_link", "arm/link_1", "camera"] {
+            publisher
+                .send_transform("world", to, t.clone(), TransformType::Static)
+                .await
+                .expect("send_transform failed");
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if !received.lock().unwrap().is_empty() {
+            break;
+        }
+    }
+
+    let received = received.lock().unwrap();
+    assert!(
+        !received.is_empty(),
+        "no matching transform reached the callback"
+    );
+    assert!(received.iter().all(|to| to == "arm/link_1"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_reconnects_after_session_closed() {
+    let server_handle = spawn_server(RECONNECT_TEST_ENDPOINT);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(RECONNECT_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+    assert!(client.is_connected());
+
+    client.close().await.expect("Failed to close session");
+    assert!(!client.is_connected());
+
+    // The next call reopens the session instead of failing.
+    let t1 = StampedIsometry::new([0.0, 0.0, 3.0], [0.0, 0.0, 0.0, 1.0], 0);
+    publish_and_wait(&client, "world", "robot", t1, |r| {
+        (r.translation()[2] - 3.0).abs() < 1e-6
+    })
+    .await;
+    assert!(client.is_connected());
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_repeating_survives_reconnect() {
+    let server =
+        comms::server::TransformServer::with_config(server_config(REPEAT_RECONNECT_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init server");
+    let buffer = server.buffer();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(REPEAT_RECONNECT_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    let buffer = &buffer;
+    let latest_stamp = || async move {
+        buffer
+            .read()
+            .await
+            .edge_latest_stamp("world", "robot")
+            .unwrap_or(0)
+    };
+
+    let _handle = client
+        .start_repeating(
+            "world",
+            "robot",
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Dynamic,
+            50.0,
+        )
+        .expect("Failed to start repeating");
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(
+        latest_stamp().await > 0,
+        "server never received the repeated transform"
+    );
+
+    client.close().await.expect("Failed to close session");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let after_close = latest_stamp().await;
+
+    // The repeat task picks up the reopened session on its next tick.
+    let mut resumed = false;
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if latest_stamp().await > after_close {
+            resumed = true;
+            break;
+        }
+    }
+    assert!(
+        resumed,
+        "publishing did not resume after the session was closed"
+    );
+    assert!(client.is_connected());
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_request_transform_reports_error_kind() {
+    let server =
+        comms::server::TransformServer::with_config(server_config(LOOKUP_ERROR_KIND_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init server");
+    let sample = |x: f64, secs: i64| {
+        StampedIsometry::new([x, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs * 1_000_000_000)
+    };
+    server
+        .buffer()
+        .write()
+        .await
+        .update(&[
+            TransformUpdate::new("world", "robot", sample(0.0, 1), TransformType::Dynamic),
+            TransformUpdate::new("world", "robot", sample(1.0, 2), TransformType::Dynamic),
+            TransformUpdate::new("map", "dock", sample(0.0, 0), TransformType::Static),
+        ])
+        .unwrap();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(LOOKUP_ERROR_KIND_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    let cases = [
+        (
+            "world",
+            "camera",
+            1_500_000_000,
+            LookupErrorKind::UnknownFrame,
+        ),
+        ("world", "dock", 1_500_000_000, LookupErrorKind::NoPath),
+        ("world", "robot", 0, LookupErrorKind::LookupInPast),
+        (
+            "world",
+            "robot",
+            3_000_000_000,
+            LookupErrorKind::LookupInFuture,
+        ),
+    ];
+    for (from, to, time, expected) in cases {
+        match client.request_transform(from, to, time).await {
+            Err(CommsError::Lookup { kind, .. }) => {
+                assert_eq!(kind, expected, "{} -> {} at {}", from, to, time)
+            }
+            other => panic!("{} -> {} at {}: unexpected {:?}", from, to, time, other),
+        }
+    }
+    assert!(client
+        .request_transform("world", "robot", 1_500_000_000)
+        .await
+        .is_ok());
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_run_with_shutdown_returns_promptly() {
+    let server = comms::server::TransformServer::with_config(server_config(SHUTDOWN_TEST_ENDPOINT))
+        .await
+        .expect("Failed to init server");
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let server_handle = tokio::spawn(async move { server.run_with_shutdown(shutdown_rx).await });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(SHUTDOWN_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+    let t = StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+    publish_and_wait(&client, "world", "robot", t, |r| {
+        (r.translation()[0] - 1.0).abs() < 1e-6
+    })
+    .await;
+    assert!(!server_handle.is_finished());
+
+    shutdown_tx.send(()).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(2), server_handle)
+        .await
+        .expect("Server did not stop after the shutdown signal")
+        .expect("Server task panicked");
+    assert!(result.is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_recent_lookups_record_served_requests() {
+    let server =
+        comms::server::TransformServer::with_config(server_config(RECENT_LOOKUPS_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init server")
+            .with_recent_lookup_capacity(2);
+    server
+        .buffer()
+        .write()
+        .await
+        .update(&[TransformUpdate::new(
+            "world",
+            "robot",
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Static,
+        )])
+        .unwrap();
+    let server_clone = server.clone();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server_clone.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(RECENT_LOOKUPS_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+    assert!(client
+        .request_recent_lookups()
+        .await
+        .expect("Failed to request recent lookups")
+        .is_empty());
+
+    // Three requests against a capacity of two: the first one is evicted.
+    client.request_transform("world", "robot", 1).await.unwrap();
+    client.request_transform("world", "robot", 2).await.unwrap();
+    assert!(client
+        .request_transform("world", "camera", 3)
+        .await
+        .is_err());
+
+    let records = client
+        .request_recent_lookups()
+        .await
+        .expect("Failed to request recent lookups");
+    assert_eq!(records, server.recent_lookups());
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        (
+            records[0].from.as_str(),
+            records[0].to.as_str(),
+            records[0].time
+        ),
+        ("world", "robot", 2)
+    );
+    assert!(records[0].error.is_none());
+    assert_eq!(
+        (
+            records[1].from.as_str(),
+            records[1].to.as_str(),
+            records[1].time
+        ),
+        ("world", "camera", 3)
+    );
+    assert!(matches!(
+        records[1].error,
+        Some((LookupErrorKind::UnknownFrame, _))
+    ));
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_clear_server_removes_frames() {
+    let server = comms::server::TransformServer::with_config(server_config(CLEAR_TEST_ENDPOINT))
+        .await
+        .expect("Failed to init server")
+        .with_remote_clear(true);
+    let server_clone = server.clone();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server_clone.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(CLEAR_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+    let t = StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+    publish_and_wait(&client, "world", "robot", t, |r| {
+        (r.translation()[0] - 1.0).abs() < 1e-6
+    })
+    .await;
+
+    client.clear_server().await.expect("Failed to clear server");
+
+    assert!(!server.buffer().read().await.has_frame("world"));
+    assert!(matches!(
+        client.request_transform("world", "robot", 0).await,
+        Err(CommsError::Lookup {
+            kind: LookupErrorKind::UnknownFrame,
+            ..
+        })
+    ));
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_clear_server_disabled_by_default() {
+    let server =
+        comms::server::TransformServer::with_config(server_config(CLEAR_DISABLED_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init server");
+    server
+        .buffer()
+        .write()
+        .await
+        .update(&[TransformUpdate::new(
+            "world",
+            "robot",
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Static,
+        )])
+        .unwrap();
+    let server_clone = server.clone();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server_clone.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(CLEAR_DISABLED_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    assert!(matches!(
+        client.clear_server().await,
+        Err(CommsError::Zenoh(message)) if message.contains("disabled")
+    ));
+    assert!(server.buffer().read().await.has_frame("world"));
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_concurrent_requests_get_their_own_responses() {
+    let server = comms::server::TransformServer::with_config(server_config(
+        CONCURRENT_REQUESTS_TEST_ENDPOINT,
+    ))
+    .await
+    .expect("Failed to init server");
+    server
+        .buffer()
+        .write()
+        .await
+        .update(&[
+            TransformUpdate::new(
+                "world",
+                "left",
+                StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            ),
+            TransformUpdate::new(
+                "world",
+                "right",
+                StampedIsometry::new([-1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            ),
+        ])
+        .unwrap();
+    let server_clone = server.clone();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server_clone.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_config(client_config(CONCURRENT_REQUESTS_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+
+    // Both requests are in flight at once on the same client.
+    let (left, right) = tokio::join!(
+        client.request_transform("world", "left", 0),
+        client.request_transform("world", "right", 0),
+    );
+    assert_eq!(left.expect("left request failed").translation()[0], 1.0);
+    assert_eq!(right.expect("right request failed").translation()[0], -1.0);
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_transform_stream_yields_published_transforms() {
+    use futures::StreamExt;
+
+    let subscriber = TransformClient::with_config(server_config(STREAM_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create subscriber");
+    let publisher = TransformClient::with_config(client_config(STREAM_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create publisher");
+    let mut stream = Box::pin(
+        subscriber
+            .transform_stream()
+            .await
+            .expect("Failed to open stream"),
+    );
+
+    // Keep publishing in the background; early puts may be dropped before
+    // the subscriber is matched.
+    let publish = tokio::spawn(async move {
+        for i in 0..50 {
+            let t = StampedIsometry::new([i as f64, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], i);
+            publisher
+                .send_transform("world", "robot", t, TransformType::Dynamic)
+                .await
+                .expect("send_transform failed");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+
+    let received: Vec<TransformUpdate> =
+        tokio::time::timeout(Duration::from_secs(5), stream.as_mut().take(3).collect())
+            .await
+            .expect("stream did not yield three transforms");
+    publish.abort();
+
+    assert!(received
+        .iter()
+        .all(|update| update.from == "world" && update.to == "robot"));
+    // Published in order, so the stamps increase.
+    assert!(received
+        .windows(2)
+        .all(|pair| pair[0].stamped_isometry.stamp() < pair[1].stamped_isometry.stamp()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rate_limit_throttles_each_client() {
+    let server =
+        comms::server::TransformServer::with_config(server_config(RATE_LIMIT_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init server")
+            .with_rate_limit(3, 60.0);
+    server
+        .buffer()
+        .write()
+        .await
+        .update(&[TransformUpdate::new(
+            "world",
+            "robot",
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Static,
+        )])
+        .unwrap();
+    let server_clone = server.clone();
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server_clone.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let noisy = TransformClient::with_client_config(ClientConfig {
+        zenoh: client_config(RATE_LIMIT_TEST_ENDPOINT),
+        client_id: Some("noisy".to_string()),
+        ..ClientConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+    let quiet = TransformClient::with_config(client_config(RATE_LIMIT_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+    assert_eq!(noisy.client_id(), "noisy");
+    assert_ne!(quiet.client_id(), noisy.client_id());
+
+    for _ in 0..3 {
+        noisy
+            .request_transform("world", "robot", 0)
+            .await
+            .expect("request within the limit failed");
+    }
+    for _ in 0..2 {
+        assert!(matches!(
+            noisy.request_transform("world", "robot", 0).await,
+            Err(CommsError::Lookup {
+                kind: LookupErrorKind::RateLimited,
+                ..
+            })
+        ));
+    }
+
+    // The limit is per client.
+    let result = quiet
+        .request_transform("world", "robot", 0)
+        .await
+        .expect("other client was throttled");
+    assert_eq!(result.translation()[0], 1.0);
+
+    server_handle.abort();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_server_alive_follows_heartbeat() {
+    let server =
+        comms::server::TransformServer::with_config(server_config(HEARTBEAT_TEST_ENDPOINT))
+            .await
+            .expect("Failed to init server")
+            .with_heartbeat_interval(0.1);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let server_handle = tokio::spawn(async move { server.run_with_shutdown(shutdown_rx).await });
+
+    let client = TransformClient::with_config(client_config(HEARTBEAT_TEST_ENDPOINT))
+        .await
+        .expect("Failed to create client");
+    let within = Duration::from_millis(500);
+
+    let mut alive = false;
+    for _ in 0..50 {
+        if client.server_alive(within) {
+            alive = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(alive, "no heartbeat received from the running server");
+
+    shutdown_tx.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(2), server_handle)
+        .await
+        .expect("Server did not stop after the shutdown signal")
+        .expect("Server task panicked")
+        .unwrap();
+    tokio::time::sleep(within * 2).await;
+    assert!(!client.server_alive(within));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_millimeter_client_stores_meters() {
+    let server_handle = spawn_server(LENGTH_UNIT_TEST_ENDPOINT);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = TransformClient::with_client_config(ClientConfig {
+        zenoh: client_config(LENGTH_UNIT_TEST_ENDPOINT),
+        length_unit: LengthUnit::Millimeters,
+        ..ClientConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+
+    let t = StampedIsometry::new([1500.0, -20.0, 3.0], [0.0, 0.0, 0.0, 1.0], 0);
+    let result = publish_and_wait(&client, "world", "sensor", t, |r| {
+        (r.translation()[0] - 1.5).abs() < 1e-9
+    })
+    .await;
+    assert!((result.translation()[1] + 0.02).abs() < 1e-9);
+    assert!((result.translation()[2] - 0.003).abs() < 1e-9);
+
+    server_handle.abort();
+}
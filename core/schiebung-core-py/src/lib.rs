@@ -4,6 +4,8 @@ use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyFloat, PyType};
 use pyo3::PyTypeInfo;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 /// Resolve a Python `stamp` argument to nanoseconds.
 ///
@@ -20,7 +22,7 @@ use pyo3::PyTypeInfo;
 fn stamp_to_ns(stamp: &Bound<'_, PyAny>) -> PyResult<i64> {
     if PyFloat::is_type_of(stamp) {
         let secs: f64 = stamp.extract()?;
-        Ok((secs * 1_000_000_000.0) as i64)
+        Ok(secs_to_nanos(secs))
     } else if let Ok(ns) = stamp.extract::<i64>() {
         Ok(ns)
     } else {
@@ -46,7 +48,7 @@ fn homogeneous_matrix(iso: &CoreStampedIsometry) -> Array2<f64> {
 }
 
 use ::schiebung::{
-    BufferObserver as CoreBufferObserver, BufferTree as CoreBufferTree,
+    secs_to_nanos, BufferObserver as CoreBufferObserver, BufferTree as CoreBufferTree,
     FormatLoader as CoreFormatLoader, StampedIsometry as CoreStampedIsometry,
     TfError as CoreTfError, TransformType as CoreTransformType,
     TransformUpdate as CoreTransformUpdate, UrdfLoader as CoreUrdfLoader,
@@ -60,10 +62,20 @@ pub enum TfError {
     AttemptedLookupInPast,
     /// Error due ti the transform not yet being available.
     AttemptedLookUpInFuture,
-    /// There is no path between the from and to frame.
+    /// The transform could not be computed, e.g. an edge has no samples.
     CouldNotFindTransform,
+    /// A requested frame was never added to the buffer.
+    UnknownFrame,
+    /// Both frames are known, but no path connects them.
+    NoPath,
     /// The graph is cyclic or the target has multiple incoming edges.
     InvalidGraph,
+    /// The lookup time falls in a gap between samples wider than the configured maximum.
+    ExtrapolationGap,
+    /// A transform received from outside is malformed (e.g. unnormalized quaternion).
+    InvalidTransform,
+    /// The edge was locked with set_static and rejects further updates.
+    LockedTransform,
     /// Error loading or parsing a file format (URDF, USD, etc.)
     LoaderError,
 }
@@ -74,30 +86,38 @@ impl From<CoreTfError> for TfError {
             CoreTfError::AttemptedLookupInPast(_) => TfError::AttemptedLookupInPast,
             CoreTfError::AttemptedLookUpInFuture(_) => TfError::AttemptedLookUpInFuture,
             CoreTfError::CouldNotFindTransform(_) => TfError::CouldNotFindTransform,
+            CoreTfError::UnknownFrame(_) => TfError::UnknownFrame,
+            CoreTfError::NoPath(_) => TfError::NoPath,
             CoreTfError::InvalidGraph(_) => TfError::InvalidGraph,
+            CoreTfError::ExtrapolationGap(_) => TfError::ExtrapolationGap,
+            CoreTfError::InvalidTransform(_) => TfError::InvalidTransform,
+            CoreTfError::LockedTransform(_) => TfError::LockedTransform,
             CoreTfError::LoaderError(_) => TfError::LoaderError,
         }
     }
 }
 
 fn core_err_to_pyerr(err: CoreTfError) -> PyErr {
-    match &err {
-        CoreTfError::AttemptedLookupInPast(msg) => {
-            PyValueError::new_err(format!("TfError.AttemptedLookupInPast: {}", msg))
-        }
-        CoreTfError::AttemptedLookUpInFuture(msg) => {
-            PyValueError::new_err(format!("TfError.AttemptedLookUpInFuture: {}", msg))
-        }
-        CoreTfError::CouldNotFindTransform(msg) => {
-            PyValueError::new_err(format!("TfError.CouldNotFindTransform: {}", msg))
-        }
-        CoreTfError::InvalidGraph(msg) => {
-            PyValueError::new_err(format!("TfError.InvalidGraph: {}", msg))
-        }
-        CoreTfError::LoaderError(msg) => {
-            PyValueError::new_err(format!("TfError.LoaderError: {}", msg))
-        }
-    }
+    // The core Display impl already renders `TfError.<Variant>: <message>`.
+    PyValueError::new_err(err.to_string())
+}
+
+/// Convert the `(from, to, stamped_isometry, kind)` tuples taken by the
+/// batch update methods into core updates.
+fn to_core_updates(
+    updates: Vec<(String, String, StampedIsometry, TransformType)>,
+) -> Vec<CoreTransformUpdate> {
+    updates
+        .into_iter()
+        .map(|(from, to, stamped_isometry, kind)| {
+            let core_iso = CoreStampedIsometry::new(
+                stamped_isometry.translation(),
+                stamped_isometry.rotation(),
+                stamped_isometry.stamp(),
+            );
+            CoreTransformUpdate::new(from, to, core_iso, kind.into())
+        })
+        .collect()
 }
 
 impl TfError {
@@ -106,7 +126,12 @@ impl TfError {
             TfError::AttemptedLookupInPast => "TfError.AttemptedLookupInPast".to_string(),
             TfError::AttemptedLookUpInFuture => "TfError.AttemptedLookUpInFuture".to_string(),
             TfError::CouldNotFindTransform => "TfError.CouldNotFindTransform".to_string(),
+            TfError::UnknownFrame => "TfError.UnknownFrame".to_string(),
+            TfError::NoPath => "TfError.NoPath".to_string(),
             TfError::InvalidGraph => "TfError.InvalidGraph".to_string(),
+            TfError::ExtrapolationGap => "TfError.ExtrapolationGap".to_string(),
+            TfError::InvalidTransform => "TfError.InvalidTransform".to_string(),
+            TfError::LockedTransform => "TfError.LockedTransform".to_string(),
             TfError::LoaderError => "TfError.LoaderError".to_string(),
         }
     }
@@ -337,6 +362,13 @@ impl StampedIsometry {
         self.inner.stamp()
     }
 
+    /// Get the timestamp in nanoseconds since Unix epoch
+    ///
+    /// Alias of `stamp()` that spells out the unit.
+    fn stamp_ns(&self) -> i64 {
+        self.inner.stamp()
+    }
+
     /// Get the timestamp in seconds as float
     fn stamp_secs(&self) -> f64 {
         self.inner.stamp_secs()
@@ -362,6 +394,12 @@ impl StampedIsometry {
         self.inner.euler_angles()
     }
 
+    /// Get the rotation as `(axis, angle)`: a unit axis `[x, y, z]` and the
+    /// angle in radians. The axis is `[1, 0, 0]` for the identity rotation.
+    fn axis_angle(&self) -> ([f64; 3], f64) {
+        self.inner.axis_angle()
+    }
+
     fn __repr__(&self) -> String {
         format!("{}", self.inner)
     }
@@ -372,11 +410,34 @@ impl StampedIsometry {
 /// allowing Python functions to be registered as observers
 struct PyBufferObserver {
     callback: Py<PyAny>,
+    /// In strict mode, where a raised exception is parked for the buffer to
+    /// re-raise; `None` logs and continues.
+    error_slot: Option<Arc<Mutex<Option<PyErr>>>>,
 }
 
 impl PyBufferObserver {
-    fn new(callback: Py<PyAny>) -> Self {
-        PyBufferObserver { callback }
+    fn new(callback: Py<PyAny>, error_slot: Option<Arc<Mutex<Option<PyErr>>>>) -> Self {
+        PyBufferObserver {
+            callback,
+            error_slot,
+        }
+    }
+
+    /// Park `err` for the buffer in strict mode (keeping the first one if
+    /// several callbacks fail), otherwise print it.
+    fn report(&self, py: Python<'_>, err: PyErr, context: &str) {
+        match &self.error_slot {
+            Some(slot) => {
+                let mut slot = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                if slot.is_none() {
+                    *slot = Some(err);
+                }
+            }
+            None => {
+                eprintln!("Error calling Python observer callback{}: {}", context, err);
+                err.print(py);
+            }
+        }
     }
 }
 
@@ -404,11 +465,7 @@ impl CoreBufferObserver for PyBufferObserver {
                     })
                     .collect();
                 if let Err(e) = batch_cb.call1((items,)) {
-                    eprintln!(
-                        "Error calling Python observer callback (on_update_batch): {}",
-                        e
-                    );
-                    e.print(py);
+                    self.report(py, e, " (on_update_batch)");
                 }
                 return;
             }
@@ -424,8 +481,7 @@ impl CoreBufferObserver for PyBufferObserver {
                     py_transform,
                     py_kind,
                 )) {
-                    eprintln!("Error calling Python observer callback: {}", e);
-                    e.print(py);
+                    self.report(py, e, "");
                 }
             }
         });
@@ -437,6 +493,40 @@ impl CoreBufferObserver for PyBufferObserver {
 pub struct BufferTree {
     /// The underlying core buffer tree (public for inter-crate access)
     pub inner: CoreBufferTree,
+    /// First exception raised by a strict observer, re-raised by the call
+    /// that triggered it.
+    observer_error: Arc<Mutex<Option<PyErr>>>,
+}
+
+impl BufferTree {
+    /// Re-raise the exception a strict observer raised during the last call,
+    /// if any.
+    fn raise_observer_error(&self) -> PyResult<()> {
+        self.take_observer_error().map_or(Ok(()), Err)
+    }
+
+    fn take_observer_error(&self) -> Option<PyErr> {
+        self.observer_error
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+    }
+
+    /// Convert the result of a buffer write, taking the exception a strict
+    /// observer raised during it in every case so it never leaks into a
+    /// later call. If the write failed too, the observer's exception becomes
+    /// the `__cause__` of the raised error.
+    fn finish_write(&self, py: Python<'_>, result: Result<(), CoreTfError>) -> PyResult<()> {
+        let observer_err = self.take_observer_error();
+        match result {
+            Ok(()) => observer_err.map_or(Ok(()), Err),
+            Err(e) => {
+                let err = core_err_to_pyerr(e);
+                err.set_cause(py, observer_err);
+                Err(err)
+            }
+        }
+    }
 }
 
 #[pymethods]
@@ -445,6 +535,7 @@ impl BufferTree {
     pub fn new() -> Self {
         BufferTree {
             inner: CoreBufferTree::new(),
+            observer_error: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -453,24 +544,33 @@ impl BufferTree {
     /// For inserting many transforms in one shot — and notifying observers
     /// (e.g. the rerun visualizer) once per batch so columnar observers can
     /// send their data in a single call — use [`update_batch`] instead.
+    ///
+    /// `quality` is an optional confidence score in `[0, 1]`, see
+    /// `lookup_with_quality`.
+    #[pyo3(signature = (from, to, stamped_isometry, kind, quality=None))]
     pub fn update(
         &mut self,
+        py: Python<'_>,
         from: String,
         to: String,
         stamped_isometry: StampedIsometry,
         kind: TransformType,
+        quality: Option<f32>,
     ) -> PyResult<()> {
         let core_iso = CoreStampedIsometry::new(
             stamped_isometry.translation(),
             stamped_isometry.rotation(),
             stamped_isometry.stamp(),
         );
-        let core_update = CoreTransformUpdate::new(from, to, core_iso, kind.into());
+        let mut core_update = CoreTransformUpdate::new(from, to, core_iso, kind.into());
+        if let Some(quality) = quality {
+            core_update = core_update
+                .try_with_quality(quality)
+                .map_err(|_| PyValueError::new_err("quality must be within [0, 1]"))?;
+        }
 
-        self.inner
-            .update(&[core_update])
-            .map_err(core_err_to_pyerr)?;
-        Ok(())
+        let result = self.inner.update(&[core_update]);
+        self.finish_write(py, result)
     }
 
     /// Insert many transforms into the buffer in a single bulk call.
@@ -481,27 +581,46 @@ impl BufferTree {
     ///
     /// The call is fail-fast: if any tuple is rejected (cycle / multiple
     /// parents), the call returns an error and earlier tuples in the list
-    /// remain applied.
+    /// remain applied. Use [`update_batch_atomic`] to apply all or nothing.
     pub fn update_batch(
         &mut self,
+        py: Python<'_>,
         updates: Vec<(String, String, StampedIsometry, TransformType)>,
     ) -> PyResult<()> {
-        let core_updates: Vec<CoreTransformUpdate> = updates
-            .into_iter()
-            .map(|(from, to, stamped_isometry, kind)| {
-                let core_iso = CoreStampedIsometry::new(
-                    stamped_isometry.translation(),
-                    stamped_isometry.rotation(),
-                    stamped_isometry.stamp(),
-                );
-                CoreTransformUpdate::new(from, to, core_iso, kind.into())
-            })
-            .collect();
+        let result = self.inner.update(&to_core_updates(updates));
+        self.finish_write(py, result)
+    }
 
-        self.inner
-            .update(&core_updates)
-            .map_err(core_err_to_pyerr)?;
-        Ok(())
+    /// Like [`update_batch`], but all-or-nothing: every tuple is validated
+    /// (cycle / multiple parents) before any is applied, so if one is
+    /// rejected the call returns an error and the buffer is left unchanged.
+    pub fn update_batch_atomic(
+        &mut self,
+        py: Python<'_>,
+        updates: Vec<(String, String, StampedIsometry, TransformType)>,
+    ) -> PyResult<()> {
+        let result = self.inner.update_batch(&to_core_updates(updates));
+        self.finish_write(py, result)
+    }
+
+    /// Insert a static transform and lock it against further updates.
+    ///
+    /// Use this for true constants such as sensor calibrations: any later
+    /// update to the same edge, static or dynamic, raises an error.
+    pub fn set_static(
+        &mut self,
+        py: Python<'_>,
+        from: String,
+        to: String,
+        stamped_isometry: StampedIsometry,
+    ) -> PyResult<()> {
+        let core_iso = CoreStampedIsometry::new(
+            stamped_isometry.translation(),
+            stamped_isometry.rotation(),
+            stamped_isometry.stamp(),
+        );
+        let result = self.inner.set_static(&from, &to, core_iso);
+        self.finish_write(py, result)
     }
 
     /// Lookup the latest transform without any checks
@@ -546,6 +665,410 @@ impl BufferTree {
         }
     }
 
+    /// Whether `lookup_transform(from, to, time)` would succeed, without
+    /// raising. Mirrors tf2's `Buffer.can_transform`.
+    ///
+    /// # Arguments
+    /// * `time` - Timestamp; `int` for nanoseconds or `float` for seconds
+    ///   (same dispatch as the [`StampedIsometry`] constructor).
+    pub fn can_transform(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        let time_ns = stamp_to_ns(&time)?;
+        Ok(self.inner.can_transform(&from, &to, time_ns))
+    }
+
+    /// Whether `lookup_latest_transform(from, to)` would succeed, without
+    /// raising.
+    pub fn can_transform_latest(&self, from: String, to: String) -> bool {
+        self.inner.can_transform_latest(&from, &to)
+    }
+
+    /// Like `lookup_transform`, but returns `(transform, interpolated)` where
+    /// `interpolated` is `False` only if every edge had a sample exactly at
+    /// `time`.
+    pub fn lookup_transform_detailed(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<(StampedIsometry, bool)> {
+        let time_ns = stamp_to_ns(&time)?;
+        let (transform, interpolated) = self
+            .inner
+            .lookup_transform_detailed(&from, &to, time_ns)
+            .map_err(core_err_to_pyerr)?;
+        Ok((StampedIsometry::from(transform), interpolated))
+    }
+
+    /// Like `lookup_transform`, but falls back to `lookup_latest_transform`
+    /// if `time` is newer than the data. Returns `(transform, fell_back)`.
+    pub fn lookup_transform_or_latest(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<(StampedIsometry, bool)> {
+        let time_ns = stamp_to_ns(&time)?;
+        let (transform, fell_back) = self
+            .inner
+            .lookup_transform_or_latest(&from, &to, time_ns)
+            .map_err(core_err_to_pyerr)?;
+        Ok((StampedIsometry::from(transform), fell_back))
+    }
+
+    /// Average velocity `[vx, vy, vz, wx, wy, wz]` of `to` relative to
+    /// `from` between `t0` and `t1`, expressed in `from`.
+    pub fn lookup_velocity(
+        &self,
+        from: String,
+        to: String,
+        t0: Bound<'_, PyAny>,
+        t1: Bound<'_, PyAny>,
+    ) -> PyResult<[f64; 6]> {
+        let (t0_ns, t1_ns) = (stamp_to_ns(&t0)?, stamp_to_ns(&t1)?);
+        self.inner
+            .lookup_velocity(&from, &to, t0_ns, t1_ns)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Transform along an explicit `/`-separated chain such as
+    /// `"base/shoulder/elbow"`. Every consecutive pair must be joined by a
+    /// direct edge.
+    pub fn lookup_chain_str(
+        &self,
+        chain: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<StampedIsometry> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .lookup_chain_str(&chain, time_ns)
+            .map(StampedIsometry::from)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Like `lookup_transform`, projected onto the ground plane: z is 0 and
+    /// the rotation keeps only the yaw.
+    pub fn lookup_ground_projection(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<StampedIsometry> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .lookup_ground_projection(&from, &to, time_ns)
+            .map(StampedIsometry::from)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Look up every `(from, to)` pair at the same `time`, in order.
+    pub fn lookup_snapshot(
+        &self,
+        pairs: Vec<(String, String)>,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<Vec<StampedIsometry>> {
+        let time_ns = stamp_to_ns(&time)?;
+        let pairs: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|(from, to)| (from.as_str(), to.as_str()))
+            .collect();
+        self.inner
+            .lookup_snapshot(&pairs, time_ns)
+            .map(|poses| poses.into_iter().map(StampedIsometry::from).collect())
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Like `lookup_transform`, post-multiplied by `offset` (expressed in
+    /// `to`; its stamp is ignored) without inserting it into the buffer.
+    pub fn lookup_transform_with_offset(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+        offset: &StampedIsometry,
+    ) -> PyResult<StampedIsometry> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .lookup_transform_with_offset(&from, &to, time_ns, offset.inner.isometry)
+            .map(StampedIsometry::from)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Rotation part of `lookup_transform` as [x, y, z, w] quaternion.
+    pub fn lookup_rotation(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<[f64; 4]> {
+        let time_ns = stamp_to_ns(&time)?;
+        let rotation = self
+            .inner
+            .lookup_rotation(&from, &to, time_ns)
+            .map_err(core_err_to_pyerr)?;
+        Ok([rotation.i, rotation.j, rotation.k, rotation.w])
+    }
+
+    /// Like `lookup_transform`, also returning the lowest quality score along
+    /// the path, or `None` if no edge on the path was given one.
+    pub fn lookup_with_quality(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<(StampedIsometry, Option<f32>)> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .lookup_with_quality(&from, &to, time_ns)
+            .map(|(transform, quality)| (StampedIsometry::from(transform), quality))
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Translation part of `lookup_transform` as [x, y, z].
+    pub fn lookup_translation(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<[f64; 3]> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .lookup_translation(&from, &to, time_ns)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Estimate the linear (m/s²) and angular (rad/s²) acceleration of `to`
+    /// relative to `from` at `time` by finite differences over `dt_secs`.
+    ///
+    /// Returns a `(linear, angular)` tuple of `[x, y, z]` lists, expressed
+    /// in `from`. Raises if any of the three sample times is out of range,
+    /// or if `dt_secs` is not a finite interval of at least one nanosecond.
+    pub fn lookup_acceleration(
+        &self,
+        from: String,
+        to: String,
+        time: Bound<'_, PyAny>,
+        dt_secs: f64,
+    ) -> PyResult<([f64; 3], [f64; 3])> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .lookup_acceleration(&from, &to, time_ns, dt_secs)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Sample `from -> to` every `1 / rate_hz` seconds from `start` to `end`,
+    /// both inclusive. Raises if any sample time is out of range, or if
+    /// `rate_hz` is not a positive finite rate of at most 1 GHz.
+    pub fn resample_edge(
+        &self,
+        from: String,
+        to: String,
+        rate_hz: f64,
+        start: Bound<'_, PyAny>,
+        end: Bound<'_, PyAny>,
+    ) -> PyResult<Vec<StampedIsometry>> {
+        let start_ns = stamp_to_ns(&start)?;
+        let end_ns = stamp_to_ns(&end)?;
+        self.inner
+            .resample_edge(&from, &to, rate_hz, start_ns, end_ns)
+            .map(|samples| samples.into_iter().map(StampedIsometry::from).collect())
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Pose of `data_frame` at `data_time` expressed in `target_frame` at
+    /// `target_time`, going through `fixed_frame` (e.g. `world`), which must
+    /// not move between the two times. Used for motion compensation.
+    ///
+    /// Times are `int` nanoseconds or `float` seconds, as for `lookup_transform`.
+    pub fn retime_transform(
+        &self,
+        data_frame: String,
+        data_time: Bound<'_, PyAny>,
+        target_frame: String,
+        target_time: Bound<'_, PyAny>,
+        fixed_frame: String,
+    ) -> PyResult<StampedIsometry> {
+        let data_time_ns = stamp_to_ns(&data_time)?;
+        let target_time_ns = stamp_to_ns(&target_time)?;
+        self.inner
+            .retime_transform(
+                &data_frame,
+                data_time_ns,
+                &target_frame,
+                target_time_ns,
+                &fixed_frame,
+            )
+            .map(StampedIsometry::from)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// How far `to` moved relative to `from` between `t1` and `t2`:
+    /// `lookup_transform(from, to, t1)` inverted, times the one at `t2`.
+    ///
+    /// Times are `int` nanoseconds or `float` seconds, as for `lookup_transform`.
+    pub fn lookup_delta(
+        &self,
+        from: String,
+        to: String,
+        t1: Bound<'_, PyAny>,
+        t2: Bound<'_, PyAny>,
+    ) -> PyResult<StampedIsometry> {
+        let t1_ns = stamp_to_ns(&t1)?;
+        let t2_ns = stamp_to_ns(&t2)?;
+        self.inner
+            .lookup_delta(&from, &to, t1_ns, t2_ns)
+            .map(StampedIsometry::from)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Lookup the transform `offset_secs` before the newest time every edge
+    /// on the path has data (e.g. "the transform 50 ms ago").
+    ///
+    /// Raises `AttemptedLookupInPast` if the offset exceeds the buffered history.
+    pub fn lookup_transform_relative(
+        &self,
+        from: String,
+        to: String,
+        offset_secs: f64,
+    ) -> PyResult<StampedIsometry> {
+        self.inner
+            .lookup_transform_relative(&from, &to, offset_secs)
+            .map(StampedIsometry::from)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Pose of every frame connected to `root`, relative to `root`, at `time`.
+    ///
+    /// Walks the tree once from `root` instead of looking up each frame
+    /// separately. Returns a dict mapping frame name to [`StampedIsometry`].
+    ///
+    /// # Arguments
+    /// * `root` - Frame the poses are expressed in
+    /// * `time` - Timestamp; `int` for nanoseconds or `float` for seconds
+    ///   (same dispatch as the [`StampedIsometry`] constructor).
+    pub fn snapshot_from(
+        &self,
+        root: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<HashMap<String, StampedIsometry>> {
+        let time_ns = stamp_to_ns(&time)?;
+        let snapshot = self
+            .inner
+            .snapshot_from(&root, time_ns)
+            .map_err(core_err_to_pyerr)?;
+        Ok(snapshot
+            .into_iter()
+            .map(|(frame, pose)| (frame, StampedIsometry::from(pose)))
+            .collect())
+    }
+
+    /// Axis-aligned bounding box `(min, max)` of every frame origin
+    /// connected to `root`, in `root`'s coordinates at `time`.
+    ///
+    /// # Arguments
+    /// * `root` - Frame the box is expressed in
+    /// * `time` - Timestamp; `int` for nanoseconds or `float` for seconds
+    ///   (same dispatch as the [`StampedIsometry`] constructor).
+    pub fn origins_aabb(
+        &self,
+        root: String,
+        time: Bound<'_, PyAny>,
+    ) -> PyResult<([f64; 3], [f64; 3])> {
+        let time_ns = stamp_to_ns(&time)?;
+        self.inner
+            .origins_aabb(&root, time_ns)
+            .map_err(core_err_to_pyerr)
+    }
+
+    /// Every edge's transform at `time` as a YAML list of tf2
+    /// `TransformStamped`-shaped entries. Edges that cannot be evaluated at
+    /// `time` are left out.
+    ///
+    /// # Arguments
+    /// * `time` - Timestamp; `int` for nanoseconds or `float` for seconds
+    ///   (same dispatch as the [`StampedIsometry`] constructor).
+    pub fn to_tf_yaml(&self, time: Bound<'_, PyAny>) -> PyResult<String> {
+        let time_ns = stamp_to_ns(&time)?;
+        Ok(self.inner.to_tf_yaml(time_ns))
+    }
+
+    /// Child frames of every edge whose newest sample is at or after `stamp`.
+    ///
+    /// # Arguments
+    /// * `stamp` - Timestamp; `int` for nanoseconds or `float` for seconds
+    ///   (same dispatch as the [`StampedIsometry`] constructor).
+    pub fn frames_updated_since(&self, stamp: Bound<'_, PyAny>) -> PyResult<Vec<String>> {
+        let stamp_ns = stamp_to_ns(&stamp)?;
+        Ok(self.inner.frames_updated_since(stamp_ns))
+    }
+
+    /// Names of every frame currently in the buffer, sorted.
+    pub fn frame_names(&self) -> Vec<String> {
+        self.inner.frame_names()
+    }
+
+    /// Counter that changes whenever an edge is added to or removed from
+    /// the graph, but not when existing edges get new samples.
+    pub fn topology_generation(&self) -> u64 {
+        self.inner.topology_generation()
+    }
+
+    /// Structural problems in the tree (a frame with several parents, a
+    /// cycle, an edge without samples), or an empty list if there are none.
+    pub fn check_invariants(&self) -> Vec<String> {
+        self.inner.check_invariants().err().unwrap_or_default()
+    }
+
+    /// Newest sample stamp (nanoseconds) on any edge touching `frame`,
+    /// or `None` if the frame is unknown.
+    pub fn last_update_stamp(&self, frame: &str) -> Option<i64> {
+        self.inner.last_update_stamp(frame)
+    }
+
+    /// Remove every frame and edge, and all frame metadata. Config and
+    /// observers are kept.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// Attach a `key = value` pair to `frame`. Shown as a tooltip by
+    /// `visualize`; does not affect transforms.
+    pub fn set_frame_metadata(&mut self, frame: &str, key: &str, value: &str) {
+        self.inner.set_frame_metadata(frame, key, value)
+    }
+
+    /// The value stored for `key` on `frame`, or `None`.
+    pub fn get_frame_metadata(&self, frame: &str, key: &str) -> Option<String> {
+        self.inner
+            .get_frame_metadata(frame, key)
+            .map(str::to_string)
+    }
+
+    /// Frames at most `hops` edges away from `frame` in either direction,
+    /// sorted by name, excluding `frame` itself.
+    pub fn neighbors_within(&self, frame: &str, hops: usize) -> Vec<String> {
+        self.inner.neighbors_within(frame, hops)
+    }
+
+    /// Copy `root` and all its descendants, with their full histories, into
+    /// a new buffer. Observers are not copied.
+    pub fn subtree(&self, root: &str) -> PyResult<BufferTree> {
+        let inner = self.inner.subtree(root).map_err(core_err_to_pyerr)?;
+        Ok(BufferTree {
+            inner,
+            observer_error: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// One line per edge: `from -> to [Kind, N samples, latest t=...s]`
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
     /// Visualize the buffer tree as a DOT graph
     /// Can not use internal visualizer because we Store the nodes in self.index
     pub fn visualize(&self) -> String {
@@ -577,8 +1100,15 @@ impl BufferTree {
     ///   callable invoked once per transform:
     ///   `callback(from: str, to: str, transform: StampedIsometry, kind: TransformType) -> None`
     ///
+    /// Exceptions raised by the observer are printed and otherwise ignored.
+    /// With `strict=True` they are instead re-raised from the `update`,
+    /// `update_batch`, `set_static` or `register_observer` call that
+    /// triggered them (the buffer change itself is kept), so a broken
+    /// callback cannot fail silently.
+    ///
     /// # Arguments
     /// * `callback` - A callable, or an object exposing `on_update_batch`
+    /// * `strict` - Re-raise observer exceptions instead of printing them
     ///
     /// # Example
     /// ```python
@@ -588,7 +1118,13 @@ impl BufferTree {
     /// buffer = BufferTree()
     /// buffer.register_observer(my_observer)
     /// ```
-    pub fn register_observer(&mut self, py: Python<'_>, callback: Py<PyAny>) -> PyResult<()> {
+    #[pyo3(signature = (callback, strict=false))]
+    pub fn register_observer(
+        &mut self,
+        py: Python<'_>,
+        callback: Py<PyAny>,
+        strict: bool,
+    ) -> PyResult<()> {
         // Accept either a plain callable or an object exposing `on_update_batch`.
         let bound = callback.bind(py);
         if !bound.is_callable() && !bound.hasattr("on_update_batch")? {
@@ -598,9 +1134,10 @@ impl BufferTree {
         }
 
         // Create the observer wrapper and register it
-        let observer = PyBufferObserver::new(callback);
+        let error_slot = strict.then(|| self.observer_error.clone());
+        let observer = PyBufferObserver::new(callback, error_slot);
         self.inner.register_observer(Box::new(observer));
-        Ok(())
+        self.raise_observer_error()
     }
 }
 
@@ -1,3 +1,4 @@
+import math
 import pytest
 import schiebung
 import tempfile
@@ -23,6 +24,13 @@ def test_stamped_isometry_creation():
     assert t.stamp() == 10_000_000_000  # 10s in ns
     assert t.stamp_secs() == 10.0
 
+def test_axis_angle():
+    angle = math.pi / 3
+    t = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, math.sin(angle / 2), math.cos(angle / 2)], 0)
+    axis, result = t.axis_angle()
+    assert axis == pytest.approx([0.0, 0.0, 1.0])
+    assert result == pytest.approx(angle)
+
 def test_simple_lookup():
     buf = BufferTree()
     t = StampedIsometry([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
@@ -52,18 +60,36 @@ def test_dynamic_lookup_interpolation():
     res = buf.lookup_transform("odom", "base_link", 5.0)
     assert res.translation() == [5.0, 0.0, 0.0]
 
+def test_can_transform():
+    buf = BufferTree()
+    buf.update_batch([
+        ("odom", "base_link", StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0), TransformType.Dynamic),
+        ("odom", "base_link", StampedIsometry([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0), TransformType.Dynamic),
+    ])
+
+    assert buf.can_transform("odom", "base_link", 0.5)
+    assert not buf.can_transform("odom", "base_link", 2.0)
+    assert not buf.can_transform("odom", "ghost", 0.5)
+    assert buf.can_transform_latest("base_link", "odom")
+    assert not buf.can_transform_latest("odom", "ghost")
+
 def test_lookup_exceptions():
     buf = BufferTree()
 
     # Case 1: No transform exists
-    with pytest.raises(ValueError, match="CouldNotFindTransform"):
+    with pytest.raises(ValueError, match="UnknownFrame"):
         buf.lookup_latest_transform("A", "B")
 
     # Case 2: Graph disconnected
     t = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
     buf.update("A", "B", t, TransformType.Static)
     # Just lookup A->C
-    with pytest.raises(ValueError, match="CouldNotFindTransform"):
+    with pytest.raises(ValueError, match="UnknownFrame"):
+         buf.lookup_transform("A", "C", 0.0)
+
+    # Case 3: Both frames known, but in different trees
+    buf.update("C", "D", t, TransformType.Static)
+    with pytest.raises(ValueError, match="NoPath"):
          buf.lookup_transform("A", "C", 0.0)
 
 def test_future_past_exceptions():
@@ -92,6 +118,43 @@ def test_cycle_detection():
     with pytest.raises(ValueError, match="InvalidGraph"):
          buf.update("C", "A", t, TransformType.Static)
 
+def test_update_batch_keeps_tuples_before_failure():
+    buf = BufferTree()
+    t = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
+    buf.update("A", "B", t, TransformType.Static)
+
+    with pytest.raises(ValueError, match="InvalidGraph"):
+        buf.update_batch([
+            ("B", "C", t, TransformType.Static),
+            ("C", "A", t, TransformType.Static),
+        ])
+
+    # The valid first tuple was applied before the cycle was rejected.
+    buf.lookup_latest_transform("B", "C")
+
+def test_update_batch_atomic():
+    buf = BufferTree()
+    t = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
+    buf.update("A", "B", t, TransformType.Static)
+
+    with pytest.raises(ValueError, match="InvalidGraph"):
+        buf.update_batch_atomic([
+            ("B", "C", t, TransformType.Static),
+            ("C", "A", t, TransformType.Static),
+        ])
+
+    # The valid first tuple was not applied either.
+    with pytest.raises(ValueError, match="UnknownFrame"):
+        buf.lookup_latest_transform("B", "C")
+
+def test_set_static_rejects_updates():
+    buf = BufferTree()
+    calibration = StampedIsometry([0.1, 0.0, 0.2], [0.0, 0.0, 0.0, 1.0], 0.0)
+    buf.set_static("robot", "camera", calibration)
+
+    with pytest.raises(ValueError, match="LockedTransform"):
+        buf.update("robot", "camera", StampedIsometry([5.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0), TransformType.Dynamic)
+
 def test_urdf_loader_creation():
     """Test that UrdfLoader can be instantiated"""
     loader = UrdfLoader()
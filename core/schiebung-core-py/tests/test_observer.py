@@ -133,3 +133,46 @@ def test_observer_exception_handling():
     t = StampedIsometry([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
     # This should complete successfully
     buf.update("a", "b", t, TransformType.Static)
+
+
+def test_strict_observer_reraises():
+    """Test that strict mode surfaces exceptions raised by the callback."""
+    def broken(from_frame, to_frame, transform, kind):
+        raise RuntimeError("observer is broken")
+
+    t = StampedIsometry([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
+
+    # Default: the exception is printed and the update goes through.
+    buf = BufferTree()
+    buf.register_observer(broken)
+    buf.update("world", "robot", t, TransformType.Static)
+
+    buf = BufferTree()
+    buf.register_observer(broken, strict=True)
+    with pytest.raises(RuntimeError, match="observer is broken"):
+        buf.update("world", "robot", t, TransformType.Static)
+    # The transform itself was still stored.
+    assert buf.lookup_latest_transform("world", "robot").translation() == [1.0, 0.0, 0.0]
+
+
+def test_strict_observer_error_does_not_leak_into_later_calls():
+    """Test that a failed update does not leave an observer exception behind."""
+    calls = []
+
+    def broken_once(from_frame, to_frame, transform, kind):
+        calls.append(to_frame)
+        if len(calls) == 1:
+            raise RuntimeError("observer is broken")
+
+    t = StampedIsometry([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0)
+    buf = BufferTree()
+    buf.register_observer(broken_once, strict=True)
+    with pytest.raises(RuntimeError, match="observer is broken"):
+        buf.update("world", "robot", t, TransformType.Static)
+
+    # A cycle is rejected with the buffer's own error...
+    with pytest.raises(ValueError, match="InvalidGraph"):
+        buf.update("robot", "world", t, TransformType.Static)
+    # ...and the next successful update raises nothing.
+    buf.update("robot", "camera", t, TransformType.Static)
+    assert calls == ["robot", "camera"]
@@ -45,3 +45,15 @@ def test_negative_float_stamp():
     """Pre-epoch timestamps round-trip too."""
     iso = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], -1.5)
     assert iso.stamp() == -1_500_000_000
+
+
+def test_stamp_ns_alias():
+    iso = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.25)
+    assert iso.stamp_ns() == iso.stamp() == 1_250_000_000
+
+
+def test_repr_shows_ns_and_seconds():
+    iso = StampedIsometry([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1_250_000_000)
+    text = repr(iso)
+    assert "1250000000ns" in text
+    assert "1.250000s" in text
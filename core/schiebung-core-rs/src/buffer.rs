@@ -1,66 +1,148 @@
-use rustc_hash::FxHashMap;
-use std::collections::VecDeque;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
 use std::fs::File;
 use std::io::Write;
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 
-use nalgebra::geometry::Isometry3;
+use nalgebra::geometry::{Isometry3, Translation3, UnitQuaternion};
 use petgraph::algo::is_cyclic_undirected;
 use petgraph::graphmap::DiGraphMap;
+use petgraph::Direction;
 
+use crate::clock::{Clock, SystemClock};
 use crate::config::{get_config, BufferConfig};
 use crate::error::TfError;
-use crate::types::{StampedIsometry, TransformType, TransformUpdate};
+use crate::types::{
+    interpolate_with_mode, nanos_to_secs, secs_to_nanos, InterpolationMode, StampedIsometry,
+    TransformType, TransformUpdate,
+};
 
 /// The TransformHistory keeps track of a single transform between two frames
-/// Update pushes a new StampedTransform to the end, if the history reaches it's max length
-/// The oldest transform is removed.
-#[derive(Debug)]
+/// Update pushes a new StampedTransform to the end and removes the transforms
+/// that fell out of the buffer window.
+#[derive(Debug, Clone)]
 struct TransformHistory {
     history: VecDeque<StampedIsometry>,
     kind: TransformType,
     /// Buffer window in nanoseconds
     buffer_window: i64,
+    /// Widest gap between samples to interpolate across, in nanoseconds
+    max_interpolation_gap: Option<i64>,
+    interpolation: InterpolationMode,
+    /// Quality score of each sample in `history`, if its update carried one.
+    qualities: VecDeque<Option<f32>>,
+    /// Set by [`BufferTree::set_static`]; a locked edge rejects all updates.
+    locked: bool,
 }
 
 impl TransformHistory {
-    pub fn new(kind: TransformType, buffer_window_secs: f64) -> Self {
+    /// `initial_capacity` pre-sizes the sample queue of dynamic edges; static
+    /// edges rarely hold more than one sample and start empty.
+    pub fn new(
+        kind: TransformType,
+        buffer_window_secs: f64,
+        max_interpolation_gap_secs: Option<f64>,
+        interpolation: InterpolationMode,
+        initial_capacity: usize,
+    ) -> Self {
+        let (history, qualities) = match kind {
+            TransformType::Dynamic => (
+                VecDeque::with_capacity(initial_capacity),
+                VecDeque::with_capacity(initial_capacity),
+            ),
+            TransformType::Static => (VecDeque::new(), VecDeque::new()),
+        };
         TransformHistory {
-            history: VecDeque::new(),
+            history,
             kind,
-            buffer_window: (buffer_window_secs * 1_000_000_000.0) as i64,
+            buffer_window: secs_to_nanos(buffer_window_secs),
+            max_interpolation_gap: max_interpolation_gap_secs.map(secs_to_nanos),
+            interpolation,
+            qualities,
+            locked: false,
         }
     }
 
-    pub fn update(&mut self, stamped_isometry: StampedIsometry) {
+    /// Append a sample with its quality score and evict every sample at the
+    /// front that is more than the buffer window older than it. The newest
+    /// sample is always kept, so an edge that publishes rarely never ends up
+    /// empty.
+    pub fn update(&mut self, stamped_isometry: StampedIsometry, quality: Option<f32>) {
+        let newest = stamped_isometry.stamp;
         self.history.push_back(stamped_isometry);
-        if (self.history.back().unwrap().stamp - self.history.front().unwrap().stamp)
-            > self.buffer_window
+        self.qualities.push_back(quality);
+        while self.history.len() > 1
+            && newest - self.history.front().unwrap().stamp > self.buffer_window
         {
             self.history.pop_front();
+            self.qualities.pop_front();
         }
     }
 
-    pub fn interpolate_isometry_at_time(&self, time: i64) -> Result<Isometry3<f64>, TfError> {
+    /// Drop every sample.
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.qualities.clear();
+    }
+
+    /// Quality score at `time`: that of the sample stamped `time`, or the
+    /// lower score of the two samples it is interpolated between. Static
+    /// edges report their newest sample. Unscored samples do not constrain
+    /// the result; `None` if neither has a score or `time` is out of range.
+    pub fn quality_at_time(&self, time: i64) -> Option<f32> {
         match self.kind {
-            TransformType::Static => {
-                return Ok(self.history.back().unwrap().isometry);
+            TransformType::Static => self.qualities.back().copied().flatten(),
+            TransformType::Dynamic => {
+                match self
+                    .history
+                    .binary_search_by(|entry| entry.stamp.cmp(&time))
+                {
+                    Ok(i) => self.qualities[i],
+                    Err(i) if i == 0 || i >= self.history.len() => None,
+                    Err(i) => match (self.qualities[i - 1], self.qualities[i]) {
+                        (Some(before), Some(after)) => Some(before.min(after)),
+                        (before, after) => before.or(after),
+                    },
+                }
             }
+        }
+    }
+
+    /// Whether a lookup at `time` hits a stored sample exactly, without
+    /// interpolating. Always true for static edges.
+    pub fn has_sample_at(&self, time: i64) -> bool {
+        match self.kind {
+            TransformType::Static => true,
+            TransformType::Dynamic => self
+                .history
+                .binary_search_by(|entry| entry.stamp.cmp(&time))
+                .is_ok(),
+        }
+    }
+
+    pub fn interpolate_isometry_at_time(&self, time: i64) -> Result<Isometry3<f64>, TfError> {
+        match self.kind {
+            TransformType::Static => self
+                .history
+                .back()
+                .map(|sample| sample.isometry)
+                .ok_or_else(|| TfError::CouldNotFindTransform("No samples".to_string())),
             TransformType::Dynamic => {
-                if self.history.len() < 2 {
-                    return Err(TfError::CouldNotFindTransform(format!(
-                        "Not enough history to interpolate. Len: {}",
-                        self.history.len()
-                    ))); // Not enough elements
+                if self.history.is_empty() {
+                    return Err(TfError::CouldNotFindTransform(
+                        "No samples to interpolate".to_string(),
+                    ));
                 }
 
+                // A single sample is only valid at its exact stamp, which the
+                // search below handles like any other exact hit.
                 let history = &self.history;
                 let idx = history.binary_search_by(|entry| entry.stamp.cmp(&time));
 
                 match idx {
-                    Ok(i) => {
-                        return Ok(history[i].isometry);
-                    }
+                    Ok(i) => Ok(history[i].isometry),
                     Err(i) => {
                         // Not found, i is the insertion point
                         if i == 0 {
@@ -70,18 +152,28 @@ impl TransformHistory {
                             )));
                         }
                         if i >= history.len() {
-                            return Err(TfError::AttemptedLookUpInFuture(format!(
+                            Err(TfError::AttemptedLookUpInFuture(format!(
                                 "Time {} is after the newest transform at {}",
                                 time,
                                 history[history.len() - 1].stamp
-                            )));
+                            )))
                         } else {
-                            // Calculate weight as f64 for interpolation
-                            let dt = (history[i].stamp - history[i - 1].stamp) as f64;
-                            let weight = (time - history[i - 1].stamp) as f64 / dt;
-                            return Ok(history[i - 1]
-                                .isometry
-                                .lerp_slerp(&history[i].isometry, weight));
+                            let gap = history[i].stamp - history[i - 1].stamp;
+                            if self.max_interpolation_gap.is_some_and(|max| gap > max) {
+                                return Err(TfError::ExtrapolationGap(format!(
+                                    "Time {} falls in a {} ns gap between samples at {} and {}",
+                                    time,
+                                    gap,
+                                    history[i - 1].stamp,
+                                    history[i].stamp
+                                )));
+                            }
+                            Ok(interpolate_with_mode(
+                                &history[i - 1],
+                                &history[i],
+                                time,
+                                self.interpolation,
+                            ))
                         }
                     }
                 }
@@ -124,6 +216,13 @@ impl NodeIndex {
         }
     }
 
+    pub fn with_capacity(capacity: usize) -> Self {
+        NodeIndex {
+            node_map: FxHashMap::with_capacity_and_hasher(capacity, Default::default()),
+            nodes: Vec::with_capacity(capacity),
+        }
+    }
+
     pub fn get(&self, node: &str) -> Option<usize> {
         self.node_map.get(node).cloned()
     }
@@ -171,6 +270,137 @@ impl NodeIndex {
 pub trait BufferObserver: Send + Sync {
     /// Handle a batch of transforms that was just inserted into the buffer.
     fn on_update(&self, updates: &[TransformUpdate]);
+
+    /// Called when an edge declared [`TransformType::Static`] receives a
+    /// sample that differs from the one it already holds.
+    ///
+    /// Static edges always resolve to their newest sample regardless of the
+    /// query time, so a "static" transform that keeps changing silently
+    /// rewrites history. This hook makes that visible. It fires before the
+    /// new sample is stored and before [`on_update`](BufferObserver::on_update)
+    /// is called for the batch. The default implementation does nothing.
+    fn on_static_changed(
+        &self,
+        _from: &str,
+        _to: &str,
+        _previous: &StampedIsometry,
+        _new: &StampedIsometry,
+    ) {
+    }
+
+    /// Called when an edge receives a sample with the same stamp as its
+    /// newest one but a different transform.
+    ///
+    /// This usually means two publishers are racing on the same edge; the
+    /// last writer wins, which otherwise shows up as unexplained jitter. It
+    /// fires before the new sample is stored and before
+    /// [`on_update`](BufferObserver::on_update) is called for the batch. The
+    /// default implementation does nothing.
+    fn on_conflict(
+        &self,
+        _from: &str,
+        _to: &str,
+        _existing: &StampedIsometry,
+        _new: &StampedIsometry,
+    ) {
+    }
+}
+
+/// Translation (in meters) and rotation (in radians) below which two samples
+/// on the same edge are considered identical.
+const SAMPLE_EQUALITY_TOLERANCE: f64 = 1e-9;
+
+/// Most samples [`BufferTree::resample_edge`] produces in one call, so a
+/// huge rate or range fails instead of exhausting memory.
+const MAX_RESAMPLE_SAMPLES: u64 = 1_000_000;
+
+/// Translation (in meters) and rotation (in radians) within which an update
+/// `B -> A` is accepted as the inverse of an existing edge `A -> B` (see
+/// [`BufferConfig::accept_inverse_edges`]). Looser than
+/// [`SAMPLE_EQUALITY_TOLERANCE`] because the two directions usually come from
+/// different publishers and have been through a float round-trip.
+const INVERSE_EDGE_TOLERANCE: f64 = 1e-6;
+
+/// Whether two isometries differ by more than `tolerance` in translation or
+/// rotation.
+fn isometries_differ(a: &Isometry3<f64>, b: &Isometry3<f64>, tolerance: f64) -> bool {
+    (a.translation.vector - b.translation.vector).norm() > tolerance
+        || a.rotation.angle_to(&b.rotation) > tolerance
+}
+
+/// `value` as a YAML float that YAML 1.1 loaders such as PyYAML read as a
+/// number: always with a decimal point and a signed exponent, and `.nan` /
+/// `.inf` for the non-finite values.
+fn yaml_float(value: f64) -> String {
+    if value.is_nan() {
+        return ".nan".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { ".inf" } else { "-.inf" }.to_string();
+    }
+    // Debug always prints a decimal point unless it switches to an exponent.
+    let formatted = format!("{:?}", value);
+    let Some((mantissa, exponent)) = formatted.split_once('e') else {
+        return formatted;
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.to_string()
+    } else {
+        format!("{}.0", mantissa)
+    };
+    if exponent.starts_with('-') {
+        format!("{}e{}", mantissa, exponent)
+    } else {
+        format!("{}e+{}", mantissa, exponent)
+    }
+}
+
+/// `value` escaped for use inside a double-quoted DOT string, so quotes and
+/// backslashes in frame names cannot end the string early.
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `value` as a double-quoted YAML string, escaping quotes, backslashes and
+/// every character a YAML parser would not take literally.
+fn yaml_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}') => {
+                quoted.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Borrowed view of one edge of a [`BufferTree`], yielded by
+/// [`BufferTree::iter_edges`].
+#[derive(Clone, Copy, Debug)]
+pub struct EdgeView<'a> {
+    /// Parent frame of the edge.
+    pub from: &'a str,
+    /// Child frame of the edge.
+    pub to: &'a str,
+    /// Whether the edge is static or dynamic.
+    pub kind: TransformType,
+    /// Every sample currently retained on the edge, oldest first.
+    pub samples: &'a VecDeque<StampedIsometry>,
+    /// Quality score of each sample in `samples`, if its update carried one.
+    pub qualities: &'a VecDeque<Option<f32>>,
+}
+
+impl<'a> EdgeView<'a> {
+    /// The newest sample on the edge, or `None` if the edge holds no samples.
+    pub fn latest(&self) -> Option<&'a StampedIsometry> {
+        self.samples.back()
+    }
 }
 
 /// In-memory transform graph with per-edge history.
@@ -217,6 +447,16 @@ pub struct BufferTree {
     index: NodeIndex,
     config: BufferConfig,
     observers: Vec<Box<dyn BufferObserver>>,
+    /// When [`save_visualization`](BufferTree::save_visualization) last
+    /// actually wrote to disk; used to rate-limit saves.
+    last_visualization_save: Mutex<Option<f64>>,
+    /// Source of "now" for time-dependent behavior (see [`set_clock`](BufferTree::set_clock)).
+    clock: Arc<dyn Clock>,
+    /// Bumped on every structural change (see [`topology_generation`](BufferTree::topology_generation)).
+    topology_generation: u64,
+    /// Free-form key/value pairs per frame, keyed by node id (see
+    /// [`set_frame_metadata`](BufferTree::set_frame_metadata)).
+    frame_metadata: FxHashMap<usize, BTreeMap<String, String>>,
 }
 
 impl BufferTree {
@@ -226,12 +466,84 @@ impl BufferTree {
     /// [`get_config`] (falling back to defaults if no file exists). The
     /// returned buffer has no nodes, edges, or observers.
     pub fn new() -> Self {
+        Self::with_config(get_config().unwrap())
+    }
+
+    /// Construct an empty buffer pre-sized for `frames` frames.
+    ///
+    /// Same as [`new`](BufferTree::new), but the frame index and graph are
+    /// allocated up front, so bulk-loading a large tree (e.g. a robot
+    /// description with hundreds of links) does not rehash along the way.
+    /// The buffer still grows past `frames` if needed.
+    pub fn with_capacity(frames: usize) -> Self {
+        let mut buffer = Self::new();
+        buffer.graph = DiGraphMap::with_capacity(frames, frames.saturating_sub(1));
+        buffer.index = NodeIndex::with_capacity(frames);
+        buffer
+    }
+
+    /// Construct an empty buffer with an explicit [`BufferConfig`].
+    ///
+    /// Use this instead of [`new`](BufferTree::new) when the configuration
+    /// should not come from the config file on disk (e.g. in tests or when
+    /// embedding the buffer in a larger application).
+    pub fn with_config(config: BufferConfig) -> Self {
         BufferTree {
             graph: DiGraphMap::new(),
             index: NodeIndex::new(),
-            config: get_config().unwrap(),
+            config,
             observers: Vec::new(),
+            last_visualization_save: Mutex::new(None),
+            clock: Arc::new(SystemClock::new()),
+            topology_generation: 0,
+            frame_metadata: FxHashMap::default(),
+        }
+    }
+
+    /// Construct a buffer (as with [`new`](BufferTree::new)) and insert
+    /// `transforms` one by one.
+    ///
+    /// Each item is `(from, to, translation, rotation, stamp, kind)`, with the
+    /// same meaning as the arguments of [`StampedIsometry::new`] and
+    /// [`TransformUpdate::new`]. Saves the usual loop over
+    /// [`update`](BufferTree::update) when loading a fixed tree, e.g. in
+    /// tests or loaders.
+    ///
+    /// # Errors
+    ///
+    /// The first error [`update`](BufferTree::update) returns, prefixed with
+    /// the position and frames of the offending item.
+    pub fn from_transforms<I, S>(transforms: I) -> Result<Self, TfError>
+    where
+        I: IntoIterator<Item = (S, S, [f64; 3], [f64; 4], i64, TransformType)>,
+        S: AsRef<str>,
+    {
+        let mut buffer = Self::new();
+        for (i, (from, to, translation, rotation, stamp, kind)) in
+            transforms.into_iter().enumerate()
+        {
+            let (from, to) = (from.as_ref(), to.as_ref());
+            buffer
+                .update(&[TransformUpdate::new(
+                    from,
+                    to,
+                    StampedIsometry::new(translation, rotation, stamp),
+                    kind,
+                )])
+                .map_err(|e| e.with_context(format!("Transform {} ('{}' -> '{}')", i, from, to)))?;
         }
+        Ok(buffer)
+    }
+
+    /// Replace the time source used for time-dependent behavior, such as the
+    /// [`save_visualization`](BufferTree::save_visualization) rate limit.
+    ///
+    /// Defaults to [`SystemClock`]. Pass a
+    /// [`ManualClock`](crate::ManualClock) to drive time by hand in tests or
+    /// simulations. Sample stamps and lookup times are unaffected; they are
+    /// always whatever the caller provides.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
     }
 
     /// Register a new observer
@@ -240,19 +552,15 @@ impl BufferTree {
     /// state at registration time.
     pub fn register_observer(&mut self, observer: Box<dyn BufferObserver>) {
         let mut replay: Vec<TransformUpdate> = Vec::new();
-        for (from_idx, to_idx, history) in self.graph.all_edges() {
-            let from_node = self.index.get_node(from_idx);
-            let to_node = self.index.get_node(to_idx);
-
-            if let (Some(from_node), Some(to_node)) = (from_node, to_node) {
-                for item in &history.history {
-                    replay.push(TransformUpdate {
-                        from: from_node.name.clone(),
-                        to: to_node.name.clone(),
-                        stamped_isometry: item.clone(),
-                        kind: history.kind,
-                    });
-                }
+        for edge in self.iter_edges() {
+            for (item, quality) in edge.samples.iter().zip(edge.qualities) {
+                replay.push(TransformUpdate {
+                    from: edge.from.to_string(),
+                    to: edge.to.to_string(),
+                    stamped_isometry: item.clone(),
+                    kind: edge.kind,
+                    quality: *quality,
+                });
             }
         }
         if !replay.is_empty() {
@@ -261,6 +569,342 @@ impl BufferTree {
         self.observers.push(observer);
     }
 
+    /// Iterate over every edge currently in the graph.
+    ///
+    /// Each [`EdgeView`] borrows the edge's frame names, kind, and retained
+    /// samples. Iteration order is unspecified.
+    pub fn iter_edges(&self) -> impl Iterator<Item = EdgeView<'_>> {
+        self.graph
+            .all_edges()
+            .filter_map(move |(from_idx, to_idx, history)| {
+                let from_node = self.index.get_node(from_idx)?;
+                let to_node = self.index.get_node(to_idx)?;
+                Some(EdgeView {
+                    from: &from_node.name,
+                    to: &to_node.name,
+                    kind: history.kind,
+                    samples: &history.history,
+                    qualities: &history.qualities,
+                })
+            })
+    }
+
+    /// Child frames of every edge whose newest sample is at or after `stamp`.
+    ///
+    /// `stamp` is in nanoseconds since Unix epoch, like the lookup times.
+    /// Useful for redrawing only the frames that moved since the last render.
+    /// The result is sorted by frame name.
+    pub fn frames_updated_since(&self, stamp: i64) -> Vec<String> {
+        let mut frames: Vec<String> = self
+            .iter_edges()
+            .filter(|edge| edge.latest().is_some_and(|latest| latest.stamp >= stamp))
+            .map(|edge| edge.to.to_string())
+            .collect();
+        frames.sort();
+        frames
+    }
+
+    /// Counter that changes whenever an edge (and with it possibly a frame)
+    /// is added to or removed from the graph.
+    ///
+    /// New samples on existing edges leave it untouched, so consumers can
+    /// cache paths or frame lists and only rebuild them when the value
+    /// differs from the one they saw last.
+    pub fn topology_generation(&self) -> u64 {
+        self.topology_generation
+    }
+
+    /// Number of frames currently in the graph.
+    pub fn frame_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Names of every frame currently in the graph, sorted.
+    ///
+    /// Frames that were only ever named by a rejected update (e.g. one that
+    /// would have closed a cycle) are not included.
+    pub fn frame_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .graph
+            .nodes()
+            .filter_map(|idx| self.index.get_node(idx))
+            .map(|node| node.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Whether `frame` is part of the graph.
+    pub fn has_frame(&self, frame: &str) -> bool {
+        self.index
+            .get(frame)
+            .is_some_and(|idx| self.graph.contains_node(idx))
+    }
+
+    /// Verify the structural invariants of the tree and list every violation.
+    ///
+    /// Checks that no frame has more than one parent, that the graph has no
+    /// cycles, and that no edge has an empty history. [`update`](BufferTree::update)
+    /// maintains the first two; an edge emptied by
+    /// [`clear_edge_history`](BufferTree::clear_edge_history) is reported
+    /// until it receives a new sample. Meant as a safety net after bulk
+    /// operations, not for the hot path: the cycle check walks the whole graph.
+    pub fn check_invariants(&self) -> Result<(), Vec<String>> {
+        let name = |idx: usize| {
+            self.index
+                .get_node(idx)
+                .map_or_else(|| format!("#{}", idx), |node| node.name.clone())
+        };
+        let mut violations = Vec::new();
+
+        for idx in self.graph.nodes() {
+            let parents: Vec<String> = self
+                .graph
+                .neighbors_directed(idx, Direction::Incoming)
+                .map(name)
+                .collect();
+            if parents.len() > 1 {
+                violations.push(format!(
+                    "Frame '{}' has {} parents: {}",
+                    name(idx),
+                    parents.len(),
+                    parents.join(", ")
+                ));
+            }
+        }
+
+        if is_cyclic_undirected(&self.graph) {
+            violations.push("Graph contains a cycle".to_string());
+        }
+
+        for (from_idx, to_idx, history) in self.graph.all_edges() {
+            if history.history.is_empty() {
+                violations.push(format!(
+                    "Edge '{}' -> '{}' has no samples",
+                    name(from_idx),
+                    name(to_idx)
+                ));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Newest sample stamp on any edge touching `frame`, as parent or child.
+    ///
+    /// Returned in nanoseconds since Unix epoch; `None` if the frame is
+    /// unknown. Useful for "is this frame still alive" diagnostics.
+    pub fn last_update_stamp(&self, frame: &str) -> Option<i64> {
+        let idx = self.index.get(frame)?;
+        self.graph
+            .edges_directed(idx, petgraph::Direction::Incoming)
+            .chain(
+                self.graph
+                    .edges_directed(idx, petgraph::Direction::Outgoing),
+            )
+            .filter_map(|(_, _, history)| history.history.back())
+            .map(|latest| latest.stamp)
+            .max()
+    }
+
+    /// Newest sample stamp on the edge `from -> to`, in nanoseconds.
+    ///
+    /// `None` if the edge does not exist or holds no samples.
+    pub fn edge_latest_stamp(&self, from: &str, to: &str) -> Option<i64> {
+        self.edge(from, to)?
+            .history
+            .back()
+            .map(|latest| latest.stamp)
+    }
+
+    /// Run `f` over the retained samples of the edge `from -> to`, oldest
+    /// first, without copying them.
+    ///
+    /// Meant for custom analysis (jitter, rates, gaps) over a single edge.
+    /// A static edge holds one sample. Use
+    /// [`iter_edges`](BufferTree::iter_edges) to visit every edge instead.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::CouldNotFindTransform`] — there is no edge `from -> to`.
+    pub fn with_edge_history<R>(
+        &self,
+        from: &str,
+        to: &str,
+        f: impl FnOnce(&VecDeque<StampedIsometry>) -> R,
+    ) -> Result<R, TfError> {
+        let history = self.edge(from, to).ok_or_else(|| {
+            TfError::CouldNotFindTransform(format!("No edge '{}' -> '{}'", from, to))
+        })?;
+        Ok(f(&history.history))
+    }
+
+    /// Drop every sample on the dynamic edge `from -> to`, keeping the edge.
+    ///
+    /// Meant for recovering from a publisher whose clock jumped backward
+    /// (simulation reset, looping bag): the stale future samples would
+    /// otherwise break interpolation for the new timeline. The topology is
+    /// unchanged, so [`topology_generation`](BufferTree::topology_generation)
+    /// does not move. Returns `false` and leaves the buffer untouched if the
+    /// edge does not exist or is static.
+    pub fn clear_edge_history(&mut self, from: &str, to: &str) -> bool {
+        let (Some(from_idx), Some(to_idx)) = (self.index.get(from), self.index.get(to)) else {
+            return false;
+        };
+        match self.graph.edge_weight_mut(from_idx, to_idx) {
+            Some(history) if matches!(history.kind, TransformType::Dynamic) => {
+                history.clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove every frame and edge, including locked ones, along with any
+    /// frame metadata.
+    ///
+    /// Config, clock, and observers are kept, so the buffer behaves like a
+    /// freshly constructed one with the same setup. Meant for recovering
+    /// from a corrupted tree without rebuilding everything around the buffer.
+    /// [`topology_generation`](BufferTree::topology_generation) moves if
+    /// any frame was removed.
+    pub fn clear(&mut self) {
+        if self.graph.node_count() > 0 {
+            self.topology_generation += 1;
+        }
+        self.graph.clear();
+        self.index = NodeIndex::new();
+        self.frame_metadata.clear();
+    }
+
+    /// Attach a `key = value` pair to `frame`, replacing any previous value
+    /// for `key`.
+    ///
+    /// Metadata does not affect transforms; it is context for tools such as
+    /// [`visualize`](BufferTree::visualize), which shows it as a tooltip on
+    /// the frame's node. `frame` does not have to be part of the graph yet,
+    /// so metadata can be set before its first transform arrives.
+    pub fn set_frame_metadata(&mut self, frame: &str, key: &str, value: &str) {
+        let idx = self.index.index(frame);
+        self.frame_metadata
+            .entry(idx)
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// The value stored for `key` on `frame` by
+    /// [`set_frame_metadata`](BufferTree::set_frame_metadata), if any.
+    pub fn get_frame_metadata(&self, frame: &str, key: &str) -> Option<&str> {
+        let idx = self.index.get(frame)?;
+        self.frame_metadata.get(&idx)?.get(key).map(String::as_str)
+    }
+
+    /// Frames at most `hops` edges away from `frame`, in either direction,
+    /// sorted by name. `frame` itself is not included.
+    ///
+    /// Useful for rendering only the neighborhood of a frame in a large
+    /// tree. An unknown frame has no neighbors.
+    pub fn neighbors_within(&self, frame: &str, hops: usize) -> Vec<String> {
+        let Some(start) = self
+            .index
+            .get(frame)
+            .filter(|idx| self.graph.contains_node(*idx))
+        else {
+            return Vec::new();
+        };
+
+        let mut visited = FxHashSet::default();
+        visited.insert(start);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((node, distance)) = queue.pop_front() {
+            if distance == hops {
+                continue;
+            }
+            let parents = self.graph.neighbors_directed(node, Direction::Incoming);
+            let children = self.graph.neighbors_directed(node, Direction::Outgoing);
+            for neighbor in parents.chain(children) {
+                if visited.insert(neighbor) {
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+
+        let mut frames: Vec<String> = visited
+            .into_iter()
+            .filter(|idx| *idx != start)
+            .filter_map(|idx| Some(self.index.get_node(idx)?.name.clone()))
+            .collect();
+        frames.sort();
+        frames
+    }
+
+    /// Copy `root` and all of its descendants into a new buffer.
+    ///
+    /// Every edge below `root` is copied with its full sample history, kind,
+    /// and lock state, so lookups between frames of the subtree give the same
+    /// results as in `self`. Frame metadata of the copied frames comes along.
+    /// `root` becomes a root of the new buffer; its own parent edge is not
+    /// included. The new buffer shares this buffer's config and clock but
+    /// starts without observers.
+    ///
+    /// # Errors
+    ///
+    /// [`TfError::UnknownFrame`] if `root` is not part of the graph.
+    pub fn subtree(&self, root: &str) -> Result<BufferTree, TfError> {
+        let root_idx = self
+            .index
+            .get(root)
+            .filter(|idx| self.graph.contains_node(*idx))
+            .ok_or_else(|| {
+                TfError::UnknownFrame(format!("Root frame '{}' does not exist", root))
+            })?;
+
+        let mut subtree = BufferTree::with_config(self.config.clone());
+        subtree.clock = self.clock.clone();
+        let new_root = subtree.index.index(root);
+        subtree.graph.add_node(new_root);
+        if let Some(metadata) = self.frame_metadata.get(&root_idx) {
+            subtree.frame_metadata.insert(new_root, metadata.clone());
+        }
+
+        let mut queue = VecDeque::from([(root_idx, new_root)]);
+        while let Some((node, new_node)) = queue.pop_front() {
+            let (ancestors, ancestor_ids) = match subtree.index.get_node(new_node) {
+                Some(parent) => {
+                    let mut ancestors = parent.ancestors.clone();
+                    ancestors.push(parent.name.clone());
+                    let mut ancestor_ids = parent.ancestor_ids.clone();
+                    ancestor_ids.push(new_node);
+                    (ancestors, ancestor_ids)
+                }
+                None => continue,
+            };
+
+            for (_, child, history) in self.graph.edges_directed(node, Direction::Outgoing) {
+                let Some(child_node) = self.index.get_node(child) else {
+                    continue;
+                };
+                let new_child = subtree.index.index(&child_node.name);
+                subtree.graph.add_edge(new_node, new_child, history.clone());
+                if let Some(metadata) = self.frame_metadata.get(&child) {
+                    subtree.frame_metadata.insert(new_child, metadata.clone());
+                }
+                if let Some(node) = subtree.index.get_node_mut(new_child) {
+                    node.ancestors = ancestors.clone();
+                    node.ancestor_ids = ancestor_ids.clone();
+                }
+                subtree.topology_generation += 1;
+                queue.push_back((child, new_child));
+            }
+        }
+
+        Ok(subtree)
+    }
+
     /// Recursively update the ancestors of a node and its children
     fn update_subtree_ancestors(
         &mut self,
@@ -304,70 +948,371 @@ impl BufferTree {
     /// batch remain applied. Observers are only notified if the entire batch
     /// succeeds, in a single `on_update` call.
     ///
+    /// With [`BufferConfig::accept_inverse_edges`] set, an update that is the
+    /// inverse of an existing edge is dropped or redirected onto that edge
+    /// (see there); observers then see the updates as actually applied.
+    ///
     /// To insert a single transform, pass a 1-element slice.
     pub fn update(&mut self, updates: &[TransformUpdate]) -> Result<(), TfError> {
-        for update in updates {
-            self.insert_one(
-                &update.from,
-                &update.to,
-                update.stamped_isometry.clone(),
-                update.kind,
-            )?;
+        if self.config.accept_inverse_edges {
+            let mut applied = Vec::with_capacity(updates.len());
+            for update in updates {
+                if let Some(update) = self.resolve_inverse_edge(update)? {
+                    self.insert_update(&update)?;
+                    applied.push(update);
+                }
+            }
+            self.notify_update(&applied);
+            return Ok(());
         }
 
-        if !updates.is_empty() {
-            for observer in &self.observers {
-                observer.on_update(updates);
-            }
+        for update in updates {
+            self.insert_update(update)?;
         }
+        self.notify_update(updates);
 
         Ok(())
     }
 
-    fn insert_one(
+    fn insert_update(&mut self, update: &TransformUpdate) -> Result<(), TfError> {
+        self.check_not_locked(&update.from, &update.to)?;
+        self.notify_sample_changed(update);
+        self.insert_one(
+            &update.from,
+            &update.to,
+            update.stamped_isometry.clone(),
+            update.kind,
+            update.quality,
+        )
+    }
+
+    /// Insert a static transform and lock its edge against further updates.
+    ///
+    /// Meant for true constants such as sensor calibrations: once set, any
+    /// later update to `from -> to`, static or dynamic, is rejected, so a
+    /// misbehaving publisher cannot overwrite the value. Observers are
+    /// notified as for a regular [`update`](BufferTree::update).
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::LockedTransform`] if the edge is already locked.
+    /// - [`TfError::InvalidGraph`] if the edge already exists as a dynamic
+    ///   edge or in the opposite direction `to -> from`, or would create a
+    ///   cycle or give `to` a second parent.
+    pub fn set_static(
         &mut self,
         from: &str,
         to: &str,
-        stamped_isometry: StampedIsometry,
-        kind: TransformType,
+        transform: StampedIsometry,
     ) -> Result<(), TfError> {
-        let from_idx = self.index.index(from);
-        let to_idx = self.index.index(to);
+        if let Some(history) = self.edge(from, to) {
+            if !matches!(history.kind, TransformType::Static) {
+                return Err(TfError::InvalidGraph(format!(
+                    "Cannot lock dynamic edge {} -> {} as static",
+                    from, to
+                )));
+            }
+        }
+        // With `accept_inverse_edges` the update would land on the reverse
+        // edge, leaving nothing at `from -> to` to lock.
+        if self.edge(to, from).is_some() {
+            return Err(TfError::InvalidGraph(format!(
+                "Cannot lock {} -> {}: the edge exists as {} -> {}",
+                from, to, to, from
+            )));
+        }
+        self.update(&[TransformUpdate::new(
+            from,
+            to,
+            transform,
+            TransformType::Static,
+        )])?;
+        let history = self
+            .index
+            .get(from)
+            .zip(self.index.get(to))
+            .and_then(|(from_idx, to_idx)| self.graph.edge_weight_mut(from_idx, to_idx))
+            .ok_or_else(|| {
+                TfError::CouldNotFindTransform(format!(
+                    "Edge {} -> {} was not created by set_static",
+                    from, to
+                ))
+            })?;
+        history.locked = true;
+        Ok(())
+    }
 
-        if !self.graph.contains_node(from_idx) {
-            self.graph.add_node(from_idx);
+    fn edge(&self, from: &str, to: &str) -> Option<&TransformHistory> {
+        let from_idx = self.index.get(from)?;
+        let to_idx = self.index.get(to)?;
+        self.graph.edge_weight(from_idx, to_idx)
+    }
+
+    fn check_not_locked(&self, from: &str, to: &str) -> Result<(), TfError> {
+        if self.edge(from, to).is_some_and(|history| history.locked) {
+            return Err(TfError::LockedTransform(format!(
+                "Edge {} -> {} was locked by set_static and cannot be updated",
+                from, to
+            )));
         }
-        if !self.graph.contains_node(to_idx) {
-            self.graph.add_node(to_idx);
+        Ok(())
+    }
+
+    fn notify_update(&self, updates: &[TransformUpdate]) {
+        if !updates.is_empty() {
+            for observer in &self.observers {
+                observer.on_update(updates);
+            }
         }
+    }
 
-        if !self.graph.contains_edge(from_idx, to_idx) {
-            self.graph.add_edge(
-                from_idx,
-                to_idx,
-                TransformHistory::new(kind, self.config.buffer_window),
-            );
-            if is_cyclic_undirected(&self.graph)
-                || self
-                    .graph
-                    .neighbors_directed(to_idx, petgraph::Direction::Incoming)
-                    .count()
-                    > 1
+    /// Map an update `B -> A` onto an existing edge `A -> B`.
+    ///
+    /// Returns the update unchanged if there is no such reverse edge,
+    /// `None` if the reverse edge already holds the matching sample, or the
+    /// inverted update for `A -> B` if the stamp is new on that edge.
+    fn resolve_inverse_edge(
+        &self,
+        update: &TransformUpdate,
+    ) -> Result<Option<TransformUpdate>, TfError> {
+        let (Some(from_idx), Some(to_idx)) =
+            (self.index.get(&update.from), self.index.get(&update.to))
+        else {
+            return Ok(Some(update.clone()));
+        };
+        if self.graph.contains_edge(from_idx, to_idx) {
+            return Ok(Some(update.clone()));
+        }
+        let Some(reverse) = self.graph.edge_weight(to_idx, from_idx) else {
+            return Ok(Some(update.clone()));
+        };
+
+        let inverted = StampedIsometry {
+            isometry: update.stamped_isometry.isometry.inverse(),
+            stamp: update.stamped_isometry.stamp,
+        };
+        let existing = match reverse.kind {
+            TransformType::Static => reverse.history.back(),
+            TransformType::Dynamic => reverse
+                .history
+                .iter()
+                .find(|sample| sample.stamp == inverted.stamp),
+        };
+
+        match existing {
+            Some(existing)
+                if isometries_differ(
+                    &existing.isometry,
+                    &inverted.isometry,
+                    INVERSE_EDGE_TOLERANCE,
+                ) =>
             {
-                // Remove the edge and nodes if they have no other edges
-                self.graph.remove_edge(from_idx, to_idx);
-                if self
-                    .graph
-                    .neighbors_directed(to_idx, petgraph::Direction::Incoming)
-                    .count()
-                    < 1
-                    && self
-                        .graph
-                        .neighbors_directed(to_idx, petgraph::Direction::Outgoing)
-                        .count()
-                        < 1
-                {
-                    self.graph.remove_node(to_idx);
+                Err(TfError::InvalidGraph(format!(
+                    "Update {} -> {} contradicts the existing edge {} -> {}",
+                    update.from, update.to, update.to, update.from
+                )))
+            }
+            Some(_) => Ok(None),
+            None => Ok(Some(TransformUpdate {
+                quality: update.quality,
+                ..TransformUpdate::new(
+                    update.to.clone(),
+                    update.from.clone(),
+                    inverted,
+                    reverse.kind,
+                )
+            })),
+        }
+    }
+
+    /// Insert a batch of transforms all-or-nothing.
+    ///
+    /// Like [`update`](BufferTree::update), but every update is validated
+    /// (cycle / multiple parents check) against the buffer *and* the rest of
+    /// the batch before any of them is applied. If any update would be
+    /// rejected, the error is returned and the buffer is left untouched.
+    /// Use this when several frames move together (e.g. the links of a rigid
+    /// body at one stamp) and a half-applied batch would be inconsistent.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::InvalidGraph`] if any update would create a cycle or
+    ///   give a frame a second parent. Nothing is inserted in that case.
+    pub fn update_batch(&mut self, updates: &[TransformUpdate]) -> Result<(), TfError> {
+        self.validate_batch(updates)?;
+        self.update(updates)
+    }
+
+    /// Check that inserting `updates` in order keeps the graph a forest,
+    /// without modifying the buffer.
+    fn validate_batch(&self, updates: &[TransformUpdate]) -> Result<(), TfError> {
+        // Union-find over tree roots: frames already in the buffer start in
+        // the tree of their root, new frames start in a tree of their own.
+        let mut trees: FxHashMap<String, String> = FxHashMap::default();
+        let mut new_edges: FxHashSet<(&str, &str)> = FxHashSet::default();
+        let mut new_children: FxHashSet<&str> = FxHashSet::default();
+
+        fn find(trees: &FxHashMap<String, String>, mut key: String) -> String {
+            while let Some(parent) = trees.get(&key) {
+                key = parent.clone();
+            }
+            key
+        }
+
+        for update in updates {
+            let (from, to) = (update.from.as_str(), update.to.as_str());
+            self.check_not_locked(from, to)?;
+            let existing = match (self.index.get(from), self.index.get(to)) {
+                (Some(from_idx), Some(to_idx)) => self.graph.contains_edge(from_idx, to_idx),
+                _ => false,
+            };
+            if existing || new_edges.contains(&(from, to)) {
+                continue;
+            }
+            if self.config.accept_inverse_edges {
+                let reverse_exists = match (self.index.get(from), self.index.get(to)) {
+                    (Some(from_idx), Some(to_idx)) => self.graph.contains_edge(to_idx, from_idx),
+                    _ => false,
+                };
+                if reverse_exists {
+                    // Rejects contradicting inverses up front; matching ones
+                    // are dropped or redirected when applied.
+                    self.resolve_inverse_edge(update)?;
+                    continue;
+                }
+                if new_edges.contains(&(to, from)) {
+                    continue;
+                }
+            }
+
+            let has_parent = new_children.contains(to)
+                || self.index.get(to).is_some_and(|to_idx| {
+                    self.graph
+                        .neighbors_directed(to_idx, petgraph::Direction::Incoming)
+                        .next()
+                        .is_some()
+                });
+            let from_tree = find(&trees, self.tree_root(from));
+            let to_tree = find(&trees, self.tree_root(to));
+            if has_parent || from_tree == to_tree {
+                return Err(TfError::InvalidGraph(format!(
+                    "Graph cycle or multiple parents detected when adding edge {} -> {}",
+                    from, to
+                )));
+            }
+
+            trees.insert(to_tree, from_tree);
+            new_edges.insert((from, to));
+            new_children.insert(to);
+        }
+
+        Ok(())
+    }
+
+    /// Name of the root of the tree `frame` belongs to (`frame` itself if it
+    /// is a root or not in the buffer yet).
+    fn tree_root(&self, frame: &str) -> String {
+        self.index
+            .get(frame)
+            .and_then(|idx| self.index.get_node(idx))
+            .and_then(|node| node.ancestors.first().cloned())
+            .unwrap_or_else(|| frame.to_string())
+    }
+
+    /// Fire [`BufferObserver::on_static_changed`] if `update` targets an
+    /// existing static edge, and [`BufferObserver::on_conflict`] if it has
+    /// the stamp of the edge's newest sample, when it carries a different
+    /// transform than the one currently stored.
+    fn notify_sample_changed(&self, update: &TransformUpdate) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let (Some(from_idx), Some(to_idx)) =
+            (self.index.get(&update.from), self.index.get(&update.to))
+        else {
+            return;
+        };
+        let Some(history) = self.graph.edge_weight(from_idx, to_idx) else {
+            return;
+        };
+        let Some(previous) = history.history.back() else {
+            return;
+        };
+        if !isometries_differ(
+            &previous.isometry,
+            &update.stamped_isometry.isometry,
+            SAMPLE_EQUALITY_TOLERANCE,
+        ) {
+            return;
+        }
+        let is_static = matches!(history.kind, TransformType::Static);
+        let is_conflict = previous.stamp == update.stamped_isometry.stamp;
+        for observer in &self.observers {
+            if is_static {
+                observer.on_static_changed(
+                    &update.from,
+                    &update.to,
+                    previous,
+                    &update.stamped_isometry,
+                );
+            }
+            if is_conflict {
+                observer.on_conflict(&update.from, &update.to, previous, &update.stamped_isometry);
+            }
+        }
+    }
+
+    fn insert_one(
+        &mut self,
+        from: &str,
+        to: &str,
+        stamped_isometry: StampedIsometry,
+        kind: TransformType,
+        quality: Option<f32>,
+    ) -> Result<(), TfError> {
+        let from_idx = self.index.index(from);
+        let to_idx = self.index.index(to);
+
+        if !self.graph.contains_node(from_idx) {
+            self.graph.add_node(from_idx);
+        }
+        if !self.graph.contains_node(to_idx) {
+            self.graph.add_node(to_idx);
+        }
+
+        if !self.graph.contains_edge(from_idx, to_idx) {
+            self.graph.add_edge(
+                from_idx,
+                to_idx,
+                TransformHistory::new(
+                    kind,
+                    self.config.buffer_window,
+                    self.config.max_interpolation_gap,
+                    self.config.interpolation,
+                    self.config.initial_history_capacity,
+                ),
+            );
+            if is_cyclic_undirected(&self.graph)
+                || self
+                    .graph
+                    .neighbors_directed(to_idx, petgraph::Direction::Incoming)
+                    .count()
+                    > 1
+            {
+                // Remove the edge and nodes if they have no other edges
+                self.graph.remove_edge(from_idx, to_idx);
+                if self
+                    .graph
+                    .neighbors_directed(to_idx, petgraph::Direction::Incoming)
+                    .count()
+                    < 1
+                    && self
+                        .graph
+                        .neighbors_directed(to_idx, petgraph::Direction::Outgoing)
+                        .count()
+                        < 1
+                {
+                    self.graph.remove_node(to_idx);
                 }
                 if self
                     .graph
@@ -399,12 +1344,11 @@ impl BufferTree {
                 new_ancestor_ids.push(from_idx);
             }
             self.update_subtree_ancestors(to_idx, new_ancestors, new_ancestor_ids);
+            self.topology_generation += 1;
         }
 
-        self.graph
-            .edge_weight_mut(from_idx, to_idx)
-            .unwrap()
-            .update(stamped_isometry);
+        let history = self.graph.edge_weight_mut(from_idx, to_idx).unwrap();
+        history.update(stamped_isometry, quality);
         Ok(())
     }
 
@@ -413,16 +1357,46 @@ impl BufferTree {
     /// We have to consider that "form" and "to" are on different branches therefore we
     /// traverse the tree upwards from both nodes until we either hit the other node or the root
     /// Afterwards we prune the leftover path above the connection point
-    #[allow(dead_code)]
-    fn find_path(&self, from: &str, to: &str) -> Option<Vec<usize>> {
-        let from_idx = self.index.get(from)?;
-        let to_idx = self.index.get(to)?;
-        self.find_path_by_id(from_idx, to_idx)
+    ///
+    /// Fails with [`TfError::UnknownFrame`] if either frame is unknown and
+    /// with [`TfError::NoPath`] if they are in separate trees.
+    fn find_path(&self, from: &str, to: &str) -> Result<Vec<usize>, TfError> {
+        // Get node IDs upfront to avoid redundant hash lookups. The index
+        // also names frames that only carry metadata or were removed, so
+        // only frames in the graph count as known, as in `has_frame`.
+        let in_graph = |name: &str| {
+            self.index
+                .get(name)
+                .filter(|&idx| self.graph.contains_node(idx))
+        };
+        let from_idx = in_graph(from).ok_or_else(|| {
+            TfError::UnknownFrame(format!("Source frame '{}' does not exist", from))
+        })?;
+        let to_idx = in_graph(to).ok_or_else(|| {
+            TfError::UnknownFrame(format!("Target frame '{}' does not exist", to))
+        })?;
+
+        self.find_path_by_id(from_idx, to_idx)?.ok_or_else(|| {
+            TfError::NoPath(format!(
+                "Could not find path between '{}' and '{}'",
+                from, to
+            ))
+        })
     }
 
-    fn find_path_by_id(&self, from_idx: usize, to_idx: usize) -> Option<Vec<usize>> {
-        let from_node = self.index.get_node(from_idx)?;
-        let to_node = self.index.get_node(to_idx)?;
+    /// `Ok(None)` if the frames share no root. Fails with
+    /// [`TfError::InvalidGraph`] if the path would be longer than
+    /// `max_path_length` edges, which only a corrupted graph produces.
+    fn find_path_by_id(
+        &self,
+        from_idx: usize,
+        to_idx: usize,
+    ) -> Result<Option<Vec<usize>>, TfError> {
+        let (Some(from_node), Some(to_node)) =
+            (self.index.get_node(from_idx), self.index.get_node(to_idx))
+        else {
+            return Ok(None);
+        };
 
         // Build full paths from root to each node using pre-computed ancestors
         let ancestor_ids_from = &from_node.ancestor_ids;
@@ -448,12 +1422,21 @@ impl BufferTree {
 
         if split_idx == 0 {
             // No common ancestor
-            return None;
+            return Ok(None);
         }
 
         // Pre-allocate result_path with exact size
         let up_len = path_from_root.len() - split_idx;
         let down_len = path_to_root.len() - split_idx + 1;
+        if up_len + down_len - 1 > self.config.max_path_length {
+            return Err(TfError::InvalidGraph(format!(
+                "Path from '{}' to '{}' has {} edges, more than the maximum of {}",
+                from_node.name,
+                to_node.name,
+                up_len + down_len - 1,
+                self.config.max_path_length
+            )));
+        }
         let mut result_path = Vec::with_capacity(up_len + down_len);
 
         // Add part from 'from' up to LCA (reversed)
@@ -464,7 +1447,7 @@ impl BufferTree {
         // Add part from LCA down to 'to' (use extend_from_slice for better performance)
         result_path.extend_from_slice(&path_to_root[split_idx - 1..]);
 
-        Some(result_path)
+        Ok(Some(result_path))
     }
 
     /// Look up a transform between two frames using the most recent sample
@@ -472,9 +1455,11 @@ impl BufferTree {
     ///
     /// Walks the path from `from` to `to` in the graph (edges are traversed
     /// either direction; reverse edges are inverted) and composes the
-    /// per-edge latest poses into a single [`StampedIsometry`]. The returned
-    /// `stamp` is the *maximum* timestamp seen across the composed edges, so
-    /// it reflects the most stale-bound part of the chain.
+    /// per-edge latest poses into a single [`StampedIsometry`]. As with tf2's
+    /// latest common time, the returned `stamp` is the *oldest* of the newest
+    /// samples on the dynamic edges of the path: the composite is only known
+    /// to be current as of then. Static edges are valid at any time and do
+    /// not count; a path of only static edges gets its newest static stamp.
     ///
     /// **No staleness check is performed** — this returns whatever the
     /// buffer last saw, which may be arbitrarily old. Use
@@ -483,47 +1468,38 @@ impl BufferTree {
     ///
     /// # Errors
     ///
-    /// - [`TfError::CouldNotFindTransform`] if either frame is unknown or
-    ///   no path connects them.
+    /// - [`TfError::UnknownFrame`] if either frame is unknown.
+    /// - [`TfError::NoPath`] if no path connects them.
+    /// - [`TfError::InvalidGraph`] if the path is longer than
+    ///   [`BufferConfig::max_path_length`].
     pub fn lookup_latest_transform(
         &self,
         from: &str,
         to: &str,
     ) -> Result<StampedIsometry, TfError> {
-        // Get node IDs upfront to avoid redundant hash lookups
-        let from_idx = self.index.get(from).ok_or_else(|| {
-            TfError::CouldNotFindTransform(format!("Source frame '{}' does not exist", from))
-        })?;
-        let to_idx = self.index.get(to).ok_or_else(|| {
-            TfError::CouldNotFindTransform(format!("Target frame '{}' does not exist", to))
+        let path = self.find_path(from, to)?;
+        let mut oldest_dynamic: Option<i64> = None;
+        let mut newest_static: i64 = 0;
+        let isometry = self.compute_transform_along_path(&path, |history| {
+            let latest_transform = history
+                .history
+                .back()
+                .ok_or_else(|| TfError::CouldNotFindTransform("No samples".to_string()))?;
+            match history.kind {
+                TransformType::Dynamic => {
+                    oldest_dynamic = Some(oldest_dynamic.map_or(latest_transform.stamp, |oldest| {
+                        oldest.min(latest_transform.stamp)
+                    }))
+                }
+                TransformType::Static => newest_static = newest_static.max(latest_transform.stamp),
+            }
+            Ok(latest_transform.isometry)
         })?;
 
-        let path = self.find_path_by_id(from_idx, to_idx);
-        match path {
-            Some(path) => {
-                let mut max_stamp: i64 = 0;
-                let isometry = self.compute_transform_along_path(&path, |history| {
-                    let latest_transform = history
-                        .history
-                        .back()
-                        .ok_or(TfError::CouldNotFindTransform(format!("")))?;
-                    // Track the maximum timestamp across all edges
-                    if latest_transform.stamp > max_stamp {
-                        max_stamp = latest_transform.stamp;
-                    }
-                    Ok(latest_transform.isometry)
-                })?;
-
-                Ok(StampedIsometry {
-                    isometry,
-                    stamp: max_stamp,
-                })
-            }
-            None => Err(TfError::CouldNotFindTransform(format!(
-                "Could not find path between '{}' and '{}'",
-                from, to
-            ))),
-        }
+        Ok(StampedIsometry {
+            isometry,
+            stamp: oldest_dynamic.unwrap_or(newest_static),
+        })
     }
 
     /// Look up a transform between two frames at a specific timestamp.
@@ -531,7 +1507,13 @@ impl BufferTree {
     /// Walks the path from `from` to `to` and, on each edge, returns the
     /// stored sample whose stamp matches `time` exactly, or interpolates
     /// (lerp/slerp) between the surrounding samples when there is no exact
-    /// hit. Static edges always return their single stored pose.
+    /// hit. Static edges always return their newest stored pose, whatever
+    /// `time` is — if a static edge is updated with a different transform,
+    /// the new pose replaces the old one for every query time (observers are
+    /// told via [`BufferObserver::on_static_changed`]).
+    ///
+    /// Looking up a frame relative to itself returns the identity stamped at
+    /// `time`, as in tf2. The frame does not need to exist in the buffer.
     ///
     /// `time` is in nanoseconds since the Unix epoch.
     ///
@@ -567,451 +1549,1298 @@ impl BufferTree {
     ///
     /// # Errors
     ///
-    /// - [`TfError::CouldNotFindTransform`] — either frame is unknown,
-    ///   no path connects them, or a dynamic edge along the path has fewer
-    ///   than 2 samples (so it cannot be interpolated).
+    /// - [`TfError::UnknownFrame`] — either frame has never been added.
+    /// - [`TfError::NoPath`] — both frames are known but in different trees.
+    /// - [`TfError::CouldNotFindTransform`] — a dynamic edge along the path
+    ///   has no samples. An edge with a single sample resolves only at its exact
+    ///   stamp; other times report past or future below.
     /// - [`TfError::AttemptedLookupInPast`] — `time` is older than the
     ///   oldest sample on some edge.
     /// - [`TfError::AttemptedLookUpInFuture`] — `time` is newer than the
     ///   newest sample on some edge.
+    /// - [`TfError::InvalidGraph`] — the path is longer than
+    ///   [`BufferConfig::max_path_length`], which means the graph is
+    ///   corrupted.
     pub fn lookup_transform(
         &self,
         from: &str,
         to: &str,
         time: i64,
     ) -> Result<StampedIsometry, TfError> {
-        // Get node IDs upfront to avoid redundant hash lookups
-        let from_idx = self.index.get(from).ok_or_else(|| {
-            TfError::CouldNotFindTransform(format!("Source frame '{}' does not exist", from))
+        let path = self.lookup_path(from, to)?;
+        let isometry = self.compute_transform_along_path(&path, |history| {
+            history.interpolate_isometry_at_time(time)
         })?;
-        let to_idx = self.index.get(to).ok_or_else(|| {
-            TfError::CouldNotFindTransform(format!("Target frame '{}' does not exist", to))
-        })?;
-
-        let path = self.find_path_by_id(from_idx, to_idx);
 
-        match path {
-            Some(path) => {
-                let isometry = self.compute_transform_along_path(&path, |history| {
-                    history.interpolate_isometry_at_time(time)
-                })?;
-
-                Ok(StampedIsometry {
-                    isometry,
-                    stamp: time,
-                })
-            }
-            None => Err(TfError::CouldNotFindTransform(format!(
-                "Could not find path between '{}' and '{}'",
-                from, to
-            ))),
-        }
+        Ok(StampedIsometry {
+            isometry,
+            stamp: time,
+        })
     }
 
-    /// Render the current graph as a Graphviz DOT-format string.
+    /// Whether [`lookup_transform`](BufferTree::lookup_transform) would
+    /// succeed for `from -> to` at `time`.
     ///
-    /// Each node is labeled with its frame name; each edge is labeled with
-    /// the most recent translation (`t=[x, y, z]`), Euler rotation
-    /// (`r=[r, p, y]`), and timestamp in seconds. Edges with no samples are
-    /// labeled `"No transforms"`.
-    pub fn visualize(&self) -> String {
-        // Create a mapping from index back to node name
-        // Convert the graph to DOT format manually
-        let mut dot = String::from("digraph {\n");
+    /// `true` only if both frames are part of the graph, a path connects
+    /// them, and every edge on it can be evaluated at `time` (no past,
+    /// future, or gap errors). The edges are checked one by one without
+    /// composing the transform. Mirrors tf2's `Buffer.can_transform`.
+    pub fn can_transform(&self, from: &str, to: &str, time: i64) -> bool {
+        self.path_is_usable(from, to, |history| {
+            history.interpolate_isometry_at_time(time).is_ok()
+        })
+    }
 
-        // Add nodes
-        for node in self.graph.nodes() {
-            let name = &self.index.get_node(node).unwrap().name;
-            dot.push_str(&format!("    {} [label=\"{}\"]\n", node, name));
-        }
+    /// Whether [`lookup_latest_transform`](BufferTree::lookup_latest_transform)
+    /// would succeed for `from -> to`: both frames are part of the graph, a
+    /// path connects them, and every edge on it holds at least one sample.
+    pub fn can_transform_latest(&self, from: &str, to: &str) -> bool {
+        self.path_is_usable(from, to, |history| !history.history.is_empty())
+    }
 
-        // Add edges with transform information
-        for edge in self.graph.all_edges() {
-            if let Some(latest) = edge.2.history.back() {
-                let translation = latest.isometry.translation.vector;
-                let rotation = latest.isometry.rotation.euler_angles();
-                dot.push_str(&format!(
-                    "    {} -> {} [label=\"t=[{:.3}, {:.3}, {:.3}]\\nr=[{:.3}, {:.3}, {:.3}]\\ntime={:.3}s\"]\n",
-                    edge.0, edge.1,
-                    translation[0], translation[1], translation[2],
-                    rotation.0, rotation.1, rotation.2,
-                    latest.stamp_secs()
-                ));
-            } else {
-                dot.push_str(&format!(
-                    "    {} -> {} [label=\"No transforms\"]\n",
-                    edge.0, edge.1
-                ));
-            }
+    /// Whether `from` and `to` are known and connected, and `usable` holds
+    /// for every edge on the path between them.
+    fn path_is_usable(
+        &self,
+        from: &str,
+        to: &str,
+        usable: impl Fn(&TransformHistory) -> bool,
+    ) -> bool {
+        if !self.has_frame(from) || !self.has_frame(to) {
+            return false;
         }
-
-        dot.push_str("}");
-        dot
+        let Ok(path) = self.lookup_path(from, to) else {
+            return false;
+        };
+        path.windows(2).all(|pair| {
+            self.graph
+                .edge_weight(pair[0], pair[1])
+                .or_else(|| self.graph.edge_weight(pair[1], pair[0]))
+                .is_some_and(&usable)
+        })
     }
 
-    /// Write the current graph to disk as both `graph.dot` and `graph.pdf`
-    /// under [`BufferConfig::save_path`].
+    /// Like [`lookup_transform`](BufferTree::lookup_transform), but also
+    /// reports whether the result was interpolated.
     ///
-    /// The DOT file always succeeds; the PDF is generated by invoking the
-    /// external `dot` binary from Graphviz. If Graphviz is not installed the
-    /// PDF step prints a warning to stderr but the function still returns
-    /// `Ok(())` — only filesystem I/O errors on the DOT file propagate.
-    pub fn save_visualization(&self) -> std::io::Result<()> {
-        let filename = &self.config.save_path;
-        println!("Saving visualization to {}/graph.(dot/pdf)", filename);
-        // Save DOT file
-        let dot_content = self.visualize();
-        let dot_filename = format!("{}/graph.dot", filename);
-        let mut file = File::create(&dot_filename)?;
-        file.write_all(dot_content.as_bytes())?;
+    /// The flag is `false` only if every dynamic edge on the path has a
+    /// sample stamped exactly at `time`, so the result is composed of stored
+    /// samples alone. Useful for flagging derived data downstream.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_transform_detailed(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+    ) -> Result<(StampedIsometry, bool), TfError> {
+        let path = self.lookup_path(from, to)?;
+        let mut interpolated = false;
+        let isometry = self.compute_transform_along_path(&path, |history| {
+            interpolated |= !history.has_sample_at(time);
+            history.interpolate_isometry_at_time(time)
+        })?;
 
-        // Generate PDF using dot command
-        let pdf_filename = format!("{}/graph.pdf", filename);
-        let output = Command::new("dot")
-            .args(["-Tpdf", &dot_filename, "-o", &pdf_filename])
-            .output()?;
+        Ok((
+            StampedIsometry {
+                isometry,
+                stamp: time,
+            },
+            interpolated,
+        ))
+    }
 
-        if !output.status.success() {
-            eprintln!(
-                "Warning: Failed to generate PDF. Is Graphviz installed? Error: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
+    /// Like [`lookup_transform`](BufferTree::lookup_transform), but fall
+    /// back to [`lookup_latest_transform`](BufferTree::lookup_latest_transform)
+    /// if `time` is newer than the data on the path.
+    ///
+    /// The flag is `true` if the fallback was taken; the returned stamp is
+    /// then that of the latest data rather than `time`. Meant for control
+    /// loops that would rather use a slightly stale transform than drop a
+    /// cycle over a few milliseconds of clock skew.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform), except
+    /// that [`TfError::AttemptedLookUpInFuture`] is never returned.
+    pub fn lookup_transform_or_latest(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+    ) -> Result<(StampedIsometry, bool), TfError> {
+        match self.lookup_transform(from, to, time) {
+            Ok(transform) => Ok((transform, false)),
+            Err(TfError::AttemptedLookUpInFuture(_)) => {
+                Ok((self.lookup_latest_transform(from, to)?, true))
+            }
+            Err(e) => Err(e),
         }
-
-        Ok(())
     }
 
-    /// Helper function to compute transforms along a path
-    /// This function handles the common logic of iterating through a path and computing
-    /// the cumulative transform. The transform_getter function determines how to get
-    /// the transform for each edge (latest vs interpolated at time).
-    fn compute_transform_along_path<F>(
+    /// Average velocity of `to` relative to `from` between `t0` and `t1`.
+    ///
+    /// Looks up the transform at both times and returns
+    /// `[vx, vy, vz, wx, wy, wz]`: the translation change and the scaled
+    /// rotation axis of the rotation change, both expressed in `from` and
+    /// divided by `t1 - t0` in seconds. Times are in nanoseconds since the
+    /// Unix epoch; `t1` before `t0` gives the negated velocity.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::CouldNotFindTransform`] if `t0 == t1`.
+    /// - Any error [`lookup_transform`](BufferTree::lookup_transform) returns
+    ///   for either time, e.g. [`TfError::AttemptedLookupInPast`].
+    pub fn lookup_velocity(
         &self,
-        path: &[usize],
-        mut transform_getter: F,
-    ) -> Result<Isometry3<f64>, TfError>
-    where
-        F: FnMut(&TransformHistory) -> Result<Isometry3<f64>, TfError>,
-    {
-        let mut isometry = Isometry3::identity();
+        from: &str,
+        to: &str,
+        t0: i64,
+        t1: i64,
+    ) -> Result<[f64; 6], TfError> {
+        if t0 == t1 {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "Cannot compute velocity of '{}' relative to '{}' over a zero time interval",
+                to, from
+            )));
+        }
+        let start = self.lookup_transform(from, to, t0)?.isometry;
+        let end = self.lookup_transform(from, to, t1)?.isometry;
+        let dt = nanos_to_secs(t1 - t0);
+
+        let linear = (end.translation.vector - start.translation.vector) / dt;
+        let angular = (end.rotation * start.rotation.inverse()).scaled_axis() / dt;
+        Ok([
+            linear.x, linear.y, linear.z, angular.x, angular.y, angular.z,
+        ])
+    }
 
-        // Use direct indexing instead of windows(2) for better performance
-        for i in 0..path.len().saturating_sub(1) {
-            let from_idx = path[i];
-            let to_idx = path[i + 1];
+    /// Look up the transform along an explicit chain of frames at `time`.
+    ///
+    /// `chain` is a `/`-separated list of frame names, e.g.
+    /// `"base/shoulder/elbow"`, and the result is the transform from the
+    /// first frame to the last. Unlike [`lookup_transform`](BufferTree::lookup_transform),
+    /// no path is searched: every consecutive pair must be joined by a
+    /// direct edge (in either direction), so a chain that no longer matches
+    /// the tree fails instead of silently taking another route. A single
+    /// frame gives the identity.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::UnknownFrame`] if a frame in the chain is unknown.
+    /// - [`TfError::CouldNotFindTransform`] if two consecutive frames are
+    ///   not directly connected.
+    /// - Any error [`lookup_transform`](BufferTree::lookup_transform) would
+    ///   return for an edge of the chain at `time`.
+    pub fn lookup_chain_str(&self, chain: &str, time: i64) -> Result<StampedIsometry, TfError> {
+        let frames: Vec<&str> = chain.split('/').collect();
+        let path = frames
+            .iter()
+            .map(|frame| {
+                self.index
+                    .get(frame)
+                    .filter(|idx| self.graph.contains_node(*idx))
+                    .ok_or_else(|| {
+                        TfError::UnknownFrame(format!(
+                            "Frame '{}' in chain '{}' does not exist",
+                            frame, chain
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<usize>, TfError>>()?;
 
-            // Try forward edge first, then reverse - avoids redundant contains_edge check
-            if let Some(edge_weight) = self.graph.edge_weight(from_idx, to_idx) {
-                isometry *= transform_getter(edge_weight)?;
-            } else if let Some(edge_weight) = self.graph.edge_weight(to_idx, from_idx) {
-                isometry *= transform_getter(edge_weight)?.inverse();
-            } else {
+        for (i, pair) in path.windows(2).enumerate() {
+            if !self.graph.contains_edge(pair[0], pair[1])
+                && !self.graph.contains_edge(pair[1], pair[0])
+            {
                 return Err(TfError::CouldNotFindTransform(format!(
-                    "Edge transform not found for edge {} -> {}",
-                    from_idx, to_idx
+                    "No direct edge between '{}' and '{}' in chain '{}'",
+                    frames[i],
+                    frames[i + 1],
+                    chain
                 )));
             }
         }
 
-        Ok(isometry)
+        let isometry = self.compute_transform_along_path(&path, |history| {
+            history.interpolate_isometry_at_time(time)
+        })?;
+        Ok(StampedIsometry {
+            isometry,
+            stamp: time,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{BufferTree, StampedIsometry, TransformType, TransformUpdate};
-    use approx::assert_relative_eq;
-    use nalgebra::geometry::Isometry3;
-    use std::sync::Mutex;
-
-    #[test]
-    fn test_buffer_tree_update() {
-        let mut buffer_tree = BufferTree::new();
-
-        let source = "A";
-        let target = "B";
-
-        let stamped_isometry =
-            StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+    /// Look up `from -> to` at `time` and project it onto the ground plane.
+    ///
+    /// The result keeps x, y, and yaw, with z set to 0 and roll and pitch
+    /// dropped, so it can be used as a 2D pose wherever a [`StampedIsometry`]
+    /// is expected. Yaw is the heading of the ZYX Euler decomposition, the
+    /// same as [`StampedIsometry::euler_angles`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_ground_projection(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+    ) -> Result<StampedIsometry, TfError> {
+        let transform = self.lookup_transform(from, to, time)?;
+        let translation = transform.isometry.translation.vector;
+        let (_, _, yaw) = transform.isometry.rotation.euler_angles();
+
+        Ok(StampedIsometry {
+            isometry: Isometry3::from_parts(
+                Translation3::new(translation.x, translation.y, 0.0),
+                UnitQuaternion::from_euler_angles(0.0, 0.0, yaw),
+            ),
+            stamp: transform.stamp,
+        })
+    }
 
-        // Add first transformation
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                source,
-                target,
-                stamped_isometry,
-                TransformType::Static,
-            )])
-            .unwrap();
+    /// Look up `from -> to` at `time` and post-multiply the result by
+    /// `offset`, expressed in `to`.
+    ///
+    /// Equivalent to inserting a static edge from `to` to a temporary frame
+    /// with transform `offset` and looking that frame up, without adding it
+    /// to the buffer. Useful for comparing a commanded pose against an
+    /// actual one or trying out a calibration.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_transform_with_offset(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+        offset: Isometry3<f64>,
+    ) -> Result<StampedIsometry, TfError> {
+        let transform = self.lookup_transform(from, to, time)?;
+        Ok(StampedIsometry {
+            isometry: transform.isometry * offset,
+            stamp: transform.stamp,
+        })
+    }
 
-        // Test lookup
-        let result = buffer_tree.lookup_latest_transform(&source, &target);
-        assert!(result.is_ok());
+    /// Rotation part of [`lookup_transform`](BufferTree::lookup_transform),
+    /// for callers that only need the relative orientation.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_rotation(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+    ) -> Result<UnitQuaternion<f64>, TfError> {
+        Ok(self.lookup_transform(from, to, time)?.isometry.rotation)
     }
 
-    #[test]
-    fn test_separation_works() {
-        // This test verifies that the core logic works without PyO3
-        let mut buffer_tree = BufferTree::new();
+    /// Translation part of [`lookup_transform`](BufferTree::lookup_transform)
+    /// as `[x, y, z]`, for callers that only need the relative position.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_translation(&self, from: &str, to: &str, time: i64) -> Result<[f64; 3], TfError> {
+        Ok(self.lookup_transform(from, to, time)?.translation())
+    }
 
-        let transform = StampedIsometry::from_secs([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+    /// Path between two named frames, with the errors the lookups report.
+    fn lookup_path(&self, from: &str, to: &str) -> Result<Vec<usize>, TfError> {
+        // A frame relative to itself: an empty path composes to the identity.
+        if from == to {
+            return Ok(Vec::new());
+        }
 
-        let result = buffer_tree.update(&[TransformUpdate::new(
-            "base_link",
-            "target_link",
-            transform,
-            TransformType::Static,
-        )]);
+        self.find_path(from, to)
+    }
 
-        assert!(result.is_ok());
+    /// Look up a transform at `time` together with its age relative to `now`.
+    ///
+    /// Returns the same transform as
+    /// [`lookup_transform`](BufferTree::lookup_transform) and `now` minus its
+    /// stamp, in seconds. Both times are in nanoseconds since the Unix epoch.
+    /// A negative age means the transform is stamped after `now`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_with_age(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+        now: i64,
+    ) -> Result<(StampedIsometry, f64), TfError> {
+        let transform = self.lookup_transform(from, to, time)?;
+        let age_secs = nanos_to_secs(now.saturating_sub(transform.stamp));
+        Ok((transform, age_secs))
+    }
 
-        let lookup_result = buffer_tree.lookup_latest_transform("base_link", "target_link");
+    /// Look up a transform at `time` together with the lowest quality score
+    /// along the path.
+    ///
+    /// A composed transform is only as trustworthy as its weakest edge, so
+    /// consumers can reject it when the returned score is too low. Each edge
+    /// contributes the score of its sample at `time`, or the lower score of
+    /// the two samples interpolated between (see
+    /// [`TransformUpdate::with_quality`]); samples without a score do not
+    /// constrain the result. The score is `None` if no edge on the path has
+    /// one at `time`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`lookup_transform`](BufferTree::lookup_transform).
+    pub fn lookup_with_quality(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+    ) -> Result<(StampedIsometry, Option<f32>), TfError> {
+        let path = self.lookup_path(from, to)?;
+        let mut quality: Option<f32> = None;
+        let isometry = self.compute_transform_along_path(&path, |history| {
+            if let Some(edge_quality) = history.quality_at_time(time) {
+                quality = Some(quality.map_or(edge_quality, |q| q.min(edge_quality)));
+            }
+            history.interpolate_isometry_at_time(time)
+        })?;
+        Ok((
+            StampedIsometry {
+                isometry,
+                stamp: time,
+            },
+            quality,
+        ))
+    }
 
-        assert!(lookup_result.is_ok());
-        let transform_result = lookup_result.unwrap();
-        assert_eq!(transform_result.stamp(), 1_000_000_000); // 1 second in nanoseconds
+    /// Pose of `data_frame` as it was at `data_time`, expressed in
+    /// `target_frame` as it is at `target_time`.
+    ///
+    /// This is the motion-compensation lookup: a camera image captured at
+    /// `data_time` has to be related to, say, the robot base at a later
+    /// `target_time` while the robot kept driving. `fixed_frame` is a frame
+    /// that does not move between the two times (usually `world` or `odom`);
+    /// the result goes from `data_frame` into it at `data_time`, then from
+    /// it into `target_frame` at `target_time`. The returned stamp is
+    /// `target_time`.
+    ///
+    /// With `data_time == target_time` this equals
+    /// [`lookup_transform(target_frame, data_frame, target_time)`](BufferTree::lookup_transform).
+    ///
+    /// # Errors
+    ///
+    /// Any error of [`lookup_transform`](BufferTree::lookup_transform) for
+    /// either of the two lookups.
+    pub fn retime_transform(
+        &self,
+        data_frame: &str,
+        data_time: i64,
+        target_frame: &str,
+        target_time: i64,
+        fixed_frame: &str,
+    ) -> Result<StampedIsometry, TfError> {
+        let data_in_fixed = self.lookup_transform(fixed_frame, data_frame, data_time)?;
+        let fixed_in_target = self.lookup_transform(target_frame, fixed_frame, target_time)?;
+        Ok(StampedIsometry {
+            isometry: fixed_in_target.isometry * data_in_fixed.isometry,
+            stamp: target_time,
+        })
     }
 
-    #[test]
-    fn test_buffer_tree_detects_cycles() {
-        let mut buffer_tree = BufferTree::new();
+    /// How far `to` moved relative to `from` between `t1` and `t2`.
+    ///
+    /// Returns `T(t1).inverse() * T(t2)`, where `T(t)` is
+    /// [`lookup_transform(from, to, t)`](BufferTree::lookup_transform): the
+    /// pose of `to` at `t2` expressed in `to` at `t1`. This is the
+    /// incremental motion used for dead reckoning, e.g. `odom -> base_link`
+    /// between two control cycles. The returned stamp is `t2`.
+    ///
+    /// # Errors
+    ///
+    /// Any error of [`lookup_transform`](BufferTree::lookup_transform) at
+    /// either time.
+    pub fn lookup_delta(
+        &self,
+        from: &str,
+        to: &str,
+        t1: i64,
+        t2: i64,
+    ) -> Result<StampedIsometry, TfError> {
+        let start = self.lookup_transform(from, to, t1)?;
+        let end = self.lookup_transform(from, to, t2)?;
+        Ok(StampedIsometry {
+            isometry: start.isometry.inverse() * end.isometry,
+            stamp: t2,
+        })
+    }
 
-        let a = "A";
-        let b = "B";
-        let c = "C";
+    /// Look up a transform `offset_secs` before the newest time at which
+    /// every edge on the path has data.
+    ///
+    /// The reference time is the oldest of the newest samples across the
+    /// dynamic edges between `from` and `to` (the latest time the whole
+    /// chain can be interpolated at). Static edges do not constrain it; a
+    /// path made only of static edges uses its newest stamp. The lookup then
+    /// happens at `reference - offset_secs` with the same semantics as
+    /// [`lookup_transform`](BufferTree::lookup_transform).
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::UnknownFrame`] / [`TfError::NoPath`] — as for
+    ///   [`lookup_transform`](BufferTree::lookup_transform).
+    /// - [`TfError::CouldNotFindTransform`] — a dynamic edge has too few
+    ///   samples.
+    /// - [`TfError::AttemptedLookupInPast`] — the offset reaches further back
+    ///   than the buffered history on some edge.
+    /// - [`TfError::AttemptedLookUpInFuture`] — `offset_secs` is negative.
+    pub fn lookup_transform_relative(
+        &self,
+        from: &str,
+        to: &str,
+        offset_secs: f64,
+    ) -> Result<StampedIsometry, TfError> {
+        let mut latest_dynamic: Option<i64> = None;
+        let mut latest_static: i64 = 0;
+        for history in self.path_edges(from, to)? {
+            let Some(newest) = history.history.back() else {
+                continue;
+            };
+            match history.kind {
+                TransformType::Dynamic => {
+                    latest_dynamic =
+                        Some(latest_dynamic.map_or(newest.stamp, |t| t.min(newest.stamp)));
+                }
+                TransformType::Static => latest_static = latest_static.max(newest.stamp),
+            }
+        }
 
-        let stamped_isometry =
-            StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        let reference = latest_dynamic.unwrap_or(latest_static);
+        let time = reference.saturating_sub(secs_to_nanos(offset_secs));
+        self.lookup_transform(from, to, time)
+    }
 
-        // Add edges A → B and B → C
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                a,
-                b,
-                stamped_isometry.clone(),
-                TransformType::Static,
-            )])
-            .unwrap();
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                b,
-                c,
-                stamped_isometry.clone(),
-                TransformType::Static,
-            )])
-            .unwrap();
+    /// Estimate the linear and angular acceleration of `to` relative to
+    /// `from` at `time`, as `(linear, angular)`, both expressed in `from`.
+    ///
+    /// Samples the transform at `time - dt_secs`, `time`, and
+    /// `time + dt_secs` and takes second-order finite differences: the
+    /// linear part from the translations, the angular part from the
+    /// difference of the two angular velocities between the samples. Units
+    /// are m/s² and rad/s².
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::CouldNotFindTransform`] if `dt_secs` is not finite or
+    ///   rounds to less than one nanosecond, or a sample time overflows.
+    /// - Any error of [`lookup_transform`](BufferTree::lookup_transform) for
+    ///   one of the three sample times, e.g.
+    ///   [`TfError::AttemptedLookUpInFuture`] if `time + dt_secs` is past the
+    ///   newest sample.
+    pub fn lookup_acceleration(
+        &self,
+        from: &str,
+        to: &str,
+        time: i64,
+        dt_secs: f64,
+    ) -> Result<([f64; 3], [f64; 3]), TfError> {
+        let dt_ns = if dt_secs.is_finite() {
+            secs_to_nanos(dt_secs)
+        } else {
+            0
+        };
+        if dt_ns <= 0 {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "Acceleration interval must be at least one nanosecond, got {} s",
+                dt_secs
+            )));
+        }
+        let (Some(before_time), Some(after_time)) =
+            (time.checked_sub(dt_ns), time.checked_add(dt_ns))
+        else {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "Acceleration interval of {} s around {} overflows the timestamp range",
+                dt_secs, time
+            )));
+        };
+        // The interval actually sampled, after rounding to nanoseconds.
+        let dt_secs = dt_ns as f64 / 1e9;
+        let before = self.lookup_transform(from, to, before_time)?.isometry;
+        let at = self.lookup_transform(from, to, time)?.isometry;
+        let after = self.lookup_transform(from, to, after_time)?.isometry;
+
+        let linear = (after.translation.vector - 2.0 * at.translation.vector
+            + before.translation.vector)
+            / (dt_secs * dt_secs);
+        let omega_before = (at.rotation * before.rotation.inverse()).scaled_axis() / dt_secs;
+        let omega_after = (after.rotation * at.rotation.inverse()).scaled_axis() / dt_secs;
+        let angular = (omega_after - omega_before) / dt_secs;
+
+        Ok((linear.into(), angular.into()))
+    }
 
-        // Creating a cycle C → A should fail
-        let result = buffer_tree.update(&[TransformUpdate::new(
-            c,
-            a,
-            stamped_isometry.clone(),
-            TransformType::Static,
-        )]);
-        assert!(result.is_err());
-        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+    /// Sample `from -> to` at a fixed rate of `rate_hz` from `start` to
+    /// `end` (inclusive, nanoseconds since the Unix epoch).
+    ///
+    /// The first sample is at `start` and samples follow every
+    /// `1 / rate_hz` seconds up to and including `end`, so a range that is a
+    /// whole number of periods long yields `(end - start) * rate_hz + 1`
+    /// samples. Each is interpolated like
+    /// [`lookup_transform`](BufferTree::lookup_transform). Useful to feed a
+    /// consumer at a fixed cadence independent of the publishing rate; use
+    /// [`valid_time_range`](BufferTree::valid_time_range) to pick a range
+    /// the buffer covers. An `end` before `start` yields no samples.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::CouldNotFindTransform`] if `rate_hz` is not finite and
+    ///   positive, its period is below one nanosecond, or the range would
+    ///   yield more than a million samples.
+    /// - Any error of [`lookup_transform`](BufferTree::lookup_transform) for
+    ///   one of the sample times.
+    pub fn resample_edge(
+        &self,
+        from: &str,
+        to: &str,
+        rate_hz: f64,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<StampedIsometry>, TfError> {
+        if !(rate_hz.is_finite() && rate_hz > 0.0) {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "Resample rate must be positive and finite, got {} Hz",
+                rate_hz
+            )));
+        }
+        let period_ns = 1_000_000_000.0 / rate_hz;
+        if period_ns < 1.0 {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "Resample rate {} Hz is finer than the nanosecond resolution of stamps",
+                rate_hz
+            )));
+        }
+        let path = self.lookup_path(from, to)?;
+        if end < start {
+            return Ok(Vec::new());
+        }
+
+        let count = ((end as i128 - start as i128) as f64 / period_ns).floor() as u64 + 1;
+        if count > MAX_RESAMPLE_SAMPLES {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "Resampling at {} Hz would yield {} samples, more than the maximum of {}",
+                rate_hz, count, MAX_RESAMPLE_SAMPLES
+            )));
+        }
+
+        let mut samples = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            // Offsets from `start` rather than accumulated periods, so
+            // rounding errors do not add up over long ranges.
+            let stamp = start + (i as f64 * period_ns).round() as i64;
+            if stamp > end {
+                break;
+            }
+            let isometry = self.compute_transform_along_path(&path, |history| {
+                history.interpolate_isometry_at_time(stamp)
+            })?;
+            samples.push(StampedIsometry { isometry, stamp });
+        }
+        Ok(samples)
     }
 
-    #[test]
-    fn test_multiple_incoming_edges() {
-        let mut buffer_tree = BufferTree::new();
+    /// The inclusive time range over which
+    /// [`lookup_transform`](BufferTree::lookup_transform) from `from` to `to`
+    /// succeeds, as `(earliest, latest)` in nanoseconds since the Unix epoch.
+    ///
+    /// This is the intersection of the sample ranges of every dynamic edge on
+    /// the path: the newest of their oldest stamps up to the oldest of their
+    /// newest stamps. Static edges hold at any time, so a path made only of
+    /// static edges yields `(i64::MIN, i64::MAX)`.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::UnknownFrame`] / [`TfError::NoPath`] — as for
+    ///   [`lookup_transform`](BufferTree::lookup_transform).
+    /// - [`TfError::CouldNotFindTransform`] — a dynamic edge has no samples,
+    ///   or the edges' ranges do not overlap.
+    pub fn valid_time_range(&self, from: &str, to: &str) -> Result<(i64, i64), TfError> {
+        let mut earliest = i64::MIN;
+        let mut latest = i64::MAX;
+        for history in self.path_edges(from, to)? {
+            if !matches!(history.kind, TransformType::Dynamic) {
+                continue;
+            }
+            let (Some(oldest), Some(newest)) = (history.history.front(), history.history.back())
+            else {
+                return Err(TfError::CouldNotFindTransform(format!(
+                    "No samples to interpolate between '{}' and '{}'",
+                    from, to
+                )));
+            };
+            earliest = earliest.max(oldest.stamp);
+            latest = latest.min(newest.stamp);
+        }
 
-        let a = "A";
-        let b = "B";
-        let c = "C";
+        if earliest > latest {
+            return Err(TfError::CouldNotFindTransform(format!(
+                "No common time between '{}' and '{}': edges cover {} to {} only",
+                from, to, earliest, latest
+            )));
+        }
+        Ok((earliest, latest))
+    }
 
-        let stamped_isometry =
-            StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+    /// Edge histories along the path from `from` to `to`, in path order.
+    fn path_edges(&self, from: &str, to: &str) -> Result<Vec<&TransformHistory>, TfError> {
+        let path = self.find_path(from, to)?;
+
+        path.windows(2)
+            .map(|pair| {
+                self.graph
+                    .edge_weight(pair[0], pair[1])
+                    .or_else(|| self.graph.edge_weight(pair[1], pair[0]))
+                    .ok_or_else(|| {
+                        TfError::CouldNotFindTransform(format!(
+                            "Edge transform not found for edge {} -> {}",
+                            pair[0], pair[1]
+                        ))
+                    })
+            })
+            .collect()
+    }
 
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                a,
-                b,
-                stamped_isometry.clone(),
-                TransformType::Static,
-            )])
-            .unwrap();
-        let result = buffer_tree.update(&[TransformUpdate::new(
-            c,
-            b,
-            stamped_isometry.clone(),
-            TransformType::Static,
-        )]);
-        assert!(result.is_err());
-        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+    /// Look up every `(from, to)` pair in `pairs` at the same `time`, in order.
+    ///
+    /// The borrow of `self` spans the whole batch, so no update can land
+    /// between two of the lookups. For a buffer shared between tasks, use
+    /// `SharedBufferTree::lookup_snapshot` (feature `tokio`), which holds a
+    /// single read lock for the batch.
+    ///
+    /// # Errors
+    ///
+    /// The first error [`lookup_transform`](BufferTree::lookup_transform)
+    /// returns for any pair.
+    pub fn lookup_snapshot(
+        &self,
+        pairs: &[(&str, &str)],
+        time: i64,
+    ) -> Result<Vec<StampedIsometry>, TfError> {
+        pairs
+            .iter()
+            .map(|(from, to)| self.lookup_transform(from, to, time))
+            .collect()
     }
 
-    #[test]
-    fn test_find_path() {
-        let mut buffer_tree = BufferTree::new();
+    /// Pose of every frame connected to `root`, relative to `root`, at `time`.
+    ///
+    /// Equivalent to calling [`lookup_transform`](BufferTree::lookup_transform)
+    /// from `root` to each frame, but the tree is walked once outward from
+    /// `root` and each edge is evaluated a single time. The map includes
+    /// `root` itself (identity); frames not connected to `root` are omitted.
+    /// Every returned pose is stamped with `time`.
+    ///
+    /// `time` is in nanoseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// - [`TfError::UnknownFrame`] if `root` is unknown.
+    /// - Any error [`lookup_transform`](BufferTree::lookup_transform) would
+    ///   return for an edge in `root`'s tree that cannot be evaluated at `time`.
+    pub fn snapshot_from(
+        &self,
+        root: &str,
+        time: i64,
+    ) -> Result<HashMap<String, StampedIsometry>, TfError> {
+        // The index also names frames that only carry metadata or whose
+        // edge was rejected, so check graph membership like `has_frame`.
+        let root_idx = self
+            .index
+            .get(root)
+            .filter(|&idx| self.graph.contains_node(idx))
+            .ok_or_else(|| {
+                TfError::UnknownFrame(format!("Root frame '{}' does not exist", root))
+            })?;
+
+        let mut poses: FxHashMap<usize, Isometry3<f64>> = FxHashMap::default();
+        poses.insert(root_idx, Isometry3::identity());
+        let mut stack = vec![root_idx];
+
+        while let Some(node) = stack.pop() {
+            let pose = poses[&node];
+
+            for child in self.graph.neighbors_directed(node, Direction::Outgoing) {
+                if poses.contains_key(&child) {
+                    continue;
+                }
+                if let Some(history) = self.graph.edge_weight(node, child) {
+                    poses.insert(child, pose * history.interpolate_isometry_at_time(time)?);
+                    stack.push(child);
+                }
+            }
 
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                "A",
-                "B",
-                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
-                TransformType::Dynamic,
-            )])
-            .unwrap();
+            for parent in self.graph.neighbors_directed(node, Direction::Incoming) {
+                if poses.contains_key(&parent) {
+                    continue;
+                }
+                if let Some(history) = self.graph.edge_weight(parent, node) {
+                    poses.insert(
+                        parent,
+                        pose * history.interpolate_isometry_at_time(time)?.inverse(),
+                    );
+                    stack.push(parent);
+                }
+            }
+        }
 
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                "A",
-                "C",
-                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.0),
-                TransformType::Dynamic,
-            )])
-            .unwrap();
+        Ok(poses
+            .into_iter()
+            .filter_map(|(idx, isometry)| {
+                let name = self.index.get_node(idx)?.name.clone();
+                Some((
+                    name,
+                    StampedIsometry {
+                        isometry,
+                        stamp: time,
+                    },
+                ))
+            })
+            .collect())
+    }
 
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                "B",
-                "D",
-                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 3.0),
-                TransformType::Dynamic,
-            )])
-            .unwrap();
+    /// Axis-aligned bounding box of the origins of every frame connected to
+    /// `root`, in `root`'s coordinates at `time`, as `(min, max)` corners.
+    ///
+    /// Built on [`snapshot_from`](BufferTree::snapshot_from), so `root`'s own
+    /// origin is included and the same errors apply. Meant for framing a
+    /// camera around the whole tree.
+    pub fn origins_aabb(&self, root: &str, time: i64) -> Result<([f64; 3], [f64; 3]), TfError> {
+        // The snapshot always contains `root` itself at the origin.
+        let mut min = [0.0; 3];
+        let mut max = [0.0; 3];
+        for pose in self.snapshot_from(root, time)?.values() {
+            for (axis, &value) in pose.translation_ref().iter().enumerate() {
+                min[axis] = f64::min(min[axis], value);
+                max[axis] = f64::max(max[axis], value);
+            }
+        }
+        Ok((min, max))
+    }
 
-        buffer_tree
-            .update(&[TransformUpdate::new(
-                "B",
-                "E",
-                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 3.0),
-                TransformType::Dynamic,
-            )])
-            .unwrap();
+    /// Dump every edge's transform at `time` (nanoseconds) as a YAML list of
+    /// tf2 `TransformStamped`-shaped entries.
+    ///
+    /// The layout matches the capture script documented on
+    /// `test_complex_interpolation` (and the fixtures in `tests/fixtures`):
+    /// `header.frame_id` is the parent, `child_frame_id` the child, and
+    /// `header.stamp` is `time` in seconds. Entries are sorted by parent and
+    /// child name. Edges that cannot be evaluated at `time`, e.g. because it
+    /// lies outside their history, are left out.
+    pub fn to_tf_yaml(&self, time: i64) -> String {
+        let mut entries: Vec<(&str, &str, StampedIsometry)> = self
+            .graph
+            .all_edges()
+            .filter_map(|(from, to, history)| {
+                let isometry = history.interpolate_isometry_at_time(time).ok()?;
+                Some((
+                    self.index.get_node(from)?.name.as_str(),
+                    self.index.get_node(to)?.name.as_str(),
+                    StampedIsometry {
+                        isometry,
+                        stamp: time,
+                    },
+                ))
+            })
+            .collect();
+        if entries.is_empty() {
+            return "[]\n".to_string();
+        }
+        entries.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        let mut yaml = String::new();
+        for (from, to, transform) in entries {
+            let t = transform.translation().map(yaml_float);
+            let q = transform.rotation().map(yaml_float);
+            yaml.push_str(&format!(
+                "- header:\n    frame_id: {}\n    stamp: {}\n  child_frame_id: {}\n  transform:\n    translation:\n      x: {}\n      y: {}\n      z: {}\n    rotation:\n      x: {}\n      y: {}\n      z: {}\n      w: {}\n",
+                yaml_string(from),
+                yaml_float(transform.stamp_secs()),
+                yaml_string(to),
+                t[0], t[1], t[2],
+                q[0], q[1], q[2], q[3],
+            ));
+        }
+        yaml
+    }
 
-        println!("{:?}", buffer_tree.visualize());
+    /// Render the current graph as a Graphviz DOT-format string.
+    ///
+    /// Each node is labeled with its frame name, with any
+    /// [frame metadata](BufferTree::set_frame_metadata) as `key=value` lines
+    /// in its tooltip; each edge is labeled with
+    /// the most recent translation (`t=[x, y, z]`), Euler rotation
+    /// (`r=[r, p, y]`), and timestamp in seconds. Edges with no samples are
+    /// labeled `"No transforms"`.
+    pub fn visualize(&self) -> String {
+        self.visualize_with_lookup_failures(&HashMap::new())
+    }
 
-        // Test path finding - these tests verify the internal find_path method works
-        // Note: find_path is private, so we test it indirectly through lookup operations
-        let result = buffer_tree.lookup_latest_transform("D", "B");
-        assert!(result.is_ok());
+    /// Like [`visualize`](BufferTree::visualize), but highlight lookups that
+    /// have been failing.
+    ///
+    /// `failures` maps `(from, to)` lookup pairs to how often they failed.
+    /// Each pair with a non-zero count gets a dashed red edge labeled
+    /// `"<n> failed lookups"`, and the graph edges along its path (if one
+    /// exists) are drawn red. Frames unknown to the buffer are drawn as
+    /// dashed nodes.
+    pub fn visualize_with_lookup_failures(
+        &self,
+        failures: &HashMap<(String, String), u64>,
+    ) -> String {
+        let mut failing: Vec<(&str, &str, u64)> = failures
+            .iter()
+            .filter(|(_, &count)| count > 0)
+            .map(|((from, to), &count)| (from.as_str(), to.as_str(), count))
+            .collect();
+        failing.sort();
+
+        let mut failing_edges: FxHashSet<(usize, usize)> = FxHashSet::default();
+        for (from, to, _) in &failing {
+            if let Ok(path) = self.find_path(from, to) {
+                for pair in path.windows(2) {
+                    failing_edges.insert((pair[0], pair[1]));
+                    failing_edges.insert((pair[1], pair[0]));
+                }
+            }
+        }
 
-        let result = buffer_tree.lookup_latest_transform("D", "C");
-        assert!(result.is_ok());
+        // Create a mapping from index back to node name
+        // Convert the graph to DOT format manually
+        let mut dot = String::from("digraph {\n");
 
-        let result = buffer_tree.lookup_latest_transform("D", "E");
-        assert!(result.is_ok());
+        // Add nodes
+        for node in self.graph.nodes() {
+            let name = dot_escape(&self.index.get_node(node).unwrap().name);
+            match self.frame_metadata.get(&node) {
+                Some(metadata) if !metadata.is_empty() => {
+                    let tooltip = metadata
+                        .iter()
+                        .map(|(key, value)| dot_escape(&format!("{}={}", key, value)))
+                        .collect::<Vec<_>>()
+                        .join("\\n");
+                    dot.push_str(&format!(
+                        "    {} [label=\"{}\", tooltip=\"{}\"]\n",
+                        node, name, tooltip
+                    ));
+                }
+                _ => dot.push_str(&format!("    {} [label=\"{}\"]\n", node, name)),
+            }
+        }
 
-        let result = buffer_tree.lookup_latest_transform("A", "E");
-        assert!(result.is_ok());
-    }
+        // Add edges with transform information
+        for edge in self.graph.all_edges() {
+            let color = if failing_edges.contains(&(edge.0, edge.1)) {
+                ", color=red"
+            } else {
+                ""
+            };
+            if let Some(latest) = edge.2.history.back() {
+                let translation = latest.isometry.translation.vector;
+                let rotation = latest.isometry.rotation.euler_angles();
+                dot.push_str(&format!(
+                    "    {} -> {} [label=\"t=[{:.3}, {:.3}, {:.3}]\\nr=[{:.3}, {:.3}, {:.3}]\\ntime={:.3}s\"{}]\n",
+                    edge.0, edge.1,
+                    translation[0], translation[1], translation[2],
+                    rotation.0, rotation.1, rotation.2,
+                    latest.stamp_secs(),
+                    color
+                ));
+            } else {
+                dot.push_str(&format!(
+                    "    {} -> {} [label=\"No transforms\"{}]\n",
+                    edge.0, edge.1, color
+                ));
+            }
+        }
 
-    #[test]
-    fn test_robot_arm_transforms() {
-        let mut buffer_tree = BufferTree::new();
+        // Add failing lookups
+        let mut unknown_frames: FxHashSet<String> = FxHashSet::default();
+        for (from, to, count) in &failing {
+            let mut node_id = |frame: &str| match self.index.get(frame) {
+                Some(idx) if self.graph.contains_node(idx) => idx.to_string(),
+                _ => {
+                    let frame = dot_escape(frame);
+                    if unknown_frames.insert(frame.clone()) {
+                        dot.push_str(&format!(
+                            "    \"{}\" [label=\"{}\", style=dashed]\n",
+                            frame, frame
+                        ));
+                    }
+                    format!("\"{}\"", frame)
+                }
+            };
+            let (from_id, to_id) = (node_id(from), node_id(to));
+            dot.push_str(&format!(
+                "    {} -> {} [label=\"{} failed lookups\", color=red, fontcolor=red, style=dashed, constraint=false]\n",
+                from_id, to_id, count
+            ));
+        }
 
-        // Define test data as a vector of (source, target, translation, rotation, timestamp) tuples
-        let transforms = vec![
-            (
-                "upper_arm_link",
-                "forearm_link",
-                [-0.425, 0.0, 0.0],
-                [0.0, 0.0, 0.0, 1.0],
-            ),
-            (
-                "shoulder_link",
-                "upper_arm_link",
-                [0.0, 0.0, 0.0],
-                [
-                    0.5001990421112379,
-                    0.49980087872426926,
-                    -0.4998008786217583,
-                    0.5001990420086454,
-                ],
-            ),
-            (
-                "base_link_inertia",
-                "shoulder_link",
-                [0.0, 0.0, 0.1625],
-                [0.0, 0.0, 0.0, 1.0],
-            ),
-            (
-                "forearm_link",
-                "wrist_1_link",
-                [-0.3922, 0.0, 0.1333],
-                [0.0, 0.0, -0.7068251811053659, 0.7073882691671998],
-            ),
-            (
-                "wrist_1_link",
-                "wrist_2_link",
-                [0.0, -0.0997, -2.044881182297852e-11],
-                [0.7071067812590626, 0.0, 0.0, 0.7071067811140325],
-            ),
-            (
-                "wrist_2_link",
-                "wrist_3_link",
-                [0.0, 0.0996, -2.042830148012698e-11],
-                [
-                    -0.7071067812590626,
-                    8.659560562354933e-17,
-                    8.880526795522719e-27,
-                    0.7071067811140325,
-                ],
-            ),
-        ];
+        dot.push_str("}");
+        dot
+    }
 
-        // Convert seconds and nanoseconds to nanoseconds
-        let timestamp_ns = 1741097108_000_000_000i64 + 171207063;
+    /// Write the current graph to disk as both `graph.dot` and `graph.pdf`
+    /// under [`BufferConfig::save_path`].
+    ///
+    /// The DOT file always succeeds; the PDF is generated by invoking the
+    /// external `dot` binary from Graphviz. If Graphviz is not installed the
+    /// PDF step prints a warning to stderr but the function still returns
+    /// `Ok(())` — only filesystem I/O errors on the DOT file propagate.
+    ///
+    /// Saves are rate-limited by [`BufferConfig::min_visualization_interval`]:
+    /// a call arriving within that interval of the previous save is coalesced
+    /// into it and returns `Ok(())` without touching the filesystem.
+    pub fn save_visualization(&self) -> std::io::Result<()> {
+        {
+            let mut last_save = self
+                .last_visualization_save
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = self.clock.now_secs();
+            if let Some(last) = *last_save {
+                // A clock that went backward counts as expired, so a jump
+                // cannot suppress saves until it catches up again.
+                let elapsed = now - last;
+                if (0.0..self.config.min_visualization_interval).contains(&elapsed) {
+                    log::debug!(
+                        "Skipping visualization save, last save was {:.3}s ago",
+                        elapsed
+                    );
+                    return Ok(());
+                }
+            }
+            *last_save = Some(now);
+        }
 
-        // Add all transforms to the buffer
-        for (source, target, translation, rotation) in transforms {
-            let stamped_isometry = StampedIsometry::new(translation, rotation, timestamp_ns);
+        let filename = &self.config.save_path;
+        println!("Saving visualization to {}/graph.(dot/pdf)", filename);
+        // Save DOT file
+        let dot_content = self.visualize();
+        let dot_filename = format!("{}/graph.dot", filename);
+        let mut file = File::create(&dot_filename)?;
+        file.write_all(dot_content.as_bytes())?;
 
-            buffer_tree
-                .update(&[TransformUpdate::new(
-                    source,
-                    target,
-                    stamped_isometry,
-                    TransformType::Dynamic,
-                )])
-                .unwrap();
+        // Generate PDF using dot command
+        let pdf_filename = format!("{}/graph.pdf", filename);
+        let output = Command::new("dot")
+            .args(["-Tpdf", &dot_filename, "-o", &pdf_filename])
+            .output()?;
+
+        if !output.status.success() {
+            eprintln!(
+                "Warning: Failed to generate PDF. Is Graphviz installed? Error: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
         }
 
-        let transform = buffer_tree.lookup_latest_transform("base_link_inertia", "shoulder_link");
-        assert!(transform.is_ok());
-        let transform = transform.unwrap();
-        let translation = transform.translation();
-        let rotation = transform.rotation();
+        Ok(())
+    }
 
-        // Check translation components
-        assert_relative_eq!(translation[0], 0.0, epsilon = 1e-3);
-        assert_relative_eq!(translation[1], 0.0, epsilon = 1e-3);
-        assert_relative_eq!(translation[2], 0.1625, epsilon = 1e-3);
+    /// Helper function to compute transforms along a path
+    /// This function handles the common logic of iterating through a path and computing
+    /// the cumulative transform. The transform_getter function determines how to get
+    /// the transform for each edge (latest vs interpolated at time).
+    ///
+    /// Errors from `transform_getter` name the failing edge and the time
+    /// range it holds, so a failed multi-hop lookup points at its cause.
+    fn compute_transform_along_path<F>(
+        &self,
+        path: &[usize],
+        mut transform_getter: F,
+    ) -> Result<Isometry3<f64>, TfError>
+    where
+        F: FnMut(&TransformHistory) -> Result<Isometry3<f64>, TfError>,
+    {
+        let mut isometry = Isometry3::identity();
 
-        // Check quaternion components (w, x, y, z)
-        assert_relative_eq!(rotation[3], 1.0, epsilon = 1e-3);
-        assert_relative_eq!(rotation[0], 0.0, epsilon = 1e-3);
-        assert_relative_eq!(rotation[1], 0.0, epsilon = 1e-3);
-        assert_relative_eq!(rotation[2], 0.001, epsilon = 1e-3);
+        // Use direct indexing instead of windows(2) for better performance
+        for i in 0..path.len().saturating_sub(1) {
+            let from_idx = path[i];
+            let to_idx = path[i + 1];
 
-        // Test that we can find paths between arbitrary frames
-        let transform = buffer_tree.lookup_latest_transform("base_link_inertia", "wrist_3_link");
-        assert!(transform.is_ok());
+            // Try forward edge first, then reverse - avoids redundant contains_edge check
+            if let Some(edge_weight) = self.graph.edge_weight(from_idx, to_idx) {
+                isometry *= transform_getter(edge_weight)
+                    .map_err(|e| self.edge_error(e, from_idx, to_idx, edge_weight))?;
+            } else if let Some(edge_weight) = self.graph.edge_weight(to_idx, from_idx) {
+                isometry *= transform_getter(edge_weight)
+                    .map_err(|e| self.edge_error(e, to_idx, from_idx, edge_weight))?
+                    .inverse();
+            } else {
+                return Err(TfError::CouldNotFindTransform(format!(
+                    "Edge transform not found for edge {} -> {}",
+                    from_idx, to_idx
+                )));
+            }
+        }
 
-        // Add these assertions
-        let transform = transform.unwrap();
-        let translation = transform.translation();
-        let rotation = transform.euler_angles();
+        Ok(isometry)
+    }
 
-        // Check translation components
-        assert_relative_eq!(translation[0], -0.001, epsilon = 1e-3);
-        assert_relative_eq!(translation[1], -0.233, epsilon = 1e-3);
-        assert_relative_eq!(translation[2], 1.079, epsilon = 1e-3);
+    /// `error` prefixed with the stored edge `parent -> child` and the range
+    /// of its samples.
+    fn edge_error(
+        &self,
+        error: TfError,
+        parent: usize,
+        child: usize,
+        history: &TransformHistory,
+    ) -> TfError {
+        let name = |idx: usize| {
+            self.index
+                .get_node(idx)
+                .map_or("?", |node| node.name.as_str())
+        };
+        let range = match (
+            history.kind,
+            history.history.front(),
+            history.history.back(),
+        ) {
+            (TransformType::Dynamic, Some(oldest), Some(newest)) => {
+                format!("samples from {} to {}", oldest.stamp, newest.stamp)
+            }
+            (TransformType::Dynamic, _, _) => "no samples".to_string(),
+            (TransformType::Static, _, _) => "static".to_string(),
+        };
+        error.with_context(format_args!(
+            "Edge '{}' -> '{}' ({})",
+            name(parent),
+            name(child),
+            range
+        ))
+    }
+}
 
-        // Check quaternion components (w, x, y, z)
-        assert_relative_eq!(rotation[0], -1.571, epsilon = 1e-2);
-        assert_relative_eq!(rotation[1], -0.002, epsilon = 1e-2);
-        assert_relative_eq!(rotation[2], 3.142, epsilon = 1e-2);
+/// One line per edge, `from -> to [Kind, N samples, latest t=...s]`, sorted
+/// by frame names. Meant for quick inspection; use
+/// [`visualize`](BufferTree::visualize) for a graph rendering.
+impl fmt::Display for BufferTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut edges: Vec<EdgeView<'_>> = self.iter_edges().collect();
+        edges.sort_by(|a, b| (a.from, a.to).cmp(&(b.from, b.to)));
+
+        for edge in edges {
+            write!(
+                f,
+                "{} -> {} [{:?}, {} samples",
+                edge.from,
+                edge.to,
+                edge.kind,
+                edge.samples.len()
+            )?;
+            match edge.latest() {
+                Some(latest) => writeln!(f, ", latest t={:.6}s]", latest.stamp_secs())?,
+                None => writeln!(f, "]")?,
+            }
+        }
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BufferTree, ManualClock, StampedIsometry, TransformType, TransformUpdate};
+    use approx::assert_relative_eq;
+    use nalgebra::geometry::Isometry3;
+    use std::sync::Mutex;
 
     #[test]
-    fn test_robot_arm_transform_inverse() {
+    fn test_buffer_tree_update() {
         let mut buffer_tree = BufferTree::new();
 
-        // Define test data as a vector of (source, target, translation, rotation) tuples
+        let source = "A";
+        let target = "B";
+
+        let stamped_isometry =
+            StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+
+        // Add first transformation
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                source,
+                target,
+                stamped_isometry,
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        // Test lookup
+        let result = buffer_tree.lookup_latest_transform(&source, &target);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_separation_works() {
+        // This test verifies that the core logic works without PyO3
+        let mut buffer_tree = BufferTree::new();
+
+        let transform = StampedIsometry::from_secs([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+
+        let result = buffer_tree.update(&[TransformUpdate::new(
+            "base_link",
+            "target_link",
+            transform,
+            TransformType::Static,
+        )]);
+
+        assert!(result.is_ok());
+
+        let lookup_result = buffer_tree.lookup_latest_transform("base_link", "target_link");
+
+        assert!(lookup_result.is_ok());
+        let transform_result = lookup_result.unwrap();
+        assert_eq!(transform_result.stamp(), 1_000_000_000); // 1 second in nanoseconds
+    }
+
+    #[test]
+    fn test_buffer_tree_detects_cycles() {
+        let mut buffer_tree = BufferTree::new();
+
+        let a = "A";
+        let b = "B";
+        let c = "C";
+
+        let stamped_isometry =
+            StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+
+        // Add edges A → B and B → C
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                a,
+                b,
+                stamped_isometry.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                b,
+                c,
+                stamped_isometry.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        // Creating a cycle C → A should fail
+        let result = buffer_tree.update(&[TransformUpdate::new(
+            c,
+            a,
+            stamped_isometry.clone(),
+            TransformType::Static,
+        )]);
+        assert!(result.is_err());
+        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+    }
+
+    #[test]
+    fn test_multiple_incoming_edges() {
+        let mut buffer_tree = BufferTree::new();
+
+        let a = "A";
+        let b = "B";
+        let c = "C";
+
+        let stamped_isometry =
+            StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                a,
+                b,
+                stamped_isometry.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        let result = buffer_tree.update(&[TransformUpdate::new(
+            c,
+            b,
+            stamped_isometry.clone(),
+            TransformType::Static,
+        )]);
+        assert!(result.is_err());
+        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+    }
+
+    #[test]
+    fn test_find_path() {
+        let mut buffer_tree = BufferTree::new();
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                TransformType::Dynamic,
+            )])
+            .unwrap();
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "C",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.0),
+                TransformType::Dynamic,
+            )])
+            .unwrap();
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "B",
+                "D",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 3.0),
+                TransformType::Dynamic,
+            )])
+            .unwrap();
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "B",
+                "E",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 3.0),
+                TransformType::Dynamic,
+            )])
+            .unwrap();
+
+        println!("{:?}", buffer_tree.visualize());
+
+        // Test path finding - these tests verify the internal find_path method works
+        // Note: find_path is private, so we test it indirectly through lookup operations
+        let result = buffer_tree.lookup_latest_transform("D", "B");
+        assert!(result.is_ok());
+
+        let result = buffer_tree.lookup_latest_transform("D", "C");
+        assert!(result.is_ok());
+
+        let result = buffer_tree.lookup_latest_transform("D", "E");
+        assert!(result.is_ok());
+
+        let result = buffer_tree.lookup_latest_transform("A", "E");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_robot_arm_transforms() {
+        let mut buffer_tree = BufferTree::new();
+
+        // Define test data as a vector of (source, target, translation, rotation, timestamp) tuples
         let transforms = vec![
             (
                 "upper_arm_link",
@@ -1061,7 +2890,7 @@ mod tests {
             ),
         ];
 
-        // Convert to nanoseconds (like the previous test)
+        // Convert seconds and nanoseconds to nanoseconds
         let timestamp_ns = 1741097108_000_000_000i64 + 171207063;
 
         // Add all transforms to the buffer
@@ -1078,29 +2907,48 @@ mod tests {
                 .unwrap();
         }
 
-        println!("{}", buffer_tree.visualize());
-        let transform = buffer_tree.lookup_latest_transform("wrist_3_link", "base_link_inertia");
+        let transform = buffer_tree.lookup_latest_transform("base_link_inertia", "shoulder_link");
+        assert!(transform.is_ok());
+        let transform = transform.unwrap();
+        let translation = transform.translation();
+        let rotation = transform.rotation();
+
+        // Check translation components
+        assert_relative_eq!(translation[0], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(translation[1], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(translation[2], 0.1625, epsilon = 1e-3);
+
+        // Check quaternion components (w, x, y, z)
+        assert_relative_eq!(rotation[3], 1.0, epsilon = 1e-3);
+        assert_relative_eq!(rotation[0], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(rotation[1], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(rotation[2], 0.001, epsilon = 1e-3);
+
+        // Test that we can find paths between arbitrary frames
+        let transform = buffer_tree.lookup_latest_transform("base_link_inertia", "wrist_3_link");
         assert!(transform.is_ok());
+
+        // Add these assertions
         let transform = transform.unwrap();
         let translation = transform.translation();
         let rotation = transform.euler_angles();
 
-        // Check translation components (should be inverse of original transform)
-        assert_relative_eq!(translation[0], 0.001, epsilon = 1e-3);
-        assert_relative_eq!(translation[1], 1.079, epsilon = 1e-3);
-        assert_relative_eq!(translation[2], -0.233, epsilon = 1e-3);
+        // Check translation components
+        assert_relative_eq!(translation[0], -0.001, epsilon = 1e-3);
+        assert_relative_eq!(translation[1], -0.233, epsilon = 1e-3);
+        assert_relative_eq!(translation[2], 1.079, epsilon = 1e-3);
 
-        // Check euler angles (should be inverse of original transform)
+        // Check quaternion components (w, x, y, z)
         assert_relative_eq!(rotation[0], -1.571, epsilon = 1e-2);
-        assert_relative_eq!(rotation[1], 0.00, epsilon = 1e-2);
-        assert_relative_eq!(rotation[2], 3.14, epsilon = 1e-2);
+        assert_relative_eq!(rotation[1], -0.002, epsilon = 1e-2);
+        assert_relative_eq!(rotation[2], 3.142, epsilon = 1e-2);
     }
 
     #[test]
-    fn test_robot_arm_transforms_interpolation() {
+    fn test_robot_arm_transform_inverse() {
         let mut buffer_tree = BufferTree::new();
 
-        // Define test data as a vector of (source, target, translation, rotation, timestamp) tuples
+        // Define test data as a vector of (source, target, translation, rotation) tuples
         let transforms = vec![
             (
                 "upper_arm_link",
@@ -1150,49 +2998,138 @@ mod tests {
             ),
         ];
 
-        // Timestamps in nanoseconds
-        let timestamp_1: i64 = 1_000_000_000; // 1 second
-        let timestamp_2: i64 = 2_000_000_000; // 2 seconds
+        // Convert to nanoseconds (like the previous test)
+        let timestamp_ns = 1741097108_000_000_000i64 + 171207063;
 
         // Add all transforms to the buffer
         for (source, target, translation, rotation) in transforms {
-            let stamped_isometry_1 = StampedIsometry::new(translation, rotation, timestamp_1);
-            let stamped_isometry_2 = StampedIsometry::new(translation, rotation, timestamp_2);
+            let stamped_isometry = StampedIsometry::new(translation, rotation, timestamp_ns);
 
             buffer_tree
                 .update(&[TransformUpdate::new(
                     source,
                     target,
-                    stamped_isometry_1,
-                    TransformType::Dynamic,
-                )])
-                .unwrap();
-            buffer_tree
-                .update(&[TransformUpdate::new(
-                    source,
-                    target,
-                    stamped_isometry_2,
+                    stamped_isometry,
                     TransformType::Dynamic,
                 )])
                 .unwrap();
         }
 
-        // Lookup at 1.5 seconds = 1_500_000_000 ns
-        let transform =
-            buffer_tree.lookup_transform("base_link_inertia", "shoulder_link", 1_500_000_000);
+        println!("{}", buffer_tree.visualize());
+        let transform = buffer_tree.lookup_latest_transform("wrist_3_link", "base_link_inertia");
         assert!(transform.is_ok());
         let transform = transform.unwrap();
         let translation = transform.translation();
-        let rotation = transform.rotation();
+        let rotation = transform.euler_angles();
 
-        // Check translation components
-        assert_relative_eq!(translation[0], 0.0, epsilon = 1e-3);
-        assert_relative_eq!(translation[1], 0.0, epsilon = 1e-3);
-        assert_relative_eq!(translation[2], 0.1625, epsilon = 1e-3);
+        // Check translation components (should be inverse of original transform)
+        assert_relative_eq!(translation[0], 0.001, epsilon = 1e-3);
+        assert_relative_eq!(translation[1], 1.079, epsilon = 1e-3);
+        assert_relative_eq!(translation[2], -0.233, epsilon = 1e-3);
 
-        // Check quaternion components (w, x, y, z)
-        assert_relative_eq!(rotation[3], 1.0, epsilon = 1e-3);
-        assert_relative_eq!(rotation[0], 0.0, epsilon = 1e-3);
+        // Check euler angles (should be inverse of original transform)
+        assert_relative_eq!(rotation[0], -1.571, epsilon = 1e-2);
+        assert_relative_eq!(rotation[1], 0.00, epsilon = 1e-2);
+        assert_relative_eq!(rotation[2], 3.14, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_robot_arm_transforms_interpolation() {
+        let mut buffer_tree = BufferTree::new();
+
+        // Define test data as a vector of (source, target, translation, rotation, timestamp) tuples
+        let transforms = vec![
+            (
+                "upper_arm_link",
+                "forearm_link",
+                [-0.425, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ),
+            (
+                "shoulder_link",
+                "upper_arm_link",
+                [0.0, 0.0, 0.0],
+                [
+                    0.5001990421112379,
+                    0.49980087872426926,
+                    -0.4998008786217583,
+                    0.5001990420086454,
+                ],
+            ),
+            (
+                "base_link_inertia",
+                "shoulder_link",
+                [0.0, 0.0, 0.1625],
+                [0.0, 0.0, 0.0, 1.0],
+            ),
+            (
+                "forearm_link",
+                "wrist_1_link",
+                [-0.3922, 0.0, 0.1333],
+                [0.0, 0.0, -0.7068251811053659, 0.7073882691671998],
+            ),
+            (
+                "wrist_1_link",
+                "wrist_2_link",
+                [0.0, -0.0997, -2.044881182297852e-11],
+                [0.7071067812590626, 0.0, 0.0, 0.7071067811140325],
+            ),
+            (
+                "wrist_2_link",
+                "wrist_3_link",
+                [0.0, 0.0996, -2.042830148012698e-11],
+                [
+                    -0.7071067812590626,
+                    8.659560562354933e-17,
+                    8.880526795522719e-27,
+                    0.7071067811140325,
+                ],
+            ),
+        ];
+
+        // Timestamps in nanoseconds
+        let timestamp_1: i64 = 1_000_000_000; // 1 second
+        let timestamp_2: i64 = 2_000_000_000; // 2 seconds
+
+        // Add all transforms to the buffer
+        for (source, target, translation, rotation) in transforms {
+            let stamped_isometry_1 = StampedIsometry::new(translation, rotation, timestamp_1);
+            let stamped_isometry_2 = StampedIsometry::new(translation, rotation, timestamp_2);
+
+            buffer_tree
+                .update(&[TransformUpdate::new(
+                    source,
+                    target,
+                    stamped_isometry_1,
+                    TransformType::Dynamic,
+                )])
+                .unwrap();
+            buffer_tree
+                .update(&[TransformUpdate::new(
+                    source,
+                    target,
+                    stamped_isometry_2,
+                    TransformType::Dynamic,
+                )])
+                .unwrap();
+        }
+
+        // Lookup at 1.5 seconds = 1_500_000_000 ns
+        let transform =
+            buffer_tree.lookup_transform("base_link_inertia", "shoulder_link", 1_500_000_000);
+        assert!(transform.is_ok());
+        let transform = transform.unwrap();
+        let translation = transform.translation();
+        let rotation = transform.rotation();
+
+        // Check translation components
+        assert_relative_eq!(translation[0], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(translation[1], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(translation[2], 0.1625, epsilon = 1e-3);
+
+        // Check quaternion components (w, x, y, z)
+        assert_relative_eq!(rotation[3], 1.0, epsilon = 1e-3);
+        assert_relative_eq!(rotation[0], 0.0, epsilon = 1e-3);
         assert_relative_eq!(rotation[1], 0.0, epsilon = 1e-3);
         assert_relative_eq!(rotation[2], 0.001, epsilon = 1e-3);
 
@@ -1246,13 +3183,13 @@ mod tests {
             }
         }
         match buffer_tree.lookup_transform("XXXXX", "shoulder_link", 3_000_000_000) {
-            Err(TfError::CouldNotFindTransform(_)) => {
+            Err(TfError::UnknownFrame(_)) => {
                 // The function returned the expected error variant
                 assert!(true);
             }
             _ => {
                 // The function did not return the expected error variant
-                assert!(false, "Expected TfError::CouldNotFindTransform");
+                assert!(false, "Expected TfError::UnknownFrame");
             }
         }
     }
@@ -1691,270 +3628,2691 @@ mod tests {
             ),
         ];
 
-        // Test each case
-        for (time, source, target, translation, rotation) in test_cases {
-            let result = buffer_tree.lookup_transform(source, target, time).unwrap();
+        // Test each case
+        for (time, source, target, translation, rotation) in test_cases {
+            let result = buffer_tree.lookup_transform(source, target, time).unwrap();
+
+            // Assert translation components
+            assert_relative_eq!(
+                result.translation()[0],
+                translation[0],
+                epsilon = 1e-6,
+                max_relative = 1e-6
+            );
+            assert_relative_eq!(
+                result.translation()[1],
+                translation[1],
+                epsilon = 1e-6,
+                max_relative = 1e-6
+            );
+            assert_relative_eq!(
+                result.translation()[2],
+                translation[2],
+                epsilon = 1e-6,
+                max_relative = 1e-6
+            );
+
+            // Assert rotation components (handle quaternion sign ambiguity)
+            let result_rot = result.rotation();
+            let expected_rot = rotation;
+
+            // Check if the quaternions are equivalent (q and -q represent the same rotation)
+            let mut matches = true;
+            let mut neg_matches = true;
+
+            for i in 0..4 {
+                if !approx::relative_eq!(result_rot[i], expected_rot[i], epsilon = 1e-6) {
+                    matches = false;
+                }
+                if !approx::relative_eq!(result_rot[i], -expected_rot[i], epsilon = 1e-6) {
+                    neg_matches = false;
+                }
+            }
+
+            assert!(
+                matches || neg_matches,
+                "Rotation mismatch: result={:?}, expected={:?}",
+                result_rot,
+                expected_rot
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup_latest_transform_no_path() {
+        let mut buffer_tree = BufferTree::new();
+
+        // Add some transforms but leave frames disconnected
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "C",
+                "D",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        // Try to lookup transform between disconnected frames
+        let result = buffer_tree.lookup_latest_transform("A", "C");
+        assert!(result.is_err());
+        assert!(matches!(result, Err(TfError::NoPath(_))));
+
+        // Try to lookup transform between non-existent frames
+        let result = buffer_tree.lookup_latest_transform("X", "Y");
+        assert!(result.is_err());
+        assert!(matches!(result, Err(TfError::UnknownFrame(_))));
+    }
+
+    #[test]
+    fn test_initial_history_capacity() {
+        let config = BufferConfig {
+            initial_history_capacity: 64,
+            ..BufferConfig::default()
+        };
+        let mut buffer_tree = BufferTree::with_config(config);
+        let sample = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "robot", sample.clone(), TransformType::Dynamic),
+                TransformUpdate::new("world", "map", sample, TransformType::Static),
+            ])
+            .unwrap();
+
+        let dynamic = buffer_tree.edge("world", "robot").unwrap();
+        assert!(dynamic.history.capacity() >= 64);
+        let static_edge = buffer_tree.edge("world", "map").unwrap();
+        assert!(static_edge.history.capacity() < 64);
+    }
+
+    #[test]
+    fn test_transform_history_buffer_window() {
+        let buffer_window = 1.0; // 1 second window
+        let mut history = TransformHistory::new(
+            TransformType::Dynamic,
+            buffer_window,
+            None,
+            InterpolationMode::Linear,
+            0,
+        );
+
+        // Add transforms at different times (using nanoseconds)
+        let transforms = vec![
+            (0, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]),
+            (200_000_000, [0.2, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.2s
+            (400_000_000, [0.4, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.4s
+            (600_000_000, [0.6, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.6s
+            (800_000_000, [0.8, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.8s
+            (1_200_000_000i64, [1.2, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 1.2s - causes older to be removed
+        ];
+
+        // Add all transforms
+        for (time, translation, rotation) in transforms {
+            let stamped_isometry = StampedIsometry::new(translation, rotation, time);
+            history.update(stamped_isometry, None);
+        }
+
+        // Check that oldest transforms (0.0s, 0.2s) were removed due to buffer window
+        assert!(history.history.len() < 6);
+        assert!(history.history.front().unwrap().stamp >= 200_000_000); // >= 0.2s
+
+        // Check that newest transforms are still present (1.2s)
+        assert_eq!(history.history.back().unwrap().stamp, 1_200_000_000);
+
+        // Verify interpolation still works within the valid time range (1.0s = 1_000_000_000 ns)
+        let result = history.interpolate_isometry_at_time(1_000_000_000);
+        assert!(result.is_ok());
+        let interpolated = result.unwrap();
+        assert_relative_eq!(interpolated.translation.vector[0], 1.0, epsilon = 1e-6);
+
+        // Verify interpolation fails for times outside the buffer window (0.1s = 100_000_000 ns)
+        let result = history.interpolate_isometry_at_time(100_000_000);
+        assert!(matches!(result, Err(TfError::AttemptedLookupInPast(_))));
+    }
+
+    #[test]
+    fn test_transform_history_evicts_all_stale_samples() {
+        let mut history = TransformHistory::new(
+            TransformType::Dynamic,
+            1.0,
+            None,
+            InterpolationMode::Linear,
+            0,
+        );
+        for stamp in [0, 100_000_000, 200_000_000] {
+            history.update(
+                StampedIsometry::new([0.0; 3], [0.0, 0.0, 0.0, 1.0], stamp),
+                None,
+            );
+        }
+        assert_eq!(history.history.len(), 3);
+
+        // After a long gap every older sample is out of the window at once,
+        // but the newest one stays.
+        history.update(
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 5_000_000_000),
+            None,
+        );
+        assert_eq!(history.history.len(), 1);
+        assert_eq!(history.qualities.len(), 1);
+        assert_eq!(history.history.back().unwrap().stamp, 5_000_000_000);
+    }
+
+    #[test]
+    fn test_lookup_latest_transform_stamp_is_oldest_dynamic_edge() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = |from: &str, to: &str, stamp: i64, kind: TransformType| {
+            TransformUpdate::new(
+                from,
+                to,
+                StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp),
+                kind,
+            )
+        };
+        buffer_tree
+            .update(&[
+                sample("world", "base", 5_000_000_000, TransformType::Dynamic),
+                sample("base", "arm", 2_000_000_000, TransformType::Dynamic),
+                sample("arm", "camera", 9_000_000_000, TransformType::Static),
+            ])
+            .unwrap();
+
+        let result = buffer_tree.lookup_latest_transform("world", "arm").unwrap();
+        assert_eq!(result.stamp(), 2_000_000_000);
+        assert_relative_eq!(result.translation()[0], 2.0);
+
+        // The static edge does not make the composite look newer or older.
+        let result = buffer_tree
+            .lookup_latest_transform("world", "camera")
+            .unwrap();
+        assert_eq!(result.stamp(), 2_000_000_000);
+
+        let result = buffer_tree
+            .lookup_latest_transform("arm", "camera")
+            .unwrap();
+        assert_eq!(result.stamp(), 9_000_000_000);
+    }
+
+    #[test]
+    fn test_ancestor_updates() {
+        let mut buffer_tree = BufferTree::new();
+
+        let a = "A";
+        let b = "B";
+        let c = "C";
+        let r = "R";
+
+        let tf = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+
+        // A -> B -> C
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                a,
+                b,
+                tf.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                b,
+                c,
+                tf.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        // Check ancestors
+        let get_ancestors = |buffer: &BufferTree, node: &str| -> Vec<String> {
+            let id = buffer.index.get(node).unwrap();
+            buffer.index.get_node(id).unwrap().ancestors.clone()
+        };
+
+        assert_eq!(get_ancestors(&buffer_tree, a), Vec::<String>::new());
+        assert_eq!(get_ancestors(&buffer_tree, b), vec![a.to_string()]);
+        assert_eq!(
+            get_ancestors(&buffer_tree, c),
+            vec![a.to_string(), b.to_string()]
+        );
+
+        // Add R -> A
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                r,
+                a,
+                tf.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        // Check updates
+        assert_eq!(get_ancestors(&buffer_tree, a), vec![r.to_string()]);
+        assert_eq!(
+            get_ancestors(&buffer_tree, b),
+            vec![r.to_string(), a.to_string()]
+        );
+        assert_eq!(
+            get_ancestors(&buffer_tree, c),
+            vec![r.to_string(), a.to_string(), b.to_string()]
+        );
+    }
+
+    /// Counts each `on_update` call and the size of every batch it sees.
+    #[derive(Default)]
+    struct CountingObserver {
+        calls: Mutex<Vec<usize>>,
+    }
+
+    impl BufferObserver for std::sync::Arc<CountingObserver> {
+        fn on_update(&self, updates: &[TransformUpdate]) {
+            self.calls.lock().unwrap().push(updates.len());
+        }
+    }
+
+    #[test]
+    fn test_update_batch_many_edges_one_stamp() {
+        // A star graph: root -> N children, all at the same stamp, in one update call.
+        let mut buffer_tree = BufferTree::new();
+        let stamp_ns = 1_000_000_000_i64;
+
+        let updates: Vec<TransformUpdate> = (0..5)
+            .map(|i| {
+                TransformUpdate::new(
+                    "root",
+                    format!("child_{}", i),
+                    StampedIsometry::new([i as f64, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp_ns),
+                    TransformType::Static,
+                )
+            })
+            .collect();
+
+        buffer_tree.update(&updates).unwrap();
+
+        for i in 0..5 {
+            let r = buffer_tree
+                .lookup_latest_transform("root", &format!("child_{}", i))
+                .unwrap();
+            assert_eq!(r.translation()[0], i as f64);
+        }
+    }
+
+    #[test]
+    fn test_update_batch_fail_fast_on_cycle() {
+        // A->B succeeds, then B->A in the same batch creates a cycle and the
+        // call returns Err. A->B remains applied (fail-fast, not transactional).
+        let mut buffer_tree = BufferTree::new();
+        let iso = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+
+        let result = buffer_tree.update(&[
+            TransformUpdate::new("A", "B", iso.clone(), TransformType::Static),
+            TransformUpdate::new("B", "A", iso.clone(), TransformType::Static),
+        ]);
+
+        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+        // First edge stuck around.
+        assert!(buffer_tree.lookup_latest_transform("A", "B").is_ok());
+    }
+
+    #[test]
+    fn test_update_batch_observer_called_once() {
+        let mut buffer_tree = BufferTree::new();
+        let observer = std::sync::Arc::new(CountingObserver::default());
+        buffer_tree.register_observer(Box::new(observer.clone()));
+
+        let updates: Vec<TransformUpdate> = (0..5)
+            .map(|i| {
+                TransformUpdate::new(
+                    "root",
+                    format!("child_{}", i),
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Static,
+                )
+            })
+            .collect();
+
+        buffer_tree.update(&updates).unwrap();
+
+        let calls = observer.calls.lock().unwrap();
+        assert_eq!(calls.len(), 1, "observer should be invoked exactly once");
+        assert_eq!(calls[0], 5, "observer should see the full 5-element batch");
+    }
+
+    #[test]
+    fn test_save_visualization_rate_limited() {
+        let save_dir = std::env::temp_dir().join("schiebung_test_save_visualization_rate_limited");
+        std::fs::create_dir_all(&save_dir).unwrap();
+        let config = BufferConfig {
+            save_path: save_dir.display().to_string(),
+            min_visualization_interval: 60.0,
+            ..BufferConfig::default()
+        };
+        let mut buffer_tree = BufferTree::with_config(config);
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        // The DOT file is written before Graphviz is invoked, so its presence
+        // tells us whether a save actually ran regardless of whether `dot` is
+        // installed on this machine.
+        let dot_file = save_dir.join("graph.dot");
+        let _ = buffer_tree.save_visualization();
+        assert!(dot_file.exists(), "first save should write the DOT file");
+        std::fs::remove_file(&dot_file).unwrap();
+
+        for _ in 0..10 {
+            assert!(buffer_tree.save_visualization().is_ok());
+        }
+        assert!(
+            !dot_file.exists(),
+            "rapid saves within the interval should be coalesced"
+        );
+
+        std::fs::remove_dir_all(&save_dir).ok();
+    }
+
+    #[test]
+    fn test_save_visualization_rate_limit_expires_with_manual_clock() {
+        let save_dir = std::env::temp_dir().join("schiebung_test_save_visualization_manual_clock");
+        std::fs::create_dir_all(&save_dir).unwrap();
+        let config = BufferConfig {
+            save_path: save_dir.display().to_string(),
+            min_visualization_interval: 60.0,
+            ..BufferConfig::default()
+        };
+        let clock = Arc::new(ManualClock::new(1000.0));
+        let mut buffer_tree = BufferTree::with_config(config);
+        buffer_tree.set_clock(clock.clone());
+
+        let dot_file = save_dir.join("graph.dot");
+        let _ = buffer_tree.save_visualization();
+        assert!(dot_file.exists(), "first save should write the DOT file");
+        std::fs::remove_file(&dot_file).unwrap();
+
+        clock.advance(59.0);
+        let _ = buffer_tree.save_visualization();
+        assert!(!dot_file.exists(), "save within the interval is coalesced");
+
+        clock.advance(1.0);
+        let _ = buffer_tree.save_visualization();
+        assert!(dot_file.exists(), "save after the interval runs again");
+        std::fs::remove_file(&dot_file).unwrap();
+
+        clock.set(10.0);
+        let _ = buffer_tree.save_visualization();
+        assert!(
+            dot_file.exists(),
+            "save after the clock jumped backward runs"
+        );
+        std::fs::remove_file(&dot_file).unwrap();
+
+        clock.advance(1.0);
+        let _ = buffer_tree.save_visualization();
+        assert!(
+            !dot_file.exists(),
+            "interval restarts from the jumped clock"
+        );
+
+        std::fs::remove_dir_all(&save_dir).ok();
+    }
+
+    /// Records every `on_static_changed` call as `(from, to, previous_x, new_x)`.
+    #[derive(Default)]
+    struct StaticChangeObserver {
+        changes: Mutex<Vec<(String, String, f64, f64)>>,
+    }
+
+    impl BufferObserver for std::sync::Arc<StaticChangeObserver> {
+        fn on_update(&self, _updates: &[TransformUpdate]) {}
+
+        fn on_static_changed(
+            &self,
+            from: &str,
+            to: &str,
+            previous: &StampedIsometry,
+            new: &StampedIsometry,
+        ) {
+            self.changes.lock().unwrap().push((
+                from.to_string(),
+                to.to_string(),
+                previous.translation()[0],
+                new.translation()[0],
+            ));
+        }
+    }
+
+    #[test]
+    fn test_static_edge_with_multiple_samples() {
+        let mut buffer_tree = BufferTree::new();
+        let observer = std::sync::Arc::new(StaticChangeObserver::default());
+        buffer_tree.register_observer(Box::new(observer.clone()));
+
+        let first = StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        let second = StampedIsometry::from_secs([2.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.0);
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                first.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        // Re-publishing the same pose is not a change.
+        buffer_tree
+            .update(&[TransformUpdate::new("A", "B", first, TransformType::Static)])
+            .unwrap();
+        assert!(observer.changes.lock().unwrap().is_empty());
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                second,
+                TransformType::Static,
+            )])
+            .unwrap();
+        assert_eq!(
+            *observer.changes.lock().unwrap(),
+            vec![("A".to_string(), "B".to_string(), 1.0, 2.0)]
+        );
+
+        // A static edge ignores the query time and always resolves to its
+        // newest sample, even for a time before that sample was published.
+        let result = buffer_tree
+            .lookup_transform("A", "B", 1_000_000_000)
+            .unwrap();
+        assert_eq!(result.translation()[0], 2.0);
+    }
+
+    #[test]
+    fn test_iter_edges() {
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "tool",
+                    StampedIsometry::from_secs([0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "tool",
+                    StampedIsometry::from_secs([0.0, 2.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+
+        let mut edges: Vec<(String, String, usize, f64)> = buffer_tree
+            .iter_edges()
+            .map(|edge| {
+                (
+                    edge.from.to_string(),
+                    edge.to.to_string(),
+                    edge.samples.len(),
+                    edge.latest().unwrap().translation()[1],
+                )
+            })
+            .collect();
+        edges.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            edges,
+            vec![
+                ("base".to_string(), "tool".to_string(), 2, 2.0),
+                ("world".to_string(), "base".to_string(), 1, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frames_updated_since() {
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "arm",
+                    StampedIsometry::from_secs([0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "arm",
+                    "tool",
+                    StampedIsometry::from_secs([0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0], 3.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            buffer_tree.frames_updated_since(0),
+            vec!["arm".to_string(), "base".to_string(), "tool".to_string()]
+        );
+        // Boundary is inclusive.
+        assert_eq!(
+            buffer_tree.frames_updated_since(2_000_000_000),
+            vec!["arm".to_string(), "tool".to_string()]
+        );
+        assert_eq!(
+            buffer_tree.frames_updated_since(2_500_000_000),
+            vec!["tool".to_string()]
+        );
+        assert!(buffer_tree.frames_updated_since(4_000_000_000).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_from_matches_lookups() {
+        let mut buffer_tree = BufferTree::new();
+        let quarter_turn_z = [0.0, 0.0, 0.7071067811865476, 0.7071067811865476];
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::from_secs([1.0, 0.0, 0.0], quarter_turn_z, 0.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "arm",
+                    StampedIsometry::from_secs([0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "arm",
+                    StampedIsometry::from_secs([0.0, 3.0, 0.0], quarter_turn_z, 1.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "arm",
+                    "tool",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.5], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "world",
+                    "camera",
+                    StampedIsometry::from_secs([0.0, 0.0, 2.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "elsewhere",
+                    "unrelated",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Static,
+                ),
+            ])
+            .unwrap();
+
+        let time = 500_000_000;
+        // Root in the middle of the tree so both edge directions are walked.
+        let snapshot = buffer_tree.snapshot_from("arm", time).unwrap();
+
+        let mut frames: Vec<&String> = snapshot.keys().collect();
+        frames.sort();
+        assert_eq!(frames, vec!["arm", "base", "camera", "tool", "world"]);
+
+        for (frame, pose) in &snapshot {
+            let expected = buffer_tree.lookup_transform("arm", frame, time).unwrap();
+            assert_eq!(pose.stamp(), time);
+            assert_relative_eq!(pose.isometry, expected.isometry, epsilon = 1e-9);
+        }
+
+        assert!(matches!(
+            buffer_tree.snapshot_from("missing", time),
+            Err(TfError::UnknownFrame(_))
+        ));
+
+        // Frames known only to the index are not roots either: one with
+        // metadata but no transforms, and one whose only edge was rejected.
+        buffer_tree.set_frame_metadata("planned", "sensor", "lidar");
+        assert!(buffer_tree
+            .update(&[TransformUpdate::new(
+                "second_parent",
+                "tool",
+                StampedIsometry::from_secs([0.0; 3], [0.0, 0.0, 0.0, 1.0], 0.0),
+                TransformType::Static,
+            )])
+            .is_err());
+        for root in ["planned", "second_parent"] {
+            assert!(matches!(
+                buffer_tree.snapshot_from(root, time),
+                Err(TfError::UnknownFrame(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_lookup_transform_relative() {
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::from_secs([0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0], 5.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "robot",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "robot",
+                    StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+
+        // The static edge's newer stamp does not move the reference time.
+        let result = buffer_tree
+            .lookup_transform_relative("world", "robot", 0.5)
+            .unwrap();
+        assert_eq!(result.stamp(), 500_000_000);
+        assert_relative_eq!(result.translation()[0], 0.5, epsilon = 1e-9);
+        assert_relative_eq!(result.translation()[2], 1.0, epsilon = 1e-9);
+
+        assert!(matches!(
+            buffer_tree.lookup_transform_relative("world", "robot", 2.0),
+            Err(TfError::AttemptedLookupInPast(_))
+        ));
+    }
+
+    #[test]
+    fn test_update_batch_is_atomic() {
+        let mut buffer_tree = BufferTree::new();
+        let identity =
+            |secs| StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", identity(0.0), TransformType::Static),
+                TransformUpdate::new("base", "arm", identity(0.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+
+        // The last update closes the cycle world -> base -> arm -> tool -> world.
+        let result = buffer_tree.update_batch(&[
+            TransformUpdate::new("base", "arm", identity(1.0), TransformType::Dynamic),
+            TransformUpdate::new("arm", "tool", identity(1.0), TransformType::Dynamic),
+            TransformUpdate::new("tool", "world", identity(1.0), TransformType::Dynamic),
+        ]);
+        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+
+        // None of the batch was applied: no new frame, no new sample.
+        assert!(buffer_tree.lookup_latest_transform("base", "tool").is_err());
+        let arm = buffer_tree.lookup_latest_transform("base", "arm").unwrap();
+        assert_eq!(arm.stamp(), 0);
+
+        // A second parent introduced within the batch is rejected too.
+        let result = buffer_tree.update_batch(&[
+            TransformUpdate::new("arm", "tool", identity(1.0), TransformType::Dynamic),
+            TransformUpdate::new("base", "tool", identity(1.0), TransformType::Dynamic),
+        ]);
+        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
+        assert!(buffer_tree.lookup_latest_transform("arm", "tool").is_err());
+
+        // A valid batch is applied in full.
+        buffer_tree
+            .update_batch(&[
+                TransformUpdate::new("base", "arm", identity(1.0), TransformType::Dynamic),
+                TransformUpdate::new("arm", "tool", identity(1.0), TransformType::Static),
+            ])
+            .unwrap();
+        assert!(buffer_tree.lookup_latest_transform("world", "tool").is_ok());
+    }
+
+    #[test]
+    fn test_accept_inverse_edges() {
+        let config = BufferConfig {
+            accept_inverse_edges: true,
+            ..BufferConfig::default()
+        };
+        let mut buffer_tree = BufferTree::with_config(config);
+        let quarter_turn_z = [0.0, 0.0, 0.7071067811865476, 0.7071067811865476];
+        let a_to_b = StampedIsometry::from_secs([1.0, 2.0, 0.0], quarter_turn_z, 0.0);
+        let b_to_a = StampedIsometry {
+            isometry: a_to_b.isometry.inverse(),
+            stamp: a_to_b.stamp,
+        };
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                a_to_b.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "B",
+                "A",
+                b_to_a.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        let forward = buffer_tree.lookup_latest_transform("A", "B").unwrap();
+        let backward = buffer_tree.lookup_latest_transform("B", "A").unwrap();
+        assert_relative_eq!(forward.isometry, a_to_b.isometry, epsilon = 1e-9);
+        assert_relative_eq!(backward.isometry, b_to_a.isometry, epsilon = 1e-9);
+
+        // An update that is not the inverse is still rejected.
+        let wrong = StampedIsometry::from_secs([5.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0);
+        assert!(matches!(
+            buffer_tree.update(&[TransformUpdate::new("B", "A", wrong, TransformType::Static)]),
+            Err(TfError::InvalidGraph(_))
+        ));
+
+        // Dynamic samples at new stamps are inverted onto the existing edge.
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "B",
+                    "C",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "C",
+                    "B",
+                    StampedIsometry::from_secs([-2.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+        let mid = buffer_tree.lookup_transform("B", "C", 500_000_000).unwrap();
+        assert_relative_eq!(mid.translation()[0], 1.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_inverse_edges_rejected_by_default() {
+        let mut buffer_tree = BufferTree::new();
+        let identity = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0);
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "A",
+                "B",
+                identity.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        assert!(matches!(
+            buffer_tree.update(&[TransformUpdate::new(
+                "B",
+                "A",
+                identity,
+                TransformType::Static
+            )]),
+            Err(TfError::InvalidGraph(_))
+        ));
+    }
+
+    #[test]
+    fn test_interpolate_matches_buffer() {
+        let quarter_turn_z = [0.0, 0.0, 0.7071067811865476, 0.7071067811865476];
+        let a = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        let b = StampedIsometry::from_secs([2.0, -4.0, 1.0], quarter_turn_z, 3.0);
+
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "robot", a.clone(), TransformType::Dynamic),
+                TransformUpdate::new("world", "robot", b.clone(), TransformType::Dynamic),
+            ])
+            .unwrap();
+
+        for time in [1_000_000_000, 1_700_000_000, 2_500_000_000, 3_000_000_000] {
+            let expected = buffer_tree
+                .lookup_transform("world", "robot", time)
+                .unwrap();
+            assert_relative_eq!(
+                crate::interpolate(&a, &b, time),
+                expected.isometry,
+                epsilon = 1e-12
+            );
+        }
+
+        // Outside the two stamps the nearer pose is returned.
+        assert_relative_eq!(crate::interpolate(&a, &b, 0), a.isometry);
+        assert_relative_eq!(crate::interpolate(&a, &b, 5_000_000_000), b.isometry);
+    }
+
+    #[test]
+    fn test_valid_time_range() {
+        let identity =
+            |secs| StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", identity(0.0), TransformType::Static),
+                TransformUpdate::new("base", "arm", identity(1.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", identity(4.0), TransformType::Dynamic),
+                TransformUpdate::new("arm", "tool", identity(2.0), TransformType::Dynamic),
+                TransformUpdate::new("arm", "tool", identity(6.0), TransformType::Dynamic),
+                TransformUpdate::new("world", "camera", identity(5.0), TransformType::Dynamic),
+                TransformUpdate::new("world", "camera", identity(7.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+
+        // Overlapping ranges [1, 4] and [2, 6] intersect to [2, 4].
+        let (earliest, latest) = buffer_tree.valid_time_range("world", "tool").unwrap();
+        assert_eq!((earliest, latest), (2_000_000_000, 4_000_000_000));
+        assert!(buffer_tree
+            .lookup_transform("world", "tool", earliest)
+            .is_ok());
+        assert!(buffer_tree
+            .lookup_transform("world", "tool", latest)
+            .is_ok());
+        assert!(buffer_tree
+            .lookup_transform("world", "tool", latest + 1)
+            .is_err());
+
+        // Static-only paths are valid at any time.
+        assert_eq!(
+            buffer_tree.valid_time_range("world", "base").unwrap(),
+            (i64::MIN, i64::MAX)
+        );
+
+        // [1, 4] and [5, 7] do not overlap.
+        assert!(matches!(
+            buffer_tree.valid_time_range("arm", "camera"),
+            Err(TfError::CouldNotFindTransform(_))
+        ));
+    }
+
+    #[test]
+    fn test_display_summary() {
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "tool",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "tool",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.5),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            buffer_tree.to_string(),
+            "base -> tool [Dynamic, 2 samples, latest t=2.500000s]\n\
+             world -> base [Static, 1 samples, latest t=1.000000s]\n"
+        );
+    }
+
+    #[test]
+    fn test_lookup_with_age() {
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::from_secs([2.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 2.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+
+        let (transform, age) = buffer_tree
+            .lookup_with_age("world", "robot", 1_500_000_000, 3_750_000_000)
+            .unwrap();
+        let expected = buffer_tree
+            .lookup_transform("world", "robot", 1_500_000_000)
+            .unwrap();
+        assert_relative_eq!(transform.isometry, expected.isometry);
+        assert_relative_eq!(
+            age,
+            (3_750_000_000 - transform.stamp()) as f64 / 1e9,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(age, 2.25, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_max_interpolation_gap() {
+        let config = BufferConfig {
+            max_interpolation_gap: Some(0.5),
+            ..BufferConfig::default()
+        };
+        let mut buffer_tree = BufferTree::with_config(config);
+        let sample =
+            |x, secs| StampedIsometry::from_secs([x, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "robot", sample(0.0, 0.0), TransformType::Dynamic),
+                TransformUpdate::new("world", "robot", sample(0.1, 0.1), TransformType::Dynamic),
+                // Dropout between 0.1 s and 5.0 s.
+                TransformUpdate::new("world", "robot", sample(5.0, 5.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+
+        let inside = buffer_tree
+            .lookup_transform("world", "robot", 50_000_000)
+            .unwrap();
+        assert_relative_eq!(inside.translation()[0], 0.05, epsilon = 1e-9);
+
+        assert!(matches!(
+            buffer_tree.lookup_transform("world", "robot", 2_000_000_000),
+            Err(TfError::ExtrapolationGap(_))
+        ));
+        // Samples at the edges of the gap are still returned exactly.
+        assert!(buffer_tree
+            .lookup_transform("world", "robot", 5_000_000_000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_lookup_transform_detailed() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample =
+            |x, secs| StampedIsometry::from_secs([x, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", sample(1.0, 0.0), TransformType::Static),
+                TransformUpdate::new("base", "arm", sample(0.0, 1.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", sample(2.0, 2.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+
+        let (exact, interpolated) = buffer_tree
+            .lookup_transform_detailed("world", "arm", 2_000_000_000)
+            .unwrap();
+        assert!(!interpolated);
+        assert_eq!(
+            exact,
+            buffer_tree
+                .lookup_transform("world", "arm", 2_000_000_000)
+                .unwrap()
+        );
+
+        let (mid, interpolated) = buffer_tree
+            .lookup_transform_detailed("arm", "world", 1_500_000_000)
+            .unwrap();
+        assert!(interpolated);
+        assert_relative_eq!(mid.translation()[0], -2.0, epsilon = 1e-9);
+
+        // Static-only paths never interpolate.
+        let (_, interpolated) = buffer_tree
+            .lookup_transform_detailed("world", "base", 1_500_000_000)
+            .unwrap();
+        assert!(!interpolated);
+
+        assert!(buffer_tree
+            .lookup_transform_detailed("world", "ghost", 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_lookup_ground_projection() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let tilted = Isometry3::from_parts(
+            Translation3::new(1.0, 2.0, 3.0),
+            UnitQuaternion::from_euler_angles(0.3, -0.2, 0.7),
+        );
+        let rotation = tilted.rotation.into_inner();
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "map",
+                "robot",
+                StampedIsometry::new(
+                    [1.0, 2.0, 3.0],
+                    [rotation.i, rotation.j, rotation.k, rotation.w],
+                    0,
+                ),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        let ground = buffer_tree
+            .lookup_ground_projection("map", "robot", 5)
+            .unwrap();
+
+        assert_eq!(ground.stamp(), 5);
+        assert_relative_eq!(ground.translation()[0], 1.0, epsilon = 1e-9);
+        assert_relative_eq!(ground.translation()[1], 2.0, epsilon = 1e-9);
+        assert_relative_eq!(ground.translation()[2], 0.0, epsilon = 1e-9);
+        let [roll, pitch, yaw] = ground.euler_angles();
+        assert_relative_eq!(roll, 0.0, epsilon = 1e-9);
+        assert_relative_eq!(pitch, 0.0, epsilon = 1e-9);
+        assert_relative_eq!(yaw, 0.7, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_lookup_rotation_and_translation() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        // Quarter turns about z: sin(pi/4) = cos(pi/4).
+        let quarter = std::f64::consts::FRAC_1_SQRT_2;
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, quarter, quarter], 0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "sensor",
+                    StampedIsometry::new([1.0, 0.0, 0.5], [0.0, 0.0, quarter, quarter], 0),
+                    TransformType::Static,
+                ),
+            ])
+            .unwrap();
+
+        let full = buffer_tree.lookup_transform("world", "sensor", 0).unwrap();
+        let translation = buffer_tree
+            .lookup_translation("world", "sensor", 0)
+            .unwrap();
+        let rotation = buffer_tree.lookup_rotation("world", "sensor", 0).unwrap();
+
+        // The second offset is rotated by the first quarter turn.
+        assert_relative_eq!(translation[0], 1.0, epsilon = 1e-9);
+        assert_relative_eq!(translation[1], 1.0, epsilon = 1e-9);
+        assert_relative_eq!(translation[2], 0.5, epsilon = 1e-9);
+        assert_eq!(translation, full.translation());
+        assert_relative_eq!(rotation.angle(), std::f64::consts::PI, epsilon = 1e-9);
+        assert_eq!(rotation, full.isometry.rotation);
+
+        assert!(matches!(
+            buffer_tree.lookup_rotation("world", "camera", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_translation("world", "camera", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_resample_edge() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let updates: Vec<_> = (0..=2)
+            .map(|secs| {
+                let secs = secs as f64;
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::from_secs([secs, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs),
+                    TransformType::Dynamic,
+                )
+            })
+            .collect();
+        buffer_tree.update(&updates).unwrap();
+
+        let (start, end, rate_hz) = (0, 2_000_000_000, 10.0);
+        let samples = buffer_tree
+            .resample_edge("world", "robot", rate_hz, start, end)
+            .unwrap();
+
+        let expected = ((end - start) as f64 / 1e9 * rate_hz) as usize + 1;
+        assert_eq!(samples.len(), expected);
+        for (i, sample) in samples.iter().enumerate() {
+            assert_eq!(sample.stamp(), i as i64 * 100_000_000);
+            assert_relative_eq!(sample.translation()[0], i as f64 * 0.1, epsilon = 1e-9);
+        }
+
+        // A rate that does not divide a second still ends exactly on `end`.
+        let thirds = buffer_tree
+            .resample_edge("world", "robot", 3.0, 0, 1_000_000_000)
+            .unwrap();
+        assert_eq!(thirds.len(), 4);
+        assert_eq!(thirds[3].stamp(), 1_000_000_000);
+
+        assert!(buffer_tree
+            .resample_edge("world", "robot", rate_hz, end, start)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            buffer_tree.resample_edge("world", "robot", rate_hz, start, 3_000_000_000),
+            Err(TfError::AttemptedLookUpInFuture(_))
+        ));
+    }
+
+    #[test]
+    fn test_resample_edge_rejects_invalid_rates() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "world",
+                "robot",
+                StampedIsometry::new([0.0; 3], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        for rate_hz in [f64::INFINITY, f64::NAN, 0.0, -1.0, 2e9] {
+            assert!(
+                matches!(
+                    buffer_tree.resample_edge("world", "robot", rate_hz, 0, 1_000_000_000),
+                    Err(TfError::CouldNotFindTransform(_))
+                ),
+                "rate {} was accepted",
+                rate_hz
+            );
+        }
+        // A valid rate over a range too long for the sample cap.
+        assert!(matches!(
+            buffer_tree.resample_edge("world", "robot", 1000.0, 0, 10_000_000_000_000),
+            Err(TfError::CouldNotFindTransform(_))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_error_names_failing_edge() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample =
+            |secs: f64| StampedIsometry::from_secs([secs, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", sample(0.0), TransformType::Dynamic),
+                TransformUpdate::new("world", "base", sample(10.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", sample(5.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", sample(10.0), TransformType::Dynamic),
+                TransformUpdate::new("arm", "tool", sample(0.0), TransformType::Static),
+            ])
+            .unwrap();
+
+        // Only the second hop lacks data at 2 s.
+        match buffer_tree.lookup_transform("world", "tool", 2_000_000_000) {
+            Err(TfError::AttemptedLookupInPast(message)) => assert_eq!(
+                message,
+                "Edge 'base' -> 'arm' (samples from 5000000000 to 10000000000): \
+                 Time 2000000000 is before the oldest transform at 5000000000"
+            ),
+            other => panic!("Expected AttemptedLookupInPast, got {:?}", other),
+        }
+
+        // Walking the edge in reverse still names it as stored.
+        match buffer_tree.lookup_transform("tool", "world", 11_000_000_000) {
+            Err(TfError::AttemptedLookUpInFuture(message)) => {
+                assert!(message.starts_with("Edge 'base' -> 'arm' (samples from"));
+            }
+            other => panic!("Expected AttemptedLookUpInFuture, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lookup_transform_with_offset() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let quarter = std::f64::consts::FRAC_1_SQRT_2;
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::from_secs([3.0, 0.0, 0.0], [0.0, 0.0, quarter, quarter], 2.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+        let offset = StampedIsometry::new([0.5, -0.2, 0.1], [0.0, quarter, 0.0, quarter], 0);
+
+        let with_offset = buffer_tree
+            .lookup_transform_with_offset("world", "robot", 1_000_000_000, offset.isometry)
+            .unwrap();
+        assert_eq!(buffer_tree.frame_count(), 2);
+
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "robot",
+                "commanded",
+                offset,
+                TransformType::Static,
+            )])
+            .unwrap();
+        let via_edge = buffer_tree
+            .lookup_transform("world", "commanded", 1_000_000_000)
+            .unwrap();
+
+        assert_eq!(with_offset.stamp(), via_edge.stamp());
+        assert_relative_eq!(with_offset.isometry, via_edge.isometry, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_single_sample_dynamic_edge() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = StampedIsometry::from_secs([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "world",
+                "robot",
+                sample.clone(),
+                TransformType::Dynamic,
+            )])
+            .unwrap();
+
+        let result = buffer_tree
+            .lookup_transform("world", "robot", 1_000_000_000)
+            .unwrap();
+        assert_eq!(result, sample);
+        assert_eq!(
+            buffer_tree.valid_time_range("world", "robot").unwrap(),
+            (1_000_000_000, 1_000_000_000)
+        );
+
+        assert!(matches!(
+            buffer_tree.lookup_transform("world", "robot", 999_999_999),
+            Err(TfError::AttemptedLookupInPast(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_transform("world", "robot", 1_000_000_001),
+            Err(TfError::AttemptedLookUpInFuture(_))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_frame_relative_to_itself() {
+        let buffer_tree = BufferTree::with_config(BufferConfig::default());
+
+        let result = buffer_tree
+            .lookup_transform("foo", "foo", 5_000_000_000)
+            .unwrap();
+        assert_eq!(result.stamp(), 5_000_000_000);
+        assert_eq!(result.isometry, Isometry3::identity());
+        assert!(!buffer_tree.has_frame("foo"));
+        assert!(matches!(
+            buffer_tree.lookup_transform("foo", "bar", 5_000_000_000),
+            Err(TfError::UnknownFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_invariants_reports_corruption() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("A", "B", sample.clone(), TransformType::Static),
+                TransformUpdate::new("B", "C", sample, TransformType::Dynamic),
+            ])
+            .unwrap();
+        assert_eq!(buffer_tree.check_invariants(), Ok(()));
+
+        // Bypass update()'s validation to close a cycle and give B a second
+        // parent, both through edges without samples.
+        let empty = || {
+            TransformHistory::new(
+                TransformType::Static,
+                120.0,
+                None,
+                InterpolationMode::Linear,
+                0,
+            )
+        };
+        let a = buffer_tree.index.get("A").unwrap();
+        let b = buffer_tree.index.get("B").unwrap();
+        let c = buffer_tree.index.get("C").unwrap();
+        let d = buffer_tree.index.index("D");
+        buffer_tree.graph.add_edge(c, a, empty());
+        buffer_tree.graph.add_edge(d, b, empty());
+
+        let violations = buffer_tree.check_invariants().unwrap_err();
+        assert_eq!(
+            violations,
+            vec![
+                "Frame 'B' has 2 parents: A, D".to_string(),
+                "Graph contains a cycle".to_string(),
+                "Edge 'C' -> 'A' has no samples".to_string(),
+                "Edge 'D' -> 'B' has no samples".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_static_locks_edge() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let calibration = StampedIsometry::from_secs([0.1, 0.0, 0.2], [0.0, 0.0, 0.0, 1.0], 0.0);
+        buffer_tree
+            .set_static("robot", "camera", calibration.clone())
+            .unwrap();
+
+        let runaway = TransformUpdate::new(
+            "robot",
+            "camera",
+            StampedIsometry::from_secs([5.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
+            TransformType::Dynamic,
+        );
+        assert!(matches!(
+            buffer_tree.update(std::slice::from_ref(&runaway)),
+            Err(TfError::LockedTransform(_))
+        ));
+        assert!(matches!(
+            buffer_tree.update_batch(&[runaway]),
+            Err(TfError::LockedTransform(_))
+        ));
+        assert!(matches!(
+            buffer_tree.set_static("robot", "camera", calibration),
+            Err(TfError::LockedTransform(_))
+        ));
+
+        let stored = buffer_tree
+            .lookup_latest_transform("robot", "camera")
+            .unwrap();
+        assert_relative_eq!(stored.translation()[0], 0.1, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_set_static_rejects_existing_inverse_edge() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig {
+            accept_inverse_edges: true,
+            ..BufferConfig::default()
+        });
+        let calibration = StampedIsometry::from_secs([0.1, 0.0, 0.2], [0.0, 0.0, 0.0, 1.0], 0.0);
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "robot",
+                "camera",
+                calibration.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        let inverse = StampedIsometry {
+            isometry: calibration.isometry.inverse(),
+            stamp: calibration.stamp,
+        };
+        assert!(matches!(
+            buffer_tree.set_static("camera", "robot", inverse),
+            Err(TfError::InvalidGraph(_))
+        ));
+        // The existing edge is untouched and still accepts updates.
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "robot",
+                "camera",
+                calibration,
+                TransformType::Static,
+            )])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_last_update_stamp() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = |secs| StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", sample(1.0), TransformType::Static),
+                TransformUpdate::new("base", "arm", sample(2.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", sample(4.0), TransformType::Dynamic),
+                TransformUpdate::new("arm", "tool", sample(3.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+
+        assert_eq!(buffer_tree.last_update_stamp("world"), Some(1_000_000_000));
+        // Newest of the incoming world -> base and outgoing base -> arm edges.
+        assert_eq!(buffer_tree.last_update_stamp("base"), Some(4_000_000_000));
+        assert_eq!(buffer_tree.last_update_stamp("arm"), Some(4_000_000_000));
+        assert_eq!(buffer_tree.last_update_stamp("tool"), Some(3_000_000_000));
+        assert_eq!(buffer_tree.last_update_stamp("unknown"), None);
+        assert!(buffer_tree.has_frame("tool"));
+        assert!(!buffer_tree.has_frame("unknown"));
+        assert_eq!(buffer_tree.frame_count(), 4);
+    }
+
+    #[test]
+    fn test_subtree() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = |x: f64, secs: f64| {
+            StampedIsometry::from_secs([x, 1.0, 0.0], [0.0, 0.0, 0.38268343, 0.92387953], secs)
+        };
+        let mut updates = vec![
+            TransformUpdate::new("world", "robot_1", sample(1.0, 0.0), TransformType::Static),
+            TransformUpdate::new("world", "robot_2", sample(2.0, 0.0), TransformType::Static),
+            TransformUpdate::new(
+                "robot_2",
+                "gripper",
+                sample(0.5, 0.0),
+                TransformType::Static,
+            ),
+            TransformUpdate::new("robot_1", "camera", sample(0.2, 0.0), TransformType::Static),
+        ];
+        for secs in [0.0, 1.0, 2.0] {
+            updates.push(TransformUpdate::new(
+                "robot_1",
+                "arm",
+                sample(secs, secs),
+                TransformType::Dynamic,
+            ));
+            updates.push(TransformUpdate::new(
+                "arm",
+                "tool",
+                sample(-secs, secs),
+                TransformType::Dynamic,
+            ));
+        }
+        buffer_tree.update(&updates).unwrap();
+
+        let subtree = buffer_tree.subtree("robot_1").unwrap();
 
-            // Assert translation components
-            assert_relative_eq!(
-                result.translation()[0],
-                translation[0],
-                epsilon = 1e-6,
-                max_relative = 1e-6
-            );
-            assert_relative_eq!(
-                result.translation()[1],
-                translation[1],
-                epsilon = 1e-6,
-                max_relative = 1e-6
-            );
-            assert_relative_eq!(
-                result.translation()[2],
-                translation[2],
-                epsilon = 1e-6,
-                max_relative = 1e-6
-            );
+        for frame in ["robot_1", "arm", "tool", "camera"] {
+            assert!(subtree.has_frame(frame), "{} missing", frame);
+        }
+        for frame in ["world", "robot_2", "gripper"] {
+            assert!(!subtree.has_frame(frame), "{} should not be copied", frame);
+        }
+        assert_eq!(subtree.iter_edges().count(), 3);
+        assert_eq!(
+            subtree.valid_time_range("robot_1", "tool").unwrap(),
+            (0, 2_000_000_000)
+        );
 
-            // Assert rotation components (handle quaternion sign ambiguity)
-            let result_rot = result.rotation();
-            let expected_rot = rotation;
+        for (from, to) in [("robot_1", "tool"), ("tool", "camera"), ("camera", "arm")] {
+            for time in [0, 500_000_000, 1_250_000_000, 2_000_000_000] {
+                let expected = buffer_tree.lookup_transform(from, to, time).unwrap();
+                let result = subtree.lookup_transform(from, to, time).unwrap();
+                assert_eq!(result, expected, "{} -> {} at {}", from, to, time);
+            }
+        }
 
-            // Check if the quaternions are equivalent (q and -q represent the same rotation)
-            let mut matches = true;
-            let mut neg_matches = true;
+        assert!(matches!(
+            buffer_tree.subtree("ghost"),
+            Err(TfError::UnknownFrame(_))
+        ));
+    }
 
-            for i in 0..4 {
-                if !approx::relative_eq!(result_rot[i], expected_rot[i], epsilon = 1e-6) {
-                    matches = false;
-                }
-                if !approx::relative_eq!(result_rot[i], -expected_rot[i], epsilon = 1e-6) {
-                    neg_matches = false;
-                }
-            }
+    #[test]
+    fn test_clear() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0);
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "world",
+                "robot",
+                sample.clone(),
+                TransformType::Static,
+            )])
+            .unwrap();
+        buffer_tree
+            .set_static("robot", "camera", sample.clone())
+            .unwrap();
+        let generation = buffer_tree.topology_generation();
+
+        buffer_tree.clear();
+
+        assert_eq!(buffer_tree.frame_count(), 0);
+        assert!(!buffer_tree.has_frame("world"));
+        assert!(buffer_tree.lookup_transform("world", "robot", 0).is_err());
+        assert_ne!(buffer_tree.topology_generation(), generation);
+
+        // The locked edge is gone too and can be set again.
+        buffer_tree.set_static("robot", "camera", sample).unwrap();
+        assert!(buffer_tree.lookup_transform("robot", "camera", 0).is_ok());
+    }
+
+    #[test]
+    fn test_clear_removes_metadata_of_frames_outside_the_graph() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        buffer_tree.set_frame_metadata("camera", "sensor", "realsense d435");
+        let generation = buffer_tree.topology_generation();
+
+        buffer_tree.clear();
+
+        assert_eq!(buffer_tree.get_frame_metadata("camera", "sensor"), None);
+        assert_eq!(buffer_tree.topology_generation(), generation);
+    }
+
+    #[test]
+    fn test_neighbors_within() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = StampedIsometry::from_secs([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0);
+        let chain = ["a", "b", "c", "d", "e"];
+        let updates: Vec<_> = chain
+            .windows(2)
+            .map(|pair| {
+                TransformUpdate::new(pair[0], pair[1], sample.clone(), TransformType::Static)
+            })
+            .collect();
+        buffer_tree.update(&updates).unwrap();
+
+        assert_eq!(buffer_tree.neighbors_within("c", 0), Vec::<String>::new());
+        assert_eq!(buffer_tree.neighbors_within("c", 1), vec!["b", "d"]);
+        assert_eq!(
+            buffer_tree.neighbors_within("c", 2),
+            vec!["a", "b", "d", "e"]
+        );
+        assert_eq!(buffer_tree.neighbors_within("a", 2), vec!["b", "c"]);
+        assert!(buffer_tree.neighbors_within("unknown", 2).is_empty());
+    }
+
+    #[test]
+    fn test_clear_edge_history_after_time_jump() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample =
+            |secs| StampedIsometry::from_secs([secs, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", sample(1.0), TransformType::Static),
+                TransformUpdate::new("base", "arm", sample(9.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", sample(10.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+        let generation = buffer_tree.topology_generation();
+
+        assert_eq!(
+            buffer_tree.edge_latest_stamp("base", "arm"),
+            Some(10_000_000_000)
+        );
+        assert_eq!(buffer_tree.edge_latest_stamp("arm", "base"), None);
+        assert!(!buffer_tree.clear_edge_history("world", "base"));
+        assert!(!buffer_tree.clear_edge_history("base", "ghost"));
+
+        // The publisher's clock restarted at 0: drop the old timeline and continue.
+        assert!(buffer_tree.clear_edge_history("base", "arm"));
+        assert_eq!(buffer_tree.edge_latest_stamp("base", "arm"), None);
+        assert_eq!(buffer_tree.topology_generation(), generation);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("base", "arm", sample(0.0), TransformType::Dynamic),
+                TransformUpdate::new("base", "arm", sample(1.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+        let result = buffer_tree
+            .lookup_transform("base", "arm", 500_000_000)
+            .unwrap();
+        assert_relative_eq!(result.translation()[0], 0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_visualize_with_lookup_failures() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", sample.clone(), TransformType::Dynamic),
+                TransformUpdate::new("world", "camera", sample, TransformType::Dynamic),
+            ])
+            .unwrap();
+        let world = buffer_tree.index.get("world").unwrap();
+        let base = buffer_tree.index.get("base").unwrap();
+        let camera = buffer_tree.index.get("camera").unwrap();
+
+        assert!(!buffer_tree.visualize().contains("failed lookups"));
+
+        let mut failures = HashMap::new();
+        failures.insert(("base".to_string(), "camera".to_string()), 3);
+        failures.insert(("world".to_string(), "ghost".to_string()), 1);
+        let dot = buffer_tree.visualize_with_lookup_failures(&failures);
+
+        assert!(dot.contains(&format!(
+            "{} -> {} [label=\"3 failed lookups\"",
+            base, camera
+        )));
+        assert!(dot.contains("\"ghost\" [label=\"ghost\", style=dashed]"));
+        assert!(dot.contains(&format!(
+            "{} -> \"ghost\" [label=\"1 failed lookups\"",
+            world
+        )));
+        // Both edges on the base -> world -> camera path are highlighted.
+        for (from, to) in [(world, base), (world, camera)] {
+            let line = dot
+                .lines()
+                .find(|line| {
+                    line.trim_start()
+                        .starts_with(&format!("{} -> {} [label=\"t=", from, to))
+                })
+                .unwrap();
+            assert!(line.ends_with(", color=red]"));
+        }
+
+        // Quotes in a frame the buffer does not know cannot break out of the
+        // DOT string.
+        let mut failures = HashMap::new();
+        failures.insert(("world".to_string(), "cam\"]; x [".to_string()), 2);
+        let dot = buffer_tree.visualize_with_lookup_failures(&failures);
+        assert!(dot.contains(r#""cam\"]; x [" [label="cam\"]; x [", style=dashed]"#));
+        assert!(dot.contains(&format!(
+            r#"{} -> "cam\"]; x [" [label="2 failed lookups""#,
+            world
+        )));
+    }
+
+    #[test]
+    fn test_lookup_acceleration() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        // Constant linear acceleration of 2 m/s² along x and angular
+        // acceleration of 0.4 rad/s² about z, starting from rest.
+        let (linear_acc, angular_acc) = (2.0, 0.4);
+        let updates: Vec<TransformUpdate> = (0..=20)
+            .map(|i| {
+                let t = i as f64 * 0.5;
+                let yaw = 0.5 * angular_acc * t * t;
+                TransformUpdate::new(
+                    "world",
+                    "cart",
+                    StampedIsometry::from_secs(
+                        [0.5 * linear_acc * t * t, 0.0, 0.0],
+                        [0.0, 0.0, (yaw / 2.0).sin(), (yaw / 2.0).cos()],
+                        t,
+                    ),
+                    TransformType::Dynamic,
+                )
+            })
+            .collect();
+        buffer_tree.update(&updates).unwrap();
 
+        let (linear, angular) = buffer_tree
+            .lookup_acceleration("world", "cart", 5_000_000_000, 0.5)
+            .unwrap();
+        assert_relative_eq!(linear[0], linear_acc, epsilon = 1e-6);
+        assert_relative_eq!(linear[1], 0.0, epsilon = 1e-6);
+        assert_relative_eq!(angular[2], angular_acc, epsilon = 1e-6);
+        assert_relative_eq!(angular[0], 0.0, epsilon = 1e-6);
+
+        assert!(matches!(
+            buffer_tree.lookup_acceleration("world", "cart", 10_000_000_000, 0.5),
+            Err(TfError::AttemptedLookUpInFuture(_))
+        ));
+        for dt_secs in [0.0, -0.5, f64::NAN, f64::INFINITY, 1e-10] {
             assert!(
-                matches || neg_matches,
-                "Rotation mismatch: result={:?}, expected={:?}",
-                result_rot,
-                expected_rot
+                matches!(
+                    buffer_tree.lookup_acceleration("world", "cart", 5_000_000_000, dt_secs),
+                    Err(TfError::CouldNotFindTransform(_))
+                ),
+                "dt_secs {} was accepted",
+                dt_secs
             );
         }
+        assert!(matches!(
+            buffer_tree.lookup_acceleration("world", "cart", i64::MAX, 0.5),
+            Err(TfError::CouldNotFindTransform(_))
+        ));
     }
 
     #[test]
-    fn test_lookup_latest_transform_no_path() {
-        let mut buffer_tree = BufferTree::new();
+    fn test_topology_generation() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = |secs| StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs);
+        assert_eq!(buffer_tree.topology_generation(), 0);
 
-        // Add some transforms but leave frames disconnected
         buffer_tree
             .update(&[TransformUpdate::new(
-                "A",
-                "B",
-                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
-                TransformType::Static,
+                "world",
+                "base",
+                sample(0.0),
+                TransformType::Dynamic,
             )])
             .unwrap();
+        let after_first_edge = buffer_tree.topology_generation();
+        assert!(after_first_edge > 0);
+
+        // New samples on an existing edge are not structural changes.
+        for secs in [1.0, 2.0, 3.0] {
+            buffer_tree
+                .update(&[TransformUpdate::new(
+                    "world",
+                    "base",
+                    sample(secs),
+                    TransformType::Dynamic,
+                )])
+                .unwrap();
+        }
+        assert_eq!(buffer_tree.topology_generation(), after_first_edge);
+
+        // Neither is a rejected edge, which leaves the graph as it was.
+        assert!(buffer_tree
+            .update(&[TransformUpdate::new(
+                "base",
+                "world",
+                sample(4.0),
+                TransformType::Dynamic
+            )])
+            .is_err());
+        assert_eq!(buffer_tree.topology_generation(), after_first_edge);
 
         buffer_tree
             .update(&[TransformUpdate::new(
-                "C",
-                "D",
-                StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
-                TransformType::Static,
+                "base",
+                "arm",
+                sample(4.0),
+                TransformType::Dynamic,
             )])
             .unwrap();
+        assert!(buffer_tree.topology_generation() > after_first_edge);
+    }
 
-        // Try to lookup transform between disconnected frames
-        let result = buffer_tree.lookup_latest_transform("A", "C");
-        assert!(result.is_err());
-        assert!(matches!(result, Err(TfError::CouldNotFindTransform(_))));
+    #[test]
+    fn test_retime_transform() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        // The robot drives along x at 1 m/s, the camera sits 0.5 m ahead of it.
+        let mut updates: Vec<TransformUpdate> = (0..=3)
+            .map(|i| {
+                let secs = i as f64;
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::from_secs([secs, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs),
+                    TransformType::Dynamic,
+                )
+            })
+            .collect();
+        updates.push(TransformUpdate::new(
+            "robot",
+            "camera",
+            StampedIsometry::from_secs([0.5, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0.0),
+            TransformType::Static,
+        ));
+        buffer_tree.update(&updates).unwrap();
 
-        // Try to lookup transform between non-existent frames
-        let result = buffer_tree.lookup_latest_transform("X", "Y");
-        assert!(result.is_err());
-        assert!(matches!(result, Err(TfError::CouldNotFindTransform(_))));
+        // Where the camera was at t1 = 1 s, seen from the robot at t2 = 2 s:
+        // 1.5 m in world, while the robot has moved on to 2 m.
+        let retimed = buffer_tree
+            .retime_transform("camera", 1_000_000_000, "robot", 2_000_000_000, "world")
+            .unwrap();
+        assert_relative_eq!(retimed.translation()[0], -0.5, epsilon = 1e-9);
+        assert_eq!(retimed.stamp(), 2_000_000_000);
+
+        // Without a time difference it is a plain lookup.
+        let same_time = buffer_tree
+            .retime_transform("camera", 2_000_000_000, "robot", 2_000_000_000, "world")
+            .unwrap();
+        let direct = buffer_tree
+            .lookup_transform("robot", "camera", 2_000_000_000)
+            .unwrap();
+        assert_relative_eq!(
+            same_time.translation()[0],
+            direct.translation()[0],
+            epsilon = 1e-9
+        );
     }
 
     #[test]
-    fn test_transform_history_buffer_window() {
-        let buffer_window = 1.0; // 1 second window
-        let mut history = TransformHistory::new(TransformType::Dynamic, buffer_window);
+    fn test_with_capacity_matches_new() {
+        let updates: Vec<TransformUpdate> = (1..200)
+            .map(|i| {
+                TransformUpdate::new(
+                    format!("link_{}", i - 1),
+                    format!("link_{}", i),
+                    StampedIsometry::new([0.1, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                    TransformType::Static,
+                )
+            })
+            .collect();
 
-        // Add transforms at different times (using nanoseconds)
-        let transforms = vec![
-            (0, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]),
-            (200_000_000, [0.2, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.2s
-            (400_000_000, [0.4, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.4s
-            (600_000_000, [0.6, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.6s
-            (800_000_000, [0.8, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 0.8s
-            (1_200_000_000i64, [1.2, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]), // 1.2s - causes older to be removed
-        ];
+        let mut presized = BufferTree::with_capacity(50);
+        let mut default = BufferTree::new();
+        presized.update(&updates).unwrap();
+        default.update(&updates).unwrap();
 
-        // Add all transforms
-        for (time, translation, rotation) in transforms {
-            let stamped_isometry = StampedIsometry::new(translation, rotation, time);
-            history.update(stamped_isometry);
-        }
+        assert_eq!(presized.visualize(), default.visualize());
+        let a = presized
+            .lookup_latest_transform("link_0", "link_199")
+            .unwrap();
+        let b = default
+            .lookup_latest_transform("link_0", "link_199")
+            .unwrap();
+        assert_relative_eq!(a.translation()[0], b.translation()[0], epsilon = 1e-9);
+        assert_relative_eq!(a.translation()[0], 19.9, epsilon = 1e-9);
+    }
 
-        // Check that oldest transforms (0.0s, 0.2s) were removed due to buffer window
-        assert!(history.history.len() < 6);
-        assert!(history.history.front().unwrap().stamp >= 200_000_000); // >= 0.2s
+    #[test]
+    fn test_with_edge_history() {
+        let mut buffer_tree = BufferTree::new();
+        let updates: Vec<TransformUpdate> = [0, 10, 30, 40]
+            .iter()
+            .map(|&ms| {
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], ms * 1_000_000),
+                    TransformType::Dynamic,
+                )
+            })
+            .collect();
+        buffer_tree.update(&updates).unwrap();
 
-        // Check that newest transforms are still present (1.2s)
-        assert_eq!(history.history.back().unwrap().stamp, 1_200_000_000);
+        let max_gap = buffer_tree
+            .with_edge_history("world", "robot", |samples| {
+                samples
+                    .iter()
+                    .zip(samples.iter().skip(1))
+                    .map(|(a, b)| b.stamp() - a.stamp())
+                    .max()
+            })
+            .unwrap();
+        assert_eq!(max_gap, Some(20_000_000));
 
-        // Verify interpolation still works within the valid time range (1.0s = 1_000_000_000 ns)
-        let result = history.interpolate_isometry_at_time(1_000_000_000);
-        assert!(result.is_ok());
-        let interpolated = result.unwrap();
-        assert_relative_eq!(interpolated.translation.vector[0], 1.0, epsilon = 1e-6);
+        assert!(matches!(
+            buffer_tree.with_edge_history("robot", "world", |samples| samples.len()),
+            Err(TfError::CouldNotFindTransform(_))
+        ));
+    }
+
+    #[test]
+    fn test_max_path_length_rejects_corrupted_graph() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig {
+            max_path_length: 16,
+            ..BufferConfig::default()
+        });
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "a",
+                    StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "a",
+                    "b",
+                    StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                    TransformType::Static,
+                ),
+            ])
+            .unwrap();
+        assert!(buffer_tree.lookup_latest_transform("world", "b").is_ok());
+
+        // Corrupt the graph: close the cycle b -> world and record the
+        // ancestors a walk around that cycle would produce.
+        let world = buffer_tree.index.get("world").unwrap();
+        let a = buffer_tree.index.get("a").unwrap();
+        let b = buffer_tree.index.get("b").unwrap();
+        let history = buffer_tree.graph.edge_weight(a, b).unwrap().clone();
+        buffer_tree.graph.add_edge(b, world, history);
+        buffer_tree.index.get_node_mut(b).unwrap().ancestor_ids =
+            [world, a, b].repeat(100)[..299].to_vec();
+
+        assert!(matches!(
+            buffer_tree.lookup_latest_transform("world", "b"),
+            Err(TfError::InvalidGraph(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_transform("world", "b", 0),
+            Err(TfError::InvalidGraph(_))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_delta() {
+        let mut buffer_tree = BufferTree::new();
+        // Facing +y and driving 3 m along it between 1 s and 3 s.
+        let yaw = std::f64::consts::FRAC_PI_4.sin();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "odom",
+                    "base_link",
+                    StampedIsometry::from_secs([1.0, 1.0, 0.0], [0.0, 0.0, yaw, yaw], 1.0),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "odom",
+                    "base_link",
+                    StampedIsometry::from_secs([1.0, 4.0, 0.0], [0.0, 0.0, yaw, yaw], 3.0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
+
+        let delta = buffer_tree
+            .lookup_delta("odom", "base_link", 1_000_000_000, 3_000_000_000)
+            .unwrap();
+        assert_eq!(delta.stamp(), 3_000_000_000);
+        // Straight ahead in the robot's own frame, no turning.
+        assert_relative_eq!(delta.translation()[0], 3.0, epsilon = 1e-9);
+        assert_relative_eq!(delta.translation()[1], 0.0, epsilon = 1e-9);
+        assert_relative_eq!(delta.isometry.rotation.angle(), 0.0, epsilon = 1e-9);
+
+        let halfway = buffer_tree
+            .lookup_delta("odom", "base_link", 1_000_000_000, 2_000_000_000)
+            .unwrap();
+        assert_relative_eq!(halfway.translation()[0], 1.5, epsilon = 1e-9);
+
+        let backwards = buffer_tree
+            .lookup_delta("odom", "base_link", 3_000_000_000, 1_000_000_000)
+            .unwrap();
+        assert_relative_eq!(backwards.isometry, delta.isometry.inverse(), epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_lookup_with_quality() {
+        let step = |stamp| StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp);
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "base", step(0), TransformType::Static),
+                TransformUpdate::new("base", "camera", step(0), TransformType::Static)
+                    .with_quality(0.9),
+                TransformUpdate::new("camera", "marker", step(0), TransformType::Dynamic)
+                    .with_quality(0.4),
+                TransformUpdate::new("base", "gripper", step(0), TransformType::Dynamic)
+                    .with_quality(0.7),
+            ])
+            .unwrap();
+
+        // The weakest edge wins; the unscored world -> base does not count.
+        let (transform, quality) = buffer_tree
+            .lookup_with_quality("world", "marker", 0)
+            .unwrap();
+        assert_eq!(quality, Some(0.4));
+        assert_eq!(transform.translation()[0], 3.0);
+        assert_eq!(
+            buffer_tree
+                .lookup_with_quality("gripper", "camera", 0)
+                .unwrap()
+                .1,
+            Some(0.7)
+        );
+        assert_eq!(
+            buffer_tree
+                .lookup_with_quality("world", "base", 0)
+                .unwrap()
+                .1,
+            None
+        );
+
+        // Scores are kept per sample: a lookup between two samples takes
+        // the lower one, and an unscored update leaves earlier scores alone.
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("camera", "marker", step(2), TransformType::Dynamic)
+                    .with_quality(0.95),
+                TransformUpdate::new("camera", "marker", step(4), TransformType::Dynamic),
+            ])
+            .unwrap();
+        let quality_at = |time| {
+            buffer_tree
+                .lookup_with_quality("camera", "marker", time)
+                .unwrap()
+                .1
+        };
+        assert_eq!(quality_at(0), Some(0.4));
+        assert_eq!(quality_at(1), Some(0.4));
+        assert_eq!(quality_at(2), Some(0.95));
+        assert_eq!(quality_at(3), Some(0.95));
+        assert_eq!(quality_at(4), None);
+        assert_eq!(
+            buffer_tree
+                .lookup_with_quality("world", "marker", 2)
+                .unwrap()
+                .1,
+            Some(0.9)
+        );
+    }
+
+    #[derive(Default)]
+    struct QualityObserver {
+        qualities: std::sync::Mutex<Vec<(i64, Option<f32>)>>,
+    }
+
+    impl BufferObserver for std::sync::Arc<QualityObserver> {
+        fn on_update(&self, updates: &[TransformUpdate]) {
+            self.qualities.lock().unwrap().extend(
+                updates
+                    .iter()
+                    .map(|update| (update.stamped_isometry.stamp, update.quality)),
+            );
+        }
+    }
 
-        // Verify interpolation fails for times outside the buffer window (0.1s = 100_000_000 ns)
-        let result = history.interpolate_isometry_at_time(100_000_000);
-        assert!(matches!(result, Err(TfError::AttemptedLookupInPast(_))));
+    #[test]
+    fn test_register_observer_replays_sample_quality() {
+        let step = |stamp| StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp);
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "robot", step(0), TransformType::Dynamic)
+                    .with_quality(0.5),
+                TransformUpdate::new("world", "robot", step(1), TransformType::Dynamic),
+                TransformUpdate::new("world", "robot", step(2), TransformType::Dynamic)
+                    .with_quality(0.8),
+            ])
+            .unwrap();
+
+        let observer = std::sync::Arc::new(QualityObserver::default());
+        buffer_tree.register_observer(Box::new(observer.clone()));
+        assert_eq!(
+            *observer.qualities.lock().unwrap(),
+            vec![(0, Some(0.5)), (1, None), (2, Some(0.8))]
+        );
     }
 
     #[test]
-    fn test_ancestor_updates() {
+    fn test_lookup_across_edge_without_samples() {
         let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[
+                TransformUpdate::new(
+                    "world",
+                    "base",
+                    StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                    TransformType::Static,
+                ),
+                TransformUpdate::new(
+                    "base",
+                    "tool",
+                    StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
 
-        let a = "A";
-        let b = "B";
-        let c = "C";
-        let r = "R";
+        let assert_no_samples = |buffer_tree: &BufferTree| {
+            for result in [
+                buffer_tree.lookup_latest_transform("world", "tool"),
+                buffer_tree.lookup_transform("world", "tool", 0),
+            ] {
+                match result {
+                    Err(TfError::CouldNotFindTransform(message)) => {
+                        assert!(message.to_lowercase().contains("no samples"), "{}", message)
+                    }
+                    other => panic!("Expected CouldNotFindTransform, got {:?}", other),
+                }
+            }
+        };
 
-        let tf = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+        assert!(buffer_tree.clear_edge_history("base", "tool"));
+        assert_no_samples(&buffer_tree);
 
-        // A -> B -> C
+        // Static edges never lose their sample through the API; empty one
+        // by hand to check lookups do not rely on that.
         buffer_tree
             .update(&[TransformUpdate::new(
-                a,
-                b,
-                tf.clone(),
-                TransformType::Static,
+                "base",
+                "tool",
+                StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Dynamic,
             )])
             .unwrap();
+        let (world, base) = (
+            buffer_tree.index.get("world").unwrap(),
+            buffer_tree.index.get("base").unwrap(),
+        );
         buffer_tree
-            .update(&[TransformUpdate::new(
-                b,
-                c,
-                tf.clone(),
-                TransformType::Static,
-            )])
+            .graph
+            .edge_weight_mut(world, base)
+            .unwrap()
+            .history
+            .clear();
+        assert_no_samples(&buffer_tree);
+    }
+
+    #[test]
+    fn test_unknown_frame_and_no_path_are_distinct() {
+        let mut buffer_tree = BufferTree::new();
+        let sample = StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "robot", sample.clone(), TransformType::Static),
+                TransformUpdate::new("map", "marker", sample, TransformType::Static),
+            ])
             .unwrap();
 
-        // Check ancestors
-        let get_ancestors = |buffer: &BufferTree, node: &str| -> Vec<String> {
-            let id = buffer.index.get(node).unwrap();
-            buffer.index.get_node(id).unwrap().ancestors.clone()
-        };
+        match buffer_tree.lookup_transform("world", "robto", 0) {
+            Err(TfError::UnknownFrame(message)) => assert!(message.contains("'robto'")),
+            other => panic!("Expected UnknownFrame, got {:?}", other),
+        }
+        match buffer_tree.lookup_transform("wrold", "robot", 0) {
+            Err(TfError::UnknownFrame(message)) => assert!(message.contains("'wrold'")),
+            other => panic!("Expected UnknownFrame, got {:?}", other),
+        }
+        match buffer_tree.lookup_transform("world", "marker", 0) {
+            Err(TfError::NoPath(message)) => {
+                assert!(message.contains("'world'") && message.contains("'marker'"))
+            }
+            other => panic!("Expected NoPath, got {:?}", other),
+        }
+    }
 
-        assert_eq!(get_ancestors(&buffer_tree, a), Vec::<String>::new());
-        assert_eq!(get_ancestors(&buffer_tree, b), vec![a.to_string()]);
-        assert_eq!(
-            get_ancestors(&buffer_tree, c),
-            vec![a.to_string(), b.to_string()]
-        );
+    #[test]
+    fn test_separate_trees_have_no_transform() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let sample = StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("a", "b", sample.clone(), TransformType::Static),
+                TransformUpdate::new("x", "y", sample, TransformType::Static),
+            ])
+            .unwrap();
 
-        // Add R -> A
+        assert!(matches!(
+            buffer_tree.lookup_transform("b", "y", 0),
+            Err(TfError::NoPath(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_latest_transform("a", "x"),
+            Err(TfError::NoPath(_))
+        ));
+        assert!(!buffer_tree.can_transform_latest("b", "y"));
+    }
+
+    #[test]
+    fn test_frame_metadata_round_trip() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        // Metadata may be set before the frame has any transforms.
+        buffer_tree.set_frame_metadata("camera", "sensor", "realsense d435");
         buffer_tree
             .update(&[TransformUpdate::new(
-                r,
-                a,
-                tf.clone(),
+                "world",
+                "camera",
+                StampedIsometry::new([0.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0], 0),
                 TransformType::Static,
             )])
             .unwrap();
+        buffer_tree.set_frame_metadata("camera", "fps", "30");
+        buffer_tree.set_frame_metadata("camera", "fps", "60");
 
-        // Check updates
-        assert_eq!(get_ancestors(&buffer_tree, a), vec![r.to_string()]);
-        assert_eq!(
-            get_ancestors(&buffer_tree, b),
-            vec![r.to_string(), a.to_string()]
-        );
         assert_eq!(
-            get_ancestors(&buffer_tree, c),
-            vec![r.to_string(), a.to_string(), b.to_string()]
+            buffer_tree.get_frame_metadata("camera", "sensor"),
+            Some("realsense d435")
         );
+        assert_eq!(buffer_tree.get_frame_metadata("camera", "fps"), Some("60"));
+        assert_eq!(buffer_tree.get_frame_metadata("camera", "serial"), None);
+        assert_eq!(buffer_tree.get_frame_metadata("world", "fps"), None);
+        assert_eq!(buffer_tree.get_frame_metadata("ghost", "fps"), None);
+
+        // Metadata on a frame without transforms does not make it known.
+        buffer_tree.set_frame_metadata("typo", "fps", "30");
+        assert!(!buffer_tree.has_frame("typo"));
+        assert!(matches!(
+            buffer_tree.lookup_transform("world", "typo", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_transform("typo", "world", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
+
+        let camera = buffer_tree.index.get("camera").unwrap();
+        let world = buffer_tree.index.get("world").unwrap();
+        let dot = buffer_tree.visualize();
+        assert!(dot.contains(&format!(
+            "{} [label=\"camera\", tooltip=\"fps=60\\nsensor=realsense d435\"]",
+            camera
+        )));
+        assert!(dot.contains(&format!("{} [label=\"world\"]", world)));
+
+        let subtree = buffer_tree.subtree("camera").unwrap();
+        assert_eq!(subtree.get_frame_metadata("camera", "fps"), Some("60"));
+
+        buffer_tree.clear();
+        assert_eq!(buffer_tree.get_frame_metadata("camera", "fps"), None);
     }
 
-    /// Counts each `on_update` call and the size of every batch it sees.
-    #[derive(Default)]
-    struct CountingObserver {
-        calls: Mutex<Vec<usize>>,
+    #[test]
+    fn test_lookup_transform_or_latest_falls_back_for_future_queries() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        for (x, stamp) in [(0.0, 0), (1.0, 1_000_000_000)] {
+            buffer_tree
+                .update(&[TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::new([x, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp),
+                    TransformType::Dynamic,
+                )])
+                .unwrap();
+        }
+
+        let (transform, fell_back) = buffer_tree
+            .lookup_transform_or_latest("world", "robot", 500_000_000)
+            .unwrap();
+        assert!(!fell_back);
+        assert_eq!(transform.stamp, 500_000_000);
+        assert_relative_eq!(transform.translation()[0], 0.5, epsilon = 1e-9);
+
+        // 5 ms past the newest sample: lookup_transform refuses, the hybrid
+        // returns the latest sample instead.
+        assert!(matches!(
+            buffer_tree.lookup_transform("world", "robot", 1_005_000_000),
+            Err(TfError::AttemptedLookUpInFuture(_))
+        ));
+        let (transform, fell_back) = buffer_tree
+            .lookup_transform_or_latest("world", "robot", 1_005_000_000)
+            .unwrap();
+        assert!(fell_back);
+        assert_eq!(transform.stamp, 1_000_000_000);
+        assert_relative_eq!(transform.translation()[0], 1.0, epsilon = 1e-9);
+
+        // Only future queries fall back.
+        assert!(matches!(
+            buffer_tree.lookup_transform_or_latest("world", "robot", -1),
+            Err(TfError::AttemptedLookupInPast(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_transform_or_latest("world", "ghost", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
     }
 
-    impl BufferObserver for std::sync::Arc<CountingObserver> {
-        fn on_update(&self, updates: &[TransformUpdate]) {
-            self.calls.lock().unwrap().push(updates.len());
+    #[test]
+    fn test_from_transforms_builds_robot_arm() {
+        let identity = [0.0, 0.0, 0.0, 1.0];
+        let arm = [
+            (
+                "world",
+                "base_link",
+                [0.0, 0.0, 0.1],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+            (
+                "base_link",
+                "shoulder",
+                [0.0, 0.0, 0.3],
+                identity,
+                0,
+                TransformType::Dynamic,
+            ),
+            (
+                "shoulder",
+                "elbow",
+                [0.4, 0.0, 0.0],
+                identity,
+                0,
+                TransformType::Dynamic,
+            ),
+            (
+                "elbow",
+                "wrist",
+                [0.3, 0.0, 0.0],
+                identity,
+                0,
+                TransformType::Dynamic,
+            ),
+            (
+                "wrist",
+                "tool",
+                [0.0, 0.0, -0.1],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+        ];
+
+        let buffer_tree = BufferTree::from_transforms(arm).unwrap();
+        let tool = buffer_tree.lookup_transform("world", "tool", 0).unwrap();
+        assert_relative_eq!(tool.translation()[0], 0.7, epsilon = 1e-9);
+        assert_relative_eq!(tool.translation()[2], 0.3, epsilon = 1e-9);
+
+        // A second parent for `elbow` is rejected, naming the item.
+        let err = BufferTree::from_transforms([
+            (
+                "world",
+                "elbow",
+                [0.0; 3],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+            (
+                "base_link",
+                "elbow",
+                [0.0; 3],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+        ])
+        .err()
+        .unwrap();
+        match err {
+            TfError::InvalidGraph(message) => {
+                assert!(message.starts_with("Transform 1 ('base_link' -> 'elbow')"))
+            }
+            other => panic!("Expected InvalidGraph, got {:?}", other),
         }
     }
 
     #[test]
-    fn test_update_batch_many_edges_one_stamp() {
-        // A star graph: root -> N children, all at the same stamp, in one update call.
-        let mut buffer_tree = BufferTree::new();
-        let stamp_ns = 1_000_000_000_i64;
+    fn test_lookup_chain_str() {
+        let identity = [0.0, 0.0, 0.0, 1.0];
+        let buffer_tree = BufferTree::from_transforms([
+            (
+                "base",
+                "shoulder",
+                [0.0, 0.0, 0.5],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+            (
+                "shoulder",
+                "elbow",
+                [0.4, 0.0, 0.0],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+            (
+                "base",
+                "camera",
+                [0.0, 0.1, 0.0],
+                identity,
+                0,
+                TransformType::Static,
+            ),
+        ])
+        .unwrap();
 
-        let updates: Vec<TransformUpdate> = (0..5)
-            .map(|i| {
+        let elbow = buffer_tree
+            .lookup_chain_str("base/shoulder/elbow", 0)
+            .unwrap();
+        assert_relative_eq!(elbow.translation()[0], 0.4, epsilon = 1e-9);
+        assert_relative_eq!(elbow.translation()[2], 0.5, epsilon = 1e-9);
+        // Edges may be walked against their direction.
+        let back = buffer_tree
+            .lookup_chain_str("elbow/shoulder/base/camera", 0)
+            .unwrap();
+        assert_relative_eq!(back.translation()[0], -0.4, epsilon = 1e-9);
+        assert_relative_eq!(back.translation()[1], 0.1, epsilon = 1e-9);
+        assert_relative_eq!(back.translation()[2], -0.5, epsilon = 1e-9);
+        assert_eq!(
+            buffer_tree
+                .lookup_chain_str("base", 0)
+                .unwrap()
+                .translation(),
+            [0.0, 0.0, 0.0]
+        );
+
+        // `base` and `elbow` are connected, but not by a direct edge.
+        match buffer_tree.lookup_chain_str("base/elbow", 0) {
+            Err(TfError::CouldNotFindTransform(message)) => {
+                assert!(message.contains("'base' and 'elbow'"))
+            }
+            other => panic!("Expected CouldNotFindTransform, got {:?}", other),
+        }
+        assert!(matches!(
+            buffer_tree.lookup_chain_str("base/shoulder/wrist", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_can_transform() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        for (x, stamp) in [(0.0, 0), (1.0, 1_000_000_000)] {
+            buffer_tree
+                .update(&[TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::new([x, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp),
+                    TransformType::Dynamic,
+                )])
+                .unwrap();
+        }
+        buffer_tree
+            .update(&[
                 TransformUpdate::new(
-                    "root",
-                    format!("child_{}", i),
-                    StampedIsometry::new([i as f64, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], stamp_ns),
+                    "robot",
+                    "camera",
+                    StampedIsometry::new([0.0, 0.0, 0.2], [0.0, 0.0, 0.0, 1.0], 0),
                     TransformType::Static,
-                )
-            })
-            .collect();
+                ),
+                TransformUpdate::new(
+                    "dock",
+                    "charger",
+                    StampedIsometry::new([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+                    TransformType::Static,
+                ),
+            ])
+            .unwrap();
 
-        buffer_tree.update(&updates).unwrap();
+        assert!(buffer_tree.can_transform("world", "camera", 500_000_000));
+        assert!(buffer_tree.can_transform("camera", "world", 1_000_000_000));
+        assert!(buffer_tree.can_transform("robot", "robot", 0));
+        // Outside the history of world -> robot.
+        assert!(!buffer_tree.can_transform("world", "camera", -1));
+        assert!(!buffer_tree.can_transform("world", "camera", 2_000_000_000));
+        // Different trees, unknown frames.
+        assert!(!buffer_tree.can_transform("world", "charger", 0));
+        assert!(!buffer_tree.can_transform("world", "ghost", 0));
+        assert!(!buffer_tree.can_transform("ghost", "ghost", 0));
+
+        // The answer agrees with lookup_transform.
+        for time in [-1, 0, 500_000_000, 2_000_000_000] {
+            assert_eq!(
+                buffer_tree.can_transform("world", "camera", time),
+                buffer_tree
+                    .lookup_transform("world", "camera", time)
+                    .is_ok()
+            );
+        }
 
-        for i in 0..5 {
-            let r = buffer_tree
-                .lookup_latest_transform("root", &format!("child_{}", i))
-                .unwrap();
-            assert_eq!(r.translation()[0], i as f64);
+        assert!(buffer_tree.can_transform_latest("world", "camera"));
+        assert!(!buffer_tree.can_transform_latest("world", "charger"));
+        assert!(!buffer_tree.can_transform_latest("world", "ghost"));
+    }
+
+    /// Records every `on_conflict` call as `(from, to, existing_x, new_x)`.
+    #[derive(Default)]
+    struct ConflictObserver {
+        conflicts: Mutex<Vec<(String, String, f64, f64)>>,
+    }
+
+    impl BufferObserver for std::sync::Arc<ConflictObserver> {
+        fn on_update(&self, _updates: &[TransformUpdate]) {}
+
+        fn on_conflict(
+            &self,
+            from: &str,
+            to: &str,
+            existing: &StampedIsometry,
+            new: &StampedIsometry,
+        ) {
+            self.conflicts.lock().unwrap().push((
+                from.to_string(),
+                to.to_string(),
+                existing.translation()[0],
+                new.translation()[0],
+            ));
         }
     }
 
     #[test]
-    fn test_update_batch_fail_fast_on_cycle() {
-        // A->B succeeds, then B->A in the same batch creates a cycle and the
-        // call returns Err. A->B remains applied (fail-fast, not transactional).
-        let mut buffer_tree = BufferTree::new();
-        let iso = StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0);
+    fn test_conflicting_samples_at_same_stamp() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let observer = std::sync::Arc::new(ConflictObserver::default());
+        buffer_tree.register_observer(Box::new(observer.clone()));
 
-        let result = buffer_tree.update(&[
-            TransformUpdate::new("A", "B", iso.clone(), TransformType::Static),
-            TransformUpdate::new("B", "A", iso.clone(), TransformType::Static),
-        ]);
+        let sample = |x: f64, secs: f64| {
+            TransformUpdate::new(
+                "world",
+                "robot",
+                StampedIsometry::from_secs([x, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs),
+                TransformType::Dynamic,
+            )
+        };
 
-        assert!(matches!(result, Err(TfError::InvalidGraph(_))));
-        // First edge stuck around.
-        assert!(buffer_tree.lookup_latest_transform("A", "B").is_ok());
+        buffer_tree.update(&[sample(1.0, 1.0)]).unwrap();
+        // Same stamp and pose (a duplicate delivery) and a newer stamp are
+        // not conflicts.
+        buffer_tree.update(&[sample(1.0, 1.0)]).unwrap();
+        buffer_tree.update(&[sample(2.0, 2.0)]).unwrap();
+        assert!(observer.conflicts.lock().unwrap().is_empty());
+
+        buffer_tree.update(&[sample(3.0, 2.0)]).unwrap();
+        assert_eq!(
+            *observer.conflicts.lock().unwrap(),
+            vec![("world".to_string(), "robot".to_string(), 2.0, 3.0)]
+        );
     }
 
     #[test]
-    fn test_update_batch_observer_called_once() {
-        let mut buffer_tree = BufferTree::new();
-        let observer = std::sync::Arc::new(CountingObserver::default());
-        buffer_tree.register_observer(Box::new(observer.clone()));
+    fn test_frame_names() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        assert!(buffer_tree.frame_names().is_empty());
 
-        let updates: Vec<TransformUpdate> = (0..5)
-            .map(|i| {
+        let identity = || StampedIsometry::new([0.0; 3], [0.0, 0.0, 0.0, 1.0], 0);
+        buffer_tree
+            .update(&[
+                TransformUpdate::new("world", "robot", identity(), TransformType::Static),
+                TransformUpdate::new("robot", "camera", identity(), TransformType::Static),
+            ])
+            .unwrap();
+        // A rejected second parent leaves no trace.
+        assert!(buffer_tree
+            .update(&[TransformUpdate::new(
+                "ghost",
+                "camera",
+                identity(),
+                TransformType::Static
+            )])
+            .is_err());
+
+        assert_eq!(buffer_tree.frame_names(), vec!["camera", "robot", "world"]);
+    }
+
+    #[test]
+    fn test_lookup_velocity() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let yaw = |angle: f64| [0.0, 0.0, (angle / 2.0).sin(), (angle / 2.0).cos()];
+        buffer_tree
+            .update(&[
                 TransformUpdate::new(
-                    "root",
-                    format!("child_{}", i),
-                    StampedIsometry::from_secs([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 1.0),
-                    TransformType::Static,
-                )
-            })
-            .collect();
+                    "world",
+                    "robot",
+                    StampedIsometry::new([0.0, 0.0, 0.0], yaw(0.1), 1_000_000_000),
+                    TransformType::Dynamic,
+                ),
+                TransformUpdate::new(
+                    "world",
+                    "robot",
+                    StampedIsometry::new([1.0, 0.5, 0.0], yaw(0.5), 3_000_000_000),
+                    TransformType::Dynamic,
+                ),
+            ])
+            .unwrap();
 
-        buffer_tree.update(&updates).unwrap();
+        let velocity = buffer_tree
+            .lookup_velocity("world", "robot", 1_000_000_000, 3_000_000_000)
+            .unwrap();
+        let expected = [0.5, 0.25, 0.0, 0.0, 0.0, 0.2];
+        assert!((0..6).all(|i| (velocity[i] - expected[i]).abs() < 1e-9));
+
+        assert!(matches!(
+            buffer_tree.lookup_velocity("world", "robot", 2_000_000_000, 2_000_000_000),
+            Err(TfError::CouldNotFindTransform(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_velocity("world", "robot", 0, 2_000_000_000),
+            Err(TfError::AttemptedLookupInPast(_))
+        ));
+        assert!(matches!(
+            buffer_tree.lookup_velocity("world", "robot", 2_000_000_000, 4_000_000_000),
+            Err(TfError::AttemptedLookUpInFuture(_))
+        ));
+    }
 
-        let calls = observer.calls.lock().unwrap();
-        assert_eq!(calls.len(), 1, "observer should be invoked exactly once");
-        assert_eq!(calls[0], 5, "observer should see the full 5-element batch");
+    #[test]
+    fn test_origins_aabb() {
+        let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+        let edge = |from: &str, to: &str, translation: [f64; 3]| {
+            TransformUpdate::new(
+                from,
+                to,
+                StampedIsometry::new(translation, [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            )
+        };
+        buffer_tree
+            .update(&[
+                edge("world", "a", [1.0, 2.0, 0.5]),
+                edge("world", "b", [-3.0, 0.5, 1.0]),
+                edge("a", "c", [0.0, 0.0, 4.5]),
+            ])
+            .unwrap();
+
+        let (min, max) = buffer_tree.origins_aabb("world", 0).unwrap();
+        assert_eq!(min, [-3.0, 0.0, 0.0]);
+        assert_eq!(max, [1.0, 2.0, 5.0]);
+
+        // Relative to a leaf the box shifts with it.
+        let (min, max) = buffer_tree.origins_aabb("c", 0).unwrap();
+        assert_eq!(min, [-4.0, -2.0, -5.0]);
+        assert_eq!(max, [0.0, 0.0, 0.0]);
+
+        assert!(matches!(
+            buffer_tree.origins_aabb("ghost", 0),
+            Err(TfError::UnknownFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_tf_yaml_quotes_names_and_writes_yaml_floats() {
+        let odd_name = "cam: \"left\"\u{1b}\u{e9}";
+        let mut buffer_tree = BufferTree::new();
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                "world",
+                odd_name,
+                StampedIsometry::new([1e-7, 1e20, f64::NAN], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            )])
+            .unwrap();
+
+        let yaml = buffer_tree.to_tf_yaml(0);
+        // YAML 1.1 loaders only read floats with a decimal point.
+        assert!(yaml.contains("x: 1.0e-7"), "{}", yaml);
+        assert!(yaml.contains("y: 1.0e+20"), "{}", yaml);
+        assert!(yaml.contains("z: .nan"), "{}", yaml);
+        assert!(!yaml.contains("\\u{"), "{}", yaml);
+
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed[0]["child_frame_id"].as_str(), Some(odd_name));
+        assert_eq!(parsed[0]["header"]["frame_id"].as_str(), Some("world"));
+        assert_eq!(
+            parsed[0]["transform"]["translation"]["x"].as_f64(),
+            Some(1e-7)
+        );
     }
 }
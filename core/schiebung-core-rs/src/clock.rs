@@ -0,0 +1,87 @@
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of the current time for time-dependent buffer behavior.
+///
+/// [`BufferTree`](crate::BufferTree) reads "now" through this trait instead
+/// of the system clock directly, so simulations and tests can inject their
+/// own notion of time via
+/// [`BufferTree::set_clock`](crate::BufferTree::set_clock).
+pub trait Clock: Send + Sync {
+    /// Current time in seconds since the Unix epoch.
+    fn now_secs(&self) -> f64;
+}
+
+/// [`Clock`] backed by the system clock. This is the default.
+///
+/// The wall clock is read once at construction and advanced by the
+/// monotonic clock from then on, so NTP corrections or manual changes to the
+/// system time never make it go backward.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start_secs: f64,
+    start: Instant,
+}
+
+impl SystemClock {
+    /// Create a clock reading the current wall-clock time.
+    pub fn new() -> Self {
+        SystemClock {
+            start_secs: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or(0.0),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> f64 {
+        self.start_secs + self.start.elapsed().as_secs_f64()
+    }
+}
+
+/// [`Clock`] that only moves when told to, for deterministic tests and
+/// simulations.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_secs: Mutex<f64>,
+}
+
+impl ManualClock {
+    /// Create a clock reading `start_secs`.
+    pub fn new(start_secs: f64) -> Self {
+        ManualClock {
+            now_secs: Mutex::new(start_secs),
+        }
+    }
+
+    /// Jump to `secs`.
+    pub fn set(&self, secs: f64) {
+        *self.lock() = secs;
+    }
+
+    /// Move the clock forward by `secs`.
+    pub fn advance(&self, secs: f64) {
+        *self.lock() += secs;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, f64> {
+        self.now_secs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_secs(&self) -> f64 {
+        *self.lock()
+    }
+}
@@ -1,6 +1,8 @@
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
 
+use crate::types::InterpolationMode;
+
 /// Runtime configuration for [`BufferTree`](crate::BufferTree).
 ///
 /// Loaded from disk via [`get_config`] using [`confy`], with sensible
@@ -15,6 +17,35 @@ pub struct BufferConfig {
     /// Filesystem directory where buffer visualizations and other artifacts
     /// are written. Defaults to the user's home directory.
     pub save_path: String,
+    /// Minimum time (in seconds) between two visualization saves. Calls to
+    /// [`BufferTree::save_visualization`](crate::BufferTree::save_visualization)
+    /// arriving faster than this are coalesced into the previous save so a
+    /// tight loop cannot keep shelling out to Graphviz. `0.0` disables the
+    /// limit.
+    pub min_visualization_interval: f64,
+    /// Accept an update `B -> A` when the edge `A -> B` already exists and
+    /// the update is (approximately) its inverse, instead of rejecting it as
+    /// a cycle. Matching samples are dropped as redundant; samples at new
+    /// stamps are inverted and stored on `A -> B`. Updates that contradict
+    /// the existing edge are still rejected.
+    pub accept_inverse_edges: bool,
+    /// Largest gap (in seconds) between two samples of a dynamic edge that
+    /// lookups will interpolate across. A lookup falling into a wider gap
+    /// fails with [`TfError::ExtrapolationGap`](crate::TfError::ExtrapolationGap)
+    /// instead of returning a pose blended across the dropout. `None`
+    /// interpolates across any gap.
+    pub max_interpolation_gap: Option<f64>,
+    /// Number of samples to pre-allocate for each new dynamic edge. Raising
+    /// it avoids reallocations while a high-rate edge fills its buffer
+    /// window; keep it small when most edges are low-rate.
+    pub initial_history_capacity: usize,
+    /// Longest path, in edges, that a lookup will compose. A well-formed
+    /// tree never comes close; a longer path means the graph is corrupted
+    /// and the lookup fails with
+    /// [`TfError::InvalidGraph`](crate::TfError::InvalidGraph).
+    pub max_path_length: usize,
+    /// How lookups interpolate between two samples of a dynamic edge.
+    pub interpolation: InterpolationMode,
 }
 
 impl Default for BufferConfig {
@@ -22,6 +53,12 @@ impl Default for BufferConfig {
         BufferConfig {
             buffer_window: 120.0,
             save_path: home_dir().unwrap().display().to_string(),
+            min_visualization_interval: 1.0,
+            accept_inverse_edges: false,
+            max_interpolation_gap: None,
+            initial_history_capacity: 8,
+            max_path_length: 1024,
+            interpolation: InterpolationMode::Linear,
         }
     }
 }
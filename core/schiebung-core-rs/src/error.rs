@@ -3,51 +3,131 @@
 /// Each variant carries a human-readable message describing the offending
 /// frames or graph state. The wrapped [`String`] is meant for logs and error
 /// reporting — match on the variant for programmatic handling.
-#[derive(Clone, Debug)]
+///
+/// The [`Display`](std::fmt::Display) output is `TfError.<Variant>: <message>`.
+#[derive(Clone, Debug, thiserror::Error)]
 pub enum TfError {
     /// The requested timestamp is older than the oldest sample retained in
-    /// the per-edge history. Increase [`BufferConfig::max_history_size`](crate::BufferConfig)
+    /// the per-edge history. Increase [`BufferConfig::buffer_window`](crate::BufferConfig::buffer_window)
     /// or query a more recent stamp.
+    #[error("TfError.AttemptedLookupInPast: {0}")]
     AttemptedLookupInPast(String),
     /// The requested timestamp is newer than the newest sample on this edge.
     /// The transform has not been published yet.
+    #[error("TfError.AttemptedLookUpInFuture: {0}")]
     AttemptedLookUpInFuture(String),
-    /// No connecting path exists between `from` and `to` in the current graph.
-    /// The frames may not be linked yet, or one of them is unknown.
+    /// A transform could not be computed for another reason, e.g. an edge
+    /// on the path holds no samples.
+    #[error("TfError.CouldNotFindTransform: {0}")]
     CouldNotFindTransform(String),
+    /// A requested frame has never been added to the buffer. Often a typo
+    /// in the frame name.
+    #[error("TfError.UnknownFrame: {0}")]
+    UnknownFrame(String),
+    /// Both frames are known, but they belong to different trees, so no
+    /// path connects them.
+    #[error("TfError.NoPath: {0}")]
+    NoPath(String),
     /// Inserting the requested edge would create a cycle, or the child frame
     /// already has a different parent. The graph must remain a forest.
+    #[error("TfError.InvalidGraph: {0}")]
     InvalidGraph(String),
+    /// The requested timestamp falls between two samples that are further
+    /// apart than [`BufferConfig::max_interpolation_gap`](crate::BufferConfig::max_interpolation_gap),
+    /// so interpolating across them would not be meaningful.
+    #[error("TfError.ExtrapolationGap: {0}")]
+    ExtrapolationGap(String),
+    /// A transform received from outside is malformed, e.g. its rotation
+    /// quaternion is not normalized.
+    #[error("TfError.InvalidTransform: {0}")]
+    InvalidTransform(String),
+    /// The edge was locked with [`BufferTree::set_static`](crate::BufferTree::set_static)
+    /// and rejects further updates.
+    #[error("TfError.LockedTransform: {0}")]
+    LockedTransform(String),
     /// Failed to load or parse a model file (URDF, USD, etc.) into the buffer.
+    #[error("TfError.LoaderError: {0}")]
     LoaderError(String),
 }
 
 impl TfError {
-    /// Render the error as a `TfError.<Variant>: <message>` string suitable
-    /// for log output. Same content as the [`Display`](std::fmt::Display)
-    /// impl; provided as a method for callers that want it without going
-    /// through formatting machinery.
-    pub fn to_string(&self) -> String {
+    /// The same variant with `context` prepended to its message.
+    pub(crate) fn with_context(self, context: impl std::fmt::Display) -> Self {
+        let wrap = |message: String| format!("{}: {}", context, message);
         match self {
-            TfError::AttemptedLookupInPast(msg) => {
-                format!("TfError.AttemptedLookupInPast: {}", msg)
-            }
-            TfError::AttemptedLookUpInFuture(msg) => {
-                format!("TfError.AttemptedLookUpInFuture: {}", msg)
-            }
-            TfError::CouldNotFindTransform(msg) => {
-                format!("TfError.CouldNotFindTransform: {}", msg)
-            }
-            TfError::InvalidGraph(msg) => format!("TfError.InvalidGraph: {}", msg),
-            TfError::LoaderError(msg) => format!("TfError.LoaderError: {}", msg),
+            TfError::AttemptedLookupInPast(m) => TfError::AttemptedLookupInPast(wrap(m)),
+            TfError::AttemptedLookUpInFuture(m) => TfError::AttemptedLookUpInFuture(wrap(m)),
+            TfError::CouldNotFindTransform(m) => TfError::CouldNotFindTransform(wrap(m)),
+            TfError::UnknownFrame(m) => TfError::UnknownFrame(wrap(m)),
+            TfError::NoPath(m) => TfError::NoPath(wrap(m)),
+            TfError::InvalidGraph(m) => TfError::InvalidGraph(wrap(m)),
+            TfError::ExtrapolationGap(m) => TfError::ExtrapolationGap(wrap(m)),
+            TfError::InvalidTransform(m) => TfError::InvalidTransform(wrap(m)),
+            TfError::LockedTransform(m) => TfError::LockedTransform(wrap(m)),
+            TfError::LoaderError(m) => TfError::LoaderError(wrap(m)),
         }
     }
 }
 
-impl std::fmt::Display for TfError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_is_human_readable() {
+        let cases = [
+            (
+                TfError::AttemptedLookupInPast("too old".to_string()),
+                "TfError.AttemptedLookupInPast: too old",
+            ),
+            (
+                TfError::AttemptedLookUpInFuture("too new".to_string()),
+                "TfError.AttemptedLookUpInFuture: too new",
+            ),
+            (
+                TfError::CouldNotFindTransform("no samples".to_string()),
+                "TfError.CouldNotFindTransform: no samples",
+            ),
+            (
+                TfError::UnknownFrame("typo".to_string()),
+                "TfError.UnknownFrame: typo",
+            ),
+            (
+                TfError::NoPath("two trees".to_string()),
+                "TfError.NoPath: two trees",
+            ),
+            (
+                TfError::InvalidGraph("cycle".to_string()),
+                "TfError.InvalidGraph: cycle",
+            ),
+            (
+                TfError::ExtrapolationGap("dropout".to_string()),
+                "TfError.ExtrapolationGap: dropout",
+            ),
+            (
+                TfError::InvalidTransform("bad quaternion".to_string()),
+                "TfError.InvalidTransform: bad quaternion",
+            ),
+            (
+                TfError::LockedTransform("calibration".to_string()),
+                "TfError.LockedTransform: calibration",
+            ),
+            (
+                TfError::LoaderError("bad file".to_string()),
+                "TfError.LoaderError: bad file",
+            ),
+        ];
+
+        for (err, expected) in cases {
+            assert_eq!(format!("{}", err), expected);
+            assert_eq!(err.to_string(), expected);
+        }
     }
-}
 
-impl std::error::Error for TfError {}
+    #[test]
+    fn test_boxes_into_std_error() {
+        let boxed: Box<dyn std::error::Error> =
+            Box::new(TfError::InvalidGraph("cycle".to_string()));
+        assert_eq!(boxed.to_string(), "TfError.InvalidGraph: cycle");
+    }
+}
@@ -1,19 +1,34 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 
-/// Transform graph storage and lookup ([`BufferTree`], [`BufferObserver`]).
+/// Transform graph storage and lookup ([`BufferTree`], [`BufferObserver`], [`EdgeView`]).
 pub mod buffer;
+/// Injectable time source ([`Clock`], [`SystemClock`], [`ManualClock`]).
+pub mod clock;
 /// Runtime configuration and config-file loading ([`BufferConfig`], [`get_config`]).
 pub mod config;
 /// Error type returned by buffer operations ([`TfError`]).
 pub mod error;
+/// [`BufferTree`] behind a tokio lock for async servers ([`SharedBufferTree`]).
+#[cfg(feature = "tokio")]
+pub mod shared;
+/// Deterministic test-data generators ([`test_util::random_chain`]).
+#[cfg(feature = "test-util")]
+pub mod test_util;
 /// Core value types: [`StampedIsometry`], [`TransformType`], [`TransformUpdate`].
 pub mod types;
 /// Loaders that ingest external model files into a [`BufferTree`] ([`UrdfLoader`]).
 pub mod utils;
 
-pub use buffer::{BufferObserver, BufferTree};
+pub use buffer::{BufferObserver, BufferTree, EdgeView};
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use config::{get_config, BufferConfig};
 pub use error::TfError;
-pub use types::{StampedIsometry, TransformType, TransformUpdate};
+#[cfg(feature = "tokio")]
+pub use shared::SharedBufferTree;
+pub use types::{
+    interpolate, interpolate_with_mode, nanos_to_secs, secs_to_nanos, InterpolationMode,
+    MatrixLayout, ParseTransformTypeError, Quaternion, StampedIsometry, TransformType,
+    TransformUpdate, Translation,
+};
 pub use utils::{FormatLoader, UrdfLoader};
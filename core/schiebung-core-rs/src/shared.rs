@@ -0,0 +1,297 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::buffer::BufferTree;
+use crate::error::TfError;
+use crate::types::StampedIsometry;
+
+/// How often [`SharedBufferTree::wait_for_frames`] checks the buffer.
+const FRAME_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A [`BufferTree`] shared between async tasks behind a tokio [`RwLock`].
+///
+/// Waiting for the lock yields to the runtime instead of blocking a worker
+/// thread, so a server can hold it across expensive lookups without stalling
+/// unrelated tasks. tokio's lock is fair: once a writer is waiting, new
+/// readers queue behind it, so a steady stream of queries cannot starve
+/// ingestion.
+///
+/// Cloning is cheap and yields another handle to the same buffer.
+#[derive(Clone)]
+pub struct SharedBufferTree {
+    inner: Arc<RwLock<BufferTree>>,
+}
+
+impl SharedBufferTree {
+    /// Wrap `buffer` for shared async access.
+    pub fn new(buffer: BufferTree) -> Self {
+        SharedBufferTree {
+            inner: Arc::new(RwLock::new(buffer)),
+        }
+    }
+
+    /// Wait for shared read access.
+    pub async fn read(&self) -> RwLockReadGuard<'_, BufferTree> {
+        self.inner.read().await
+    }
+
+    /// Wait for exclusive write access.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, BufferTree> {
+        self.inner.write().await
+    }
+
+    /// [`BufferTree::lookup_snapshot`] under a single read lock, so every
+    /// pair is looked up in the same state of the tree even while other
+    /// tasks write to it.
+    pub async fn lookup_snapshot(
+        &self,
+        pairs: &[(&str, &str)],
+        time: i64,
+    ) -> Result<Vec<StampedIsometry>, TfError> {
+        self.read().await.lookup_snapshot(pairs, time)
+    }
+
+    /// Wait until every frame in `frames` is in the buffer, checking under
+    /// the read lock every few milliseconds.
+    ///
+    /// The usual "wait until the robot is publishing" step before the first
+    /// lookup. Fails with [`TfError::UnknownFrame`], naming the frames still
+    /// missing, if they have not all appeared after `timeout`.
+    pub async fn wait_for_frames(&self, frames: &[&str], timeout: Duration) -> Result<(), TfError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let missing: Vec<&str> = {
+                let buffer = self.read().await;
+                frames
+                    .iter()
+                    .copied()
+                    .filter(|frame| !buffer.has_frame(frame))
+                    .collect()
+            };
+            if missing.is_empty() {
+                return Ok(());
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Err(TfError::UnknownFrame(format!(
+                    "Frames did not appear within {:?}: {}",
+                    timeout,
+                    missing.join(", ")
+                )));
+            }
+            tokio::time::sleep(FRAME_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Read access from synchronous code, blocking the current thread.
+    ///
+    /// # Panics
+    ///
+    /// When called from within an async execution context; use
+    /// [`read`](SharedBufferTree::read) there.
+    pub fn blocking_read(&self) -> RwLockReadGuard<'_, BufferTree> {
+        self.inner.blocking_read()
+    }
+
+    /// Write access from synchronous code, blocking the current thread.
+    ///
+    /// # Panics
+    ///
+    /// When called from within an async execution context; use
+    /// [`write`](SharedBufferTree::write) there.
+    pub fn blocking_write(&self) -> RwLockWriteGuard<'_, BufferTree> {
+        self.inner.blocking_write()
+    }
+}
+
+impl From<BufferTree> for SharedBufferTree {
+    fn from(buffer: BufferTree) -> Self {
+        Self::new(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BufferConfig, StampedIsometry, TransformType, TransformUpdate};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    fn sample(secs: f64) -> StampedIsometry {
+        StampedIsometry::from_secs([secs, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], secs)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_queries_do_not_starve_ingestion() {
+        let mut buffer = BufferTree::with_config(BufferConfig {
+            buffer_window: 1000.0,
+            ..BufferConfig::default()
+        });
+        buffer
+            .update(&[
+                TransformUpdate::new("world", "robot", sample(0.0), TransformType::Dynamic),
+                TransformUpdate::new("world", "robot", sample(1.0), TransformType::Dynamic),
+            ])
+            .unwrap();
+        let shared = SharedBufferTree::new(buffer);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // More readers than worker threads, each re-acquiring the lock as
+        // soon as it is released.
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let shared = shared.clone();
+                let stop = stop.clone();
+                tokio::spawn(async move {
+                    let mut lookups = 0u64;
+                    while !stop.load(Ordering::Relaxed) {
+                        let buffer = shared.read().await;
+                        buffer
+                            .lookup_transform("world", "robot", 500_000_000)
+                            .unwrap();
+                        drop(buffer);
+                        lookups += 1;
+                        tokio::task::yield_now().await;
+                    }
+                    lookups
+                })
+            })
+            .collect();
+
+        let writer = {
+            let shared = shared.clone();
+            tokio::spawn(async move {
+                for i in 2..202 {
+                    shared
+                        .write()
+                        .await
+                        .update(&[TransformUpdate::new(
+                            "world",
+                            "robot",
+                            sample(i as f64),
+                            TransformType::Dynamic,
+                        )])
+                        .unwrap();
+                }
+            })
+        };
+        tokio::time::timeout(Duration::from_secs(10), writer)
+            .await
+            .expect("Ingestion starved by concurrent queries")
+            .unwrap();
+
+        stop.store(true, Ordering::Relaxed);
+        for reader in readers {
+            assert!(reader.await.unwrap() > 0);
+        }
+        assert_eq!(
+            shared.read().await.edge_latest_stamp("world", "robot"),
+            Some(201_000_000_000)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_lookup_snapshot_is_consistent_under_writes() {
+        let shared = SharedBufferTree::new(BufferTree::with_config(BufferConfig {
+            buffer_window: 1000.0,
+            ..BufferConfig::default()
+        }));
+        // Each write replaces both edges with the same new value, so a
+        // consistent read always sees them agree.
+        let write_generation = |buffer: &mut BufferTree, value: f64| {
+            buffer.clear();
+            let updates: Vec<_> = ["left", "right"]
+                .into_iter()
+                .flat_map(|frame| {
+                    [0.0, 1.0].map(|secs| {
+                        TransformUpdate::new(
+                            "world",
+                            frame,
+                            StampedIsometry::from_secs(
+                                [value, 0.0, 0.0],
+                                [0.0, 0.0, 0.0, 1.0],
+                                secs,
+                            ),
+                            TransformType::Dynamic,
+                        )
+                    })
+                })
+                .collect();
+            buffer.update(&updates).unwrap();
+        };
+        write_generation(&mut *shared.write().await, 0.0);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let writer = {
+            let shared = shared.clone();
+            let stop = stop.clone();
+            tokio::spawn(async move {
+                let mut value = 0.0;
+                while !stop.load(Ordering::Relaxed) {
+                    value += 1.0;
+                    write_generation(&mut *shared.write().await, value);
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+
+        for _ in 0..500 {
+            let snapshot = shared
+                .lookup_snapshot(&[("world", "left"), ("world", "right")], 500_000_000)
+                .await
+                .unwrap();
+            assert_eq!(snapshot[0].translation(), snapshot[1].translation());
+            tokio::task::yield_now().await;
+        }
+        stop.store(true, Ordering::Relaxed);
+        writer.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_wait_for_frames_unblocks_when_frames_appear() {
+        let shared = SharedBufferTree::new(BufferTree::with_config(BufferConfig::default()));
+        let writer = {
+            let shared = shared.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                shared
+                    .write()
+                    .await
+                    .update(&[
+                        TransformUpdate::new("world", "robot", sample(1.0), TransformType::Static),
+                        TransformUpdate::new("robot", "camera", sample(1.0), TransformType::Static),
+                    ])
+                    .unwrap();
+            })
+        };
+
+        shared
+            .wait_for_frames(&["world", "camera"], Duration::from_secs(5))
+            .await
+            .unwrap();
+        writer.await.unwrap();
+
+        let err = shared
+            .wait_for_frames(&["camera", "gripper"], Duration::from_millis(30))
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, TfError::UnknownFrame(msg) if msg.ends_with("gripper")));
+    }
+
+    #[test]
+    fn test_blocking_access_outside_runtime() {
+        let shared = SharedBufferTree::from(BufferTree::with_config(BufferConfig::default()));
+        shared
+            .blocking_write()
+            .update(&[TransformUpdate::new(
+                "world",
+                "robot",
+                sample(1.0),
+                TransformType::Static,
+            )])
+            .unwrap();
+        assert!(shared.blocking_read().has_frame("robot"));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::buffer::BufferTree;
+use crate::config::BufferConfig;
+use crate::types::{StampedIsometry, TransformType, TransformUpdate};
+
+/// Build a buffer holding a dynamic chain `frames[0] -> frames[1] -> ...`
+/// with reproducible pseudo-random transforms.
+///
+/// Every edge gets one sample per entry of `times` (seconds), each with a
+/// translation in `[-1, 1)` per axis and a uniformly distributed rotation.
+/// The same `frames`, `seed`, and `times` always produce the same buffer,
+/// so tests can exercise interpolation without literal sample tables.
+///
+/// The buffer window is widened to cover all of `times`.
+pub fn random_chain(frames: &[&str], seed: u64, times: &[f64]) -> BufferTree {
+    let span = times.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+        - times.iter().cloned().fold(f64::INFINITY, f64::min);
+    let config = BufferConfig {
+        buffer_window: BufferConfig::default().buffer_window.max(span + 1.0),
+        ..BufferConfig::default()
+    };
+    let mut buffer = BufferTree::with_config(config);
+
+    let mut rng = SplitMix64(seed);
+    let mut updates = Vec::with_capacity(frames.len().saturating_sub(1) * times.len());
+    for pair in frames.windows(2) {
+        for &time in times {
+            let translation = [
+                rng.next_f64() * 2.0 - 1.0,
+                rng.next_f64() * 2.0 - 1.0,
+                rng.next_f64() * 2.0 - 1.0,
+            ];
+            updates.push(TransformUpdate::new(
+                pair[0],
+                pair[1],
+                StampedIsometry::from_secs(translation, rng.next_quaternion(), time),
+                TransformType::Dynamic,
+            ));
+        }
+    }
+    buffer
+        .update(&updates)
+        .expect("a chain of distinct frames is a valid tree");
+    buffer
+}
+
+/// SplitMix64, a small seedable generator; good enough for test data and
+/// keeps `rand` out of the dependency tree.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniformly distributed unit quaternion `[x, y, z, w]` (Shoemake's method).
+    fn next_quaternion(&mut self) -> [f64; 4] {
+        let (u1, u2, u3) = (self.next_f64(), self.next_f64(), self.next_f64());
+        let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+        let (theta1, theta2) = (
+            2.0 * std::f64::consts::PI * u2,
+            2.0 * std::f64::consts::PI * u3,
+        );
+        [
+            a * theta1.sin(),
+            a * theta1.cos(),
+            b * theta2.sin(),
+            b * theta2.cos(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(from, to, stamp, translation, rotation)`
+    type Sample = (String, String, i64, [f64; 3], [f64; 4]);
+
+    #[test]
+    fn test_random_chain_is_deterministic() {
+        let frames = ["world", "base", "arm", "tool"];
+        let times = [0.0, 0.5, 1.0, 1.5];
+        let samples = |buffer: &BufferTree| {
+            let mut samples: Vec<Sample> = buffer
+                .iter_edges()
+                .flat_map(|edge| {
+                    edge.samples.iter().map(move |s| {
+                        (
+                            edge.from.to_string(),
+                            edge.to.to_string(),
+                            s.stamp(),
+                            s.translation(),
+                            s.rotation(),
+                        )
+                    })
+                })
+                .collect();
+            samples.sort_by(|a, b| (&a.0, &a.1, a.2).cmp(&(&b.0, &b.1, b.2)));
+            samples
+        };
+
+        let a = samples(&random_chain(&frames, 42, &times));
+        let b = samples(&random_chain(&frames, 42, &times));
+        let c = samples(&random_chain(&frames, 43, &times));
+
+        assert_eq!(a.len(), 3 * times.len());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let buffer = random_chain(&frames, 42, &times);
+        assert!(buffer
+            .lookup_transform("world", "tool", 750_000_000)
+            .is_ok());
+    }
+}
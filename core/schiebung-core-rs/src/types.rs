@@ -1,6 +1,39 @@
-use nalgebra::{Isometry3, Quaternion, Translation3, UnitQuaternion};
+use nalgebra::{Isometry3, Matrix3, Translation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt;
+use std::str::FromStr;
+
+use crate::error::TfError;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Convert seconds to nanoseconds, rounding to the nearest nanosecond.
+///
+/// Whole and fractional seconds are converted separately, so values like
+/// `1.000000001` do not lose their last nanosecond to the multiplication.
+/// Negative values (times before the Unix epoch, negative offsets) convert
+/// symmetrically. Values outside the `i64` range saturate to
+/// [`i64::MIN`]/[`i64::MAX`]; NaN maps to 0.
+pub fn secs_to_nanos(secs: f64) -> i64 {
+    if secs.is_nan() {
+        return 0;
+    }
+    let whole = secs.trunc();
+    let fraction = ((secs - whole) * NANOS_PER_SEC as f64).round() as i64;
+    (whole as i64)
+        .checked_mul(NANOS_PER_SEC)
+        .and_then(|nanos| nanos.checked_add(fraction))
+        .unwrap_or(if secs < 0.0 { i64::MIN } else { i64::MAX })
+}
+
+/// Convert nanoseconds to seconds.
+///
+/// Whole and fractional seconds are converted separately, so the result
+/// is rounded once instead of going through a lossy `i64 as f64` first.
+pub fn nanos_to_secs(nanos: i64) -> f64 {
+    (nanos / NANOS_PER_SEC) as f64 + (nanos % NANOS_PER_SEC) as f64 / NANOS_PER_SEC as f64
+}
 
 /// Whether a transform is expected to change over time.
 ///
@@ -42,6 +75,27 @@ impl TransformType {
     }
 }
 
+/// Error returned when parsing a [`TransformType`] from an unknown string.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("unknown transform type '{0}', expected 'static' or 'dynamic'")]
+pub struct ParseTransformTypeError(pub String);
+
+/// Parses `"static"` or `"dynamic"`, ignoring case, for use in config files
+/// and command-line flags.
+impl FromStr for TransformType {
+    type Err = ParseTransformTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("static") {
+            Ok(TransformType::Static)
+        } else if s.eq_ignore_ascii_case("dynamic") {
+            Ok(TransformType::Dynamic)
+        } else {
+            Err(ParseTransformTypeError(s.to_string()))
+        }
+    }
+}
+
 impl fmt::Display for TransformType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -51,6 +105,76 @@ impl fmt::Display for TransformType {
     }
 }
 
+/// A translation `[x, y, z]`.
+///
+/// Accepted wherever [`StampedIsometry`] takes a translation; a plain
+/// `[f64; 3]` converts into it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Translation([f64; 3]);
+
+impl Translation {
+    /// Create a translation from its components.
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Translation([x, y, z])
+    }
+
+    /// The components as `[x, y, z]`.
+    pub fn to_array(self) -> [f64; 3] {
+        self.0
+    }
+}
+
+impl From<[f64; 3]> for Translation {
+    fn from(xyz: [f64; 3]) -> Self {
+        Translation(xyz)
+    }
+}
+
+/// A rotation quaternion with its component order named at construction.
+///
+/// The raw arrays used across the API are `[x, y, z, w]`, while many
+/// sources (Eigen, nalgebra's `Quaternion::new`, some file formats) put `w`
+/// first. Building the rotation with [`xyzw`](Quaternion::xyzw) or
+/// [`wxyz`](Quaternion::wxyz) makes the order explicit at the call site. A
+/// plain `[f64; 4]` converts into it as `[x, y, z, w]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion([f64; 4]);
+
+impl Quaternion {
+    /// The identity rotation.
+    pub fn identity() -> Self {
+        Quaternion([0.0, 0.0, 0.0, 1.0])
+    }
+
+    /// Create a quaternion from components given `x` first, `w` last.
+    pub fn xyzw(x: f64, y: f64, z: f64, w: f64) -> Self {
+        Quaternion([x, y, z, w])
+    }
+
+    /// Create a quaternion from components given `w` first.
+    pub fn wxyz(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Quaternion([x, y, z, w])
+    }
+
+    /// The components as `[x, y, z, w]`.
+    pub fn to_xyzw(self) -> [f64; 4] {
+        self.0
+    }
+
+    /// The components as `[w, x, y, z]`.
+    pub fn to_wxyz(self) -> [f64; 4] {
+        let [x, y, z, w] = self.0;
+        [w, x, y, z]
+    }
+}
+
+impl From<[f64; 4]> for Quaternion {
+    /// Interprets the array as `[x, y, z, w]`.
+    fn from(xyzw: [f64; 4]) -> Self {
+        Quaternion(xyzw)
+    }
+}
+
 /// A 3D rigid-body transform paired with a wall-clock timestamp.
 ///
 /// This is the unit value the buffer stores per edge; lookups return it,
@@ -86,15 +210,19 @@ impl PartialOrd for StampedIsometry {
 
 impl StampedIsometry {
     /// Create a new StampedIsometry with timestamp in nanoseconds
-    pub fn new(translation: [f64; 3], rotation: [f64; 4], stamp_ns: i64) -> Self {
+    ///
+    /// `rotation` is `[x, y, z, w]` when given as an array; pass a
+    /// [`Quaternion`] built with [`Quaternion::wxyz`] for `w`-first data.
+    pub fn new(
+        translation: impl Into<Translation>,
+        rotation: impl Into<Quaternion>,
+        stamp_ns: i64,
+    ) -> Self {
+        let [tx, ty, tz] = translation.into().to_array();
+        let [w, x, y, z] = rotation.into().to_wxyz();
         let isometry = Isometry3::from_parts(
-            Translation3::new(translation[0], translation[1], translation[2]),
-            UnitQuaternion::from_quaternion(Quaternion::new(
-                rotation[3], // w
-                rotation[0], // x
-                rotation[1], // y
-                rotation[2], // z
-            )),
+            Translation3::new(tx, ty, tz),
+            UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(w, x, y, z)),
         );
         StampedIsometry {
             isometry,
@@ -102,10 +230,42 @@ impl StampedIsometry {
         }
     }
 
+    /// Like [`new`](StampedIsometry::new), but reject a `rotation` whose
+    /// norm differs from 1 by more than `tolerance` instead of silently
+    /// normalizing it.
+    ///
+    /// Use this where quaternions come from outside (e.g. the network) to
+    /// catch a misbehaving publisher early.
+    ///
+    /// # Errors
+    ///
+    /// [`TfError::InvalidTransform`] if the quaternion is not normalized
+    /// within `tolerance`.
+    pub fn try_new(
+        translation: impl Into<Translation>,
+        rotation: impl Into<Quaternion>,
+        stamp_ns: i64,
+        tolerance: f64,
+    ) -> Result<Self, TfError> {
+        let rotation = rotation.into().to_xyzw();
+        let norm = rotation.iter().map(|c| c * c).sum::<f64>().sqrt();
+        if norm.is_nan() || (norm - 1.0).abs() > tolerance {
+            return Err(TfError::InvalidTransform(format!(
+                "Quaternion {:?} has norm {}, expected 1 within {}",
+                rotation, norm, tolerance
+            )));
+        }
+        Ok(Self::new(translation, rotation, stamp_ns))
+    }
+
     /// Create a new StampedIsometry with timestamp in seconds (f64)
     /// Convenience constructor for backwards compatibility
-    pub fn from_secs(translation: [f64; 3], rotation: [f64; 4], stamp_secs: f64) -> Self {
-        Self::new(translation, rotation, (stamp_secs * 1_000_000_000.0) as i64)
+    pub fn from_secs(
+        translation: impl Into<Translation>,
+        rotation: impl Into<Quaternion>,
+        stamp_secs: f64,
+    ) -> Self {
+        Self::new(translation, rotation, secs_to_nanos(stamp_secs))
     }
 
     /// Get the translation as [x, y, z]
@@ -120,15 +280,26 @@ impl StampedIsometry {
         [q.i, q.j, q.k, q.w]
     }
 
+    /// Borrow the translation as [x, y, z], without copying it out like
+    /// [`translation`](StampedIsometry::translation).
+    pub fn translation_ref(&self) -> &[f64; 3] {
+        &self.isometry.translation.vector.data.0[0]
+    }
+
+    /// Borrow the rotation as an [x, y, z, w] quaternion, without copying
+    /// it out like [`rotation`](StampedIsometry::rotation).
+    pub fn rotation_ref(&self) -> &[f64; 4] {
+        &self.isometry.rotation.as_ref().coords.data.0[0]
+    }
+
     /// Get the timestamp in nanoseconds
     pub fn stamp(&self) -> i64 {
         self.stamp
     }
 
-    /// Get the timestamp in seconds as f64
-    /// Careful here, this will truncate!
+    /// Get the timestamp in seconds as f64, see [`nanos_to_secs`]
     pub fn stamp_secs(&self) -> f64 {
-        self.stamp as f64 / 1_000_000_000.0
+        nanos_to_secs(self.stamp)
     }
 
     /// Get the timestamp as std::time::Duration from Unix epoch
@@ -142,10 +313,47 @@ impl StampedIsometry {
         [roll, pitch, yaw]
     }
 
+    /// Rotation as a unit axis and an angle in radians (in `[0, π]`).
+    ///
+    /// For the identity rotation the axis is arbitrary; `[1, 0, 0]` is returned.
+    pub fn axis_angle(&self) -> ([f64; 3], f64) {
+        match self.isometry.rotation.axis_angle() {
+            Some((axis, angle)) => ([axis.x, axis.y, axis.z], angle),
+            None => ([1.0, 0.0, 0.0], 0.0),
+        }
+    }
+
     /// Euclidean length of the translation component (rotation is ignored).
     pub fn norm(&self) -> f64 {
         self.isometry.translation.vector.norm()
     }
+
+    /// The 4x4 homogeneous transform matrix, flattened in `layout` order.
+    ///
+    /// Use [`MatrixLayout::RowMajor`] for numpy and
+    /// [`MatrixLayout::ColumnMajor`] for Eigen or OpenGL, so the array can be
+    /// handed over without transposing.
+    pub fn to_matrix_array(&self, layout: MatrixLayout) -> [f64; 16] {
+        let matrix = self.isometry.to_homogeneous();
+        let matrix = match layout {
+            MatrixLayout::RowMajor => matrix.transpose(),
+            MatrixLayout::ColumnMajor => matrix,
+        };
+        // nalgebra stores matrices column-major.
+        let mut out = [0.0; 16];
+        out.copy_from_slice(matrix.as_slice());
+        out
+    }
+}
+
+/// Element order of a flattened matrix, see
+/// [`StampedIsometry::to_matrix_array`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixLayout {
+    /// Rows one after another, as numpy and C arrays store them.
+    RowMajor,
+    /// Columns one after another, as Eigen, OpenGL and nalgebra store them.
+    ColumnMajor,
 }
 
 impl fmt::Display for StampedIsometry {
@@ -154,12 +362,96 @@ impl fmt::Display for StampedIsometry {
         let r = self.rotation();
         write!(
             f,
-            "StampedIsometry(translation=[{:.3}, {:.3}, {:.3}], rotation=[{:.3}, {:.3}, {:.3}, {:.3}], stamp={:.6}s)",
-            t[0], t[1], t[2], r[0], r[1], r[2], r[3], self.stamp_secs()
+            "StampedIsometry(translation=[{:.3}, {:.3}, {:.3}], rotation=[{:.3}, {:.3}, {:.3}, {:.3}], stamp={}ns ({:.6}s))",
+            t[0], t[1], t[2], r[0], r[1], r[2], r[3], self.stamp, self.stamp_secs()
         )
     }
 }
 
+/// Interpolate between two stamped poses at `time` (nanoseconds since the
+/// Unix epoch).
+///
+/// Translation is interpolated linearly and rotation by slerp, weighted by
+/// where `time` falls between `a.stamp` and `b.stamp`. This is the
+/// interpolation [`BufferTree::lookup_transform`](crate::BufferTree::lookup_transform)
+/// applies between neighbouring samples by default
+/// ([`InterpolationMode::Linear`]), usable without a buffer (e.g. on
+/// sparse poses received over the network). `time` outside the two stamps
+/// is clamped to the nearer pose; if both stamps are equal `a` is returned.
+pub fn interpolate(a: &StampedIsometry, b: &StampedIsometry, time: i64) -> Isometry3<f64> {
+    interpolate_with_mode(a, b, time, InterpolationMode::Linear)
+}
+
+/// [`interpolate`] with an explicit [`InterpolationMode`].
+pub fn interpolate_with_mode(
+    a: &StampedIsometry,
+    b: &StampedIsometry,
+    time: i64,
+    mode: InterpolationMode,
+) -> Isometry3<f64> {
+    let dt = (b.stamp - a.stamp) as f64;
+    if dt == 0.0 {
+        return a.isometry;
+    }
+    let weight = ((time - a.stamp) as f64 / dt).clamp(0.0, 1.0);
+    match mode {
+        InterpolationMode::Linear => a.isometry.lerp_slerp(&b.isometry, weight),
+        InterpolationMode::Screw => {
+            a.isometry * screw_scale(&(a.isometry.inverse() * b.isometry), weight)
+        }
+    }
+}
+
+/// How poses are interpolated between two samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterpolationMode {
+    /// Translation linearly, rotation by slerp, each on its own. Cheap, and
+    /// exact whenever the rotation between the samples is small.
+    #[default]
+    Linear,
+    /// Constant-twist (screw) motion: the SE(3) geodesic between the
+    /// samples. A frame rotating about an offset axis follows the arc
+    /// around that axis instead of cutting the chord.
+    Screw,
+}
+
+/// Below this rotation angle (radians) the `V` matrix of the SE(3)
+/// exponential is taken from its Taylor expansion, avoiding `0 / 0`.
+const SMALL_ANGLE: f64 = 1e-6;
+
+/// The `V` matrix mapping the translational part of a twist with rotation
+/// vector `omega` to the translation of its exponential.
+fn se3_v(omega: &Vector3<f64>) -> Matrix3<f64> {
+    let theta = omega.norm();
+    let w = omega.cross_matrix();
+    if theta < SMALL_ANGLE {
+        return Matrix3::identity() + w * 0.5 + w * w / 6.0;
+    }
+    Matrix3::identity()
+        + w * ((1.0 - theta.cos()) / (theta * theta))
+        + w * w * ((theta - theta.sin()) / (theta * theta * theta))
+}
+
+/// `exp(weight * log(relative))` in SE(3): the pose reached after `weight`
+/// of the constant-twist motion from the identity to `relative`.
+fn screw_scale(relative: &Isometry3<f64>, weight: f64) -> Isometry3<f64> {
+    let omega = relative.rotation.scaled_axis();
+    // V is invertible for rotation angles below 2 pi, and scaled_axis
+    // returns angles of at most pi.
+    let v = se3_v(&omega)
+        .try_inverse()
+        .expect("V is invertible for angles up to pi")
+        * relative.translation.vector;
+
+    let omega = omega * weight;
+    let translation = se3_v(&omega) * (v * weight);
+    Isometry3::from_parts(
+        Translation3::from(translation),
+        UnitQuaternion::from_scaled_axis(omega),
+    )
+}
+
 /// One transform to insert into a [`BufferTree`](crate::BufferTree) via
 /// [`update`](crate::BufferTree::update).
 ///
@@ -176,6 +468,9 @@ pub struct TransformUpdate {
     pub stamped_isometry: StampedIsometry,
     /// Whether this edge is static or part of a time series.
     pub kind: TransformType,
+    /// Optional confidence of the estimate, in `[0, 1]`. See
+    /// [`BufferTree::lookup_with_quality`](crate::BufferTree::lookup_with_quality).
+    pub quality: Option<f32>,
 }
 
 impl TransformUpdate {
@@ -193,6 +488,251 @@ impl TransformUpdate {
             to: to.into(),
             stamped_isometry,
             kind,
+            quality: None,
+        }
+    }
+
+    /// Attach a quality score, e.g. the confidence of the estimator that
+    /// produced the transform.
+    ///
+    /// Scores outside `[0, 1]` are clamped and NaN leaves the update without
+    /// a score. Use [`try_with_quality`](Self::try_with_quality) to reject
+    /// such values instead.
+    pub fn with_quality(mut self, quality: f32) -> Self {
+        self.quality = (!quality.is_nan()).then(|| quality.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Attach a quality score, rejecting values outside `[0, 1]`.
+    ///
+    /// # Errors
+    ///
+    /// [`TfError::InvalidTransform`] if `quality` is NaN or not within
+    /// `[0, 1]`.
+    pub fn try_with_quality(mut self, quality: f32) -> Result<Self, TfError> {
+        if !(0.0..=1.0).contains(&quality) {
+            return Err(TfError::InvalidTransform(format!(
+                "Quality {} is not within [0, 1]",
+                quality
+            )));
+        }
+        self.quality = Some(quality);
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secs_to_nanos_rounds_and_saturates() {
+        // Truncating `secs * 1e9` would give 1_000_000_000 here.
+        assert_eq!(secs_to_nanos(1.000000001), 1_000_000_001);
+        assert_eq!(secs_to_nanos(0.3), 300_000_000);
+        assert_eq!(secs_to_nanos(-1.5), -1_500_000_000);
+        assert_eq!(secs_to_nanos(-0.000000001), -1);
+        // A current Unix time keeps its whole seconds exactly.
+        assert_eq!(secs_to_nanos(1_700_000_000.25), 1_700_000_000_250_000_000);
+        assert_eq!(secs_to_nanos(1e12), i64::MAX);
+        assert_eq!(secs_to_nanos(-1e12), i64::MIN);
+        assert_eq!(secs_to_nanos(f64::INFINITY), i64::MAX);
+        assert_eq!(secs_to_nanos(f64::NEG_INFINITY), i64::MIN);
+        assert_eq!(secs_to_nanos(f64::NAN), 0);
+    }
+
+    #[test]
+    fn test_quality_outside_unit_range() {
+        let update = || {
+            TransformUpdate::new(
+                "a",
+                "b",
+                StampedIsometry::new([0.0; 3], [0.0, 0.0, 0.0, 1.0], 0),
+                TransformType::Static,
+            )
+        };
+        assert_eq!(update().with_quality(1.5).quality, Some(1.0));
+        assert_eq!(update().with_quality(-0.5).quality, Some(0.0));
+        assert_eq!(update().with_quality(f32::NAN).quality, None);
+
+        assert_eq!(update().try_with_quality(0.5).unwrap().quality, Some(0.5));
+        for quality in [1.5, -0.5, f32::NAN] {
+            assert!(matches!(
+                update().try_with_quality(quality),
+                Err(TfError::InvalidTransform(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_screw_interpolation_follows_the_arc() {
+        // A quarter turn about z around the origin, starting one metre out
+        // on x: the frame rotates and translates at the same time.
+        let a = StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+        let eighth_turn = std::f64::consts::FRAC_PI_4;
+        let b = StampedIsometry::new(
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, eighth_turn.sin(), eighth_turn.cos()],
+            1_000_000_000,
+        );
+
+        let linear = interpolate_with_mode(&a, &b, 500_000_000, InterpolationMode::Linear);
+        let screw = interpolate_with_mode(&a, &b, 500_000_000, InterpolationMode::Screw);
+
+        // Same rotation either way, but only the screw stays on the circle.
+        assert!(screw.rotation.angle_to(&linear.rotation) < 1e-9);
+        assert!((linear.translation.vector.norm() - 0.5_f64.sqrt()).abs() < 1e-9);
+        let on_arc = [eighth_turn.cos(), eighth_turn.sin(), 0.0];
+        for (actual, expected) in screw.translation.vector.iter().zip(on_arc) {
+            assert!((actual - expected).abs() < 1e-9);
+        }
+
+        // Both modes hit the samples at the ends.
+        for mode in [InterpolationMode::Linear, InterpolationMode::Screw] {
+            let start = interpolate_with_mode(&a, &b, 0, mode);
+            let end = interpolate_with_mode(&a, &b, 1_000_000_000, mode);
+            assert!((start.translation.vector - a.isometry.translation.vector).norm() < 1e-9);
+            assert!((end.translation.vector - b.isometry.translation.vector).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_nanos_to_secs_keeps_precision() {
+        assert_eq!(nanos_to_secs(1_500_000_000), 1.5);
+        assert_eq!(nanos_to_secs(-1_500_000_000), -1.5);
+        assert_eq!(nanos_to_secs(0), 0.0);
+        // Dividing the whole stamp as one float rounds twice and lands one
+        // step off the nearest f64.
+        let stamp = 1_234_567_890_987_654_321;
+        assert_ne!(nanos_to_secs(stamp), stamp as f64 / 1e9);
+        assert_eq!(nanos_to_secs(stamp), 1_234_567_890.987_654_2);
+        assert_eq!(
+            secs_to_nanos(nanos_to_secs(-123_456_789_012)),
+            -123_456_789_012
+        );
+        assert_eq!(nanos_to_secs(i64::MAX).round(), 9_223_372_037.0);
+    }
+
+    #[test]
+    fn test_try_new_rejects_unnormalized_quaternion() {
+        let scaled = [0.0, 0.0, 0.0, 2.0];
+        assert!(matches!(
+            StampedIsometry::try_new([0.0; 3], scaled, 0, 1e-6),
+            Err(TfError::InvalidTransform(_))
+        ));
+        assert!(matches!(
+            StampedIsometry::try_new([0.0; 3], [f64::NAN, 0.0, 0.0, 1.0], 0, 1e-6),
+            Err(TfError::InvalidTransform(_))
+        ));
+        // Within tolerance it is accepted (and normalized as usual).
+        let nearly =
+            StampedIsometry::try_new([0.0; 3], [0.0, 0.0, 0.0, 1.0 + 1e-9], 0, 1e-6).unwrap();
+        assert_eq!(nearly.rotation(), [0.0, 0.0, 0.0, 1.0]);
+        // The lenient constructor keeps normalizing.
+        assert_eq!(
+            StampedIsometry::new([0.0; 3], scaled, 0).rotation(),
+            [0.0, 0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_to_matrix_array() {
+        // A quarter turn about z, then a shift of (1, 2, 3).
+        let eighth_turn = std::f64::consts::FRAC_PI_4;
+        let transform = StampedIsometry::new(
+            [1.0, 2.0, 3.0],
+            [0.0, 0.0, eighth_turn.sin(), eighth_turn.cos()],
+            0,
+        );
+        #[rustfmt::skip]
+        let row_major = [
+            0.0, -1.0, 0.0, 1.0,
+            1.0,  0.0, 0.0, 2.0,
+            0.0,  0.0, 1.0, 3.0,
+            0.0,  0.0, 0.0, 1.0,
+        ];
+        #[rustfmt::skip]
+        let column_major = [
+             0.0, 1.0, 0.0, 0.0,
+            -1.0, 0.0, 0.0, 0.0,
+             0.0, 0.0, 1.0, 0.0,
+             1.0, 2.0, 3.0, 1.0,
+        ];
+
+        for (layout, expected) in [
+            (MatrixLayout::RowMajor, row_major),
+            (MatrixLayout::ColumnMajor, column_major),
+        ] {
+            let actual = transform.to_matrix_array(layout);
+            for (a, e) in actual.iter().zip(expected) {
+                assert!((a - e).abs() < 1e-12, "{:?}: {:?}", layout, actual);
+            }
         }
     }
+
+    #[test]
+    fn test_axis_angle() {
+        let angle = std::f64::consts::FRAC_PI_3;
+        let about_z = StampedIsometry::new(
+            [0.0; 3],
+            [0.0, 0.0, (angle / 2.0).sin(), (angle / 2.0).cos()],
+            0,
+        );
+        let (axis, result) = about_z.axis_angle();
+        assert!((result - angle).abs() < 1e-12);
+        assert!((axis[0]).abs() < 1e-12 && (axis[1]).abs() < 1e-12);
+        assert!((axis[2] - 1.0).abs() < 1e-12);
+
+        let identity = StampedIsometry::new([0.0; 3], [0.0, 0.0, 0.0, 1.0], 0);
+        assert_eq!(identity.axis_angle(), ([1.0, 0.0, 0.0], 0.0));
+    }
+
+    #[test]
+    fn test_transform_type_from_str() {
+        assert!(matches!(
+            "Static".parse::<TransformType>(),
+            Ok(TransformType::Static)
+        ));
+        assert!(matches!(
+            "dynamic".parse::<TransformType>(),
+            Ok(TransformType::Dynamic)
+        ));
+        let err = "sometimes".parse::<TransformType>().unwrap_err();
+        assert_eq!(err, ParseTransformTypeError("sometimes".to_string()));
+        assert_eq!(
+            err.to_string(),
+            "unknown transform type 'sometimes', expected 'static' or 'dynamic'"
+        );
+    }
+
+    #[test]
+    fn test_borrowed_accessors_match_copies() {
+        let rotation = [0.1, -0.2, 0.3, 0.9];
+        let stamped = StampedIsometry::new([1.5, -2.0, 3.25], rotation, 7);
+        assert_eq!(stamped.translation_ref(), &stamped.translation());
+        assert_eq!(stamped.rotation_ref(), &stamped.rotation());
+        assert_eq!(stamped.translation_ref(), &[1.5, -2.0, 3.25]);
+    }
+
+    #[test]
+    fn test_quaternion_orderings() {
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        // 90° about z, written in both orders.
+        let from_xyzw = Quaternion::xyzw(0.0, 0.0, h, h);
+        let from_wxyz = Quaternion::wxyz(h, 0.0, 0.0, h);
+        assert_eq!(from_xyzw, from_wxyz);
+        assert_eq!(from_wxyz.to_xyzw(), [0.0, 0.0, h, h]);
+        assert_eq!(from_xyzw.to_wxyz(), [h, 0.0, 0.0, h]);
+        assert_eq!(Quaternion::from([0.0, 0.0, h, h]), from_xyzw);
+
+        let typed = StampedIsometry::new(Translation::new(1.0, 2.0, 3.0), from_wxyz, 0);
+        let raw = StampedIsometry::new([1.0, 2.0, 3.0], [0.0, 0.0, h, h], 0);
+        assert_eq!(typed.translation(), raw.translation());
+        assert!(typed.isometry.rotation.angle_to(&raw.isometry.rotation) < 1e-12);
+        assert!((typed.euler_angles()[2] - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+
+        // w-first data passed as `wxyz` is not mistaken for `[x, y, z, w]`.
+        let identity = StampedIsometry::new([0.0; 3], Quaternion::wxyz(1.0, 0.0, 0.0, 0.0), 0);
+        assert_eq!(identity.rotation(), Quaternion::identity().to_xyzw());
+    }
 }
@@ -0,0 +1,223 @@
+//! Data-driven comparison against ROS 2 tf2.
+//!
+//! Every `*.yaml` file in `tests/fixtures` holds a list of `inputs` fed into a
+//! tf2 buffer and the `outputs` tf2 returned for
+//! `lookup_transform(header.frame_id, child_frame_id, header.stamp)`. Both use
+//! the dict layout printed by the capture script documented on
+//! `test_complex_interpolation` in `src/buffer.rs`, so new cases can be added
+//! by pasting its output into a new fixture file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use approx::relative_eq;
+use schiebung::{
+    secs_to_nanos, BufferConfig, BufferTree, StampedIsometry, TransformType, TransformUpdate,
+};
+use serde::Deserialize;
+
+const EPSILON: f64 = 1e-6;
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    inputs: Vec<TransformStamped>,
+    outputs: Vec<TransformStamped>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransformStamped {
+    header: Header,
+    child_frame_id: String,
+    transform: Transform,
+}
+
+#[derive(Debug, Deserialize)]
+struct Header {
+    frame_id: String,
+    stamp: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transform {
+    translation: Vector3,
+    rotation: Quaternion,
+}
+
+#[derive(Debug, Deserialize)]
+struct Vector3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Quaternion {
+    x: f64,
+    y: f64,
+    z: f64,
+    w: f64,
+}
+
+impl TransformStamped {
+    fn stamp_ns(&self) -> i64 {
+        secs_to_nanos(self.header.stamp)
+    }
+
+    fn translation(&self) -> [f64; 3] {
+        let t = &self.transform.translation;
+        [t.x, t.y, t.z]
+    }
+
+    fn rotation(&self) -> [f64; 4] {
+        let q = &self.transform.rotation;
+        [q.x, q.y, q.z, q.w]
+    }
+
+    fn to_stamped_isometry(&self) -> StampedIsometry {
+        StampedIsometry::new(self.translation(), self.rotation(), self.stamp_ns())
+    }
+}
+
+fn fixture_paths() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn load_fixture(path: &Path) -> Fixture {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+    serde_yaml::from_str(&text)
+        .unwrap_or_else(|e| panic!("Could not parse {}: {}", path.display(), e))
+}
+
+fn run_fixture(path: &Path) {
+    let fixture = load_fixture(path);
+    let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+
+    for input in &fixture.inputs {
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                &input.header.frame_id,
+                &input.child_frame_id,
+                input.to_stamped_isometry(),
+                TransformType::Dynamic,
+            )])
+            .unwrap_or_else(|e| panic!("{}: failed to insert input: {}", path.display(), e));
+    }
+
+    for expected in &fixture.outputs {
+        let from = &expected.header.frame_id;
+        let to = &expected.child_frame_id;
+        let time = expected.stamp_ns();
+        let result = buffer_tree
+            .lookup_transform(from, to, time)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "{}: lookup {} -> {} at {} failed: {}",
+                    path.display(),
+                    from,
+                    to,
+                    time,
+                    e
+                )
+            });
+
+        let translation = result.translation();
+        let expected_translation = expected.translation();
+        assert!(
+            (0..3).all(|i| relative_eq!(
+                translation[i],
+                expected_translation[i],
+                epsilon = EPSILON
+            )),
+            "{}: translation mismatch for {} -> {} at {}: result={:?}, expected={:?}",
+            path.display(),
+            from,
+            to,
+            time,
+            translation,
+            expected_translation
+        );
+
+        // q and -q represent the same rotation
+        let rotation = result.rotation();
+        let expected_rotation = expected.rotation();
+        let matches =
+            (0..4).all(|i| relative_eq!(rotation[i], expected_rotation[i], epsilon = EPSILON));
+        let neg_matches =
+            (0..4).all(|i| relative_eq!(rotation[i], -expected_rotation[i], epsilon = EPSILON));
+        assert!(
+            matches || neg_matches,
+            "{}: rotation mismatch for {} -> {} at {}: result={:?}, expected={:?}",
+            path.display(),
+            from,
+            to,
+            time,
+            rotation,
+            expected_rotation
+        );
+    }
+}
+
+#[test]
+fn test_tf2_reference_fixtures() {
+    let paths = fixture_paths();
+    assert!(!paths.is_empty(), "No fixtures found in tests/fixtures");
+    for path in paths {
+        run_fixture(&path);
+    }
+}
+
+#[test]
+fn test_to_tf_yaml_round_trips_through_fixture_format() {
+    let path = fixture_paths().into_iter().next().unwrap();
+    let fixture = load_fixture(&path);
+    let mut buffer_tree = BufferTree::with_config(BufferConfig::default());
+    for input in &fixture.inputs {
+        buffer_tree
+            .update(&[TransformUpdate::new(
+                &input.header.frame_id,
+                &input.child_frame_id,
+                input.to_stamped_isometry(),
+                TransformType::Dynamic,
+            )])
+            .unwrap();
+    }
+
+    let time = fixture
+        .inputs
+        .iter()
+        .map(|input| input.stamp_ns())
+        .max()
+        .unwrap()
+        / 2;
+    let yaml = buffer_tree.to_tf_yaml(time);
+    let exported: Vec<TransformStamped> = serde_yaml::from_str(&yaml)
+        .unwrap_or_else(|e| panic!("Could not parse exported YAML: {}\n{}", e, yaml));
+
+    assert_eq!(exported.len(), buffer_tree.iter_edges().count());
+    for entry in &exported {
+        assert_eq!(entry.stamp_ns(), time);
+        let expected = buffer_tree
+            .lookup_transform(&entry.header.frame_id, &entry.child_frame_id, time)
+            .unwrap();
+        let translation = expected.translation();
+        let rotation = expected.rotation();
+        assert!((0..3).all(|i| relative_eq!(
+            entry.translation()[i],
+            translation[i],
+            epsilon = EPSILON
+        )));
+        assert!((0..4).all(|i| relative_eq!(entry.rotation()[i], rotation[i], epsilon = EPSILON)));
+    }
+
+    let empty = BufferTree::with_config(BufferConfig::default());
+    let exported: Vec<TransformStamped> = serde_yaml::from_str(&empty.to_tf_yaml(time)).unwrap();
+    assert!(exported.is_empty());
+}
@@ -5,12 +5,13 @@
 
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use schiebung::BufferTree as CoreBufferTree;
+use schiebung::SharedBufferTree;
 use schiebung_server::{
     CommsError, Server as CoreServer, ServerHandle as CoreServerHandle,
     TransformClient as CoreTransformClient,
 };
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 // Re-export Python wrapper types from schiebung-py to avoid duplication
@@ -66,7 +67,8 @@ impl ServerHandle {
 /// Python wrapper for the BufferTree (read-only access while server runs)
 #[pyclass]
 pub struct BufferTreeRef {
-    inner: Arc<RwLock<CoreBufferTree>>,
+    inner: SharedBufferTree,
+    runtime: Arc<Runtime>,
 }
 
 #[pymethods]
@@ -86,10 +88,7 @@ impl BufferTreeRef {
         to_frame: &str,
         time: i64,
     ) -> PyResult<StampedIsometry> {
-        let guard = self
-            .inner
-            .read()
-            .map_err(|e| PyValueError::new_err(format!("Lock poisoned: {}", e)))?;
+        let guard = self.inner.blocking_read();
 
         let result = guard
             .lookup_transform(from_frame, to_frame, time)
@@ -114,10 +113,7 @@ impl BufferTreeRef {
         from_frame: &str,
         to_frame: &str,
     ) -> PyResult<StampedIsometry> {
-        let guard = self
-            .inner
-            .read()
-            .map_err(|e| PyValueError::new_err(format!("Lock poisoned: {}", e)))?;
+        let guard = self.inner.blocking_read();
 
         let result = guard
             .lookup_latest_transform(from_frame, to_frame)
@@ -126,15 +122,39 @@ impl BufferTreeRef {
         Ok(StampedIsometry::from(result))
     }
 
+    /// Block until every frame in `frames` is in the buffer.
+    ///
+    /// The GIL is released while waiting, so other Python threads keep running.
+    ///
+    /// Args:
+    ///     frames: The frame names to wait for
+    ///     timeout: The maximum time to wait, in seconds
+    ///
+    /// Raises:
+    ///     ValueError: If some frames are still missing after `timeout`
+    pub fn wait_for_frames(
+        &self,
+        py: Python<'_>,
+        frames: Vec<String>,
+        timeout: f64,
+    ) -> PyResult<()> {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .map_err(|e| PyValueError::new_err(format!("Invalid timeout: {}", e)))?;
+        let frames: Vec<&str> = frames.iter().map(String::as_str).collect();
+
+        py.detach(|| {
+            self.runtime
+                .block_on(self.inner.wait_for_frames(&frames, timeout))
+        })
+        .map_err(|e| PyValueError::new_err(format!("Transform lookup error: {}", e)))
+    }
+
     /// Visualize the buffer tree as a DOT graph string.
     ///
     /// Returns:
     ///     A DOT format string representing the transform graph.
     pub fn visualize(&self) -> PyResult<String> {
-        let guard = self
-            .inner
-            .read()
-            .map_err(|e| PyValueError::new_err(format!("Lock poisoned: {}", e)))?;
+        let guard = self.inner.blocking_read();
 
         Ok(guard.visualize())
     }
@@ -143,10 +163,7 @@ impl BufferTreeRef {
     ///
     /// Requires graphviz to be installed on the system.
     pub fn save_visualization(&self) -> PyResult<()> {
-        let guard = self
-            .inner
-            .read()
-            .map_err(|e| PyValueError::new_err(format!("Lock poisoned: {}", e)))?;
+        let guard = self.inner.blocking_read();
 
         guard
             .save_visualization()
@@ -211,6 +228,7 @@ impl Server {
     pub fn buffer(&self) -> BufferTreeRef {
         BufferTreeRef {
             inner: self.inner.buffer(),
+            runtime: self.runtime.clone(),
         }
     }
 
@@ -299,6 +317,31 @@ impl TransformClient {
             .map_err(comms_err_to_pyerr)
     }
 
+    /// Send a batch of samples for one edge to the server in a single message.
+    ///
+    /// Args:
+    ///     from_frame: The source frame name
+    ///     to_frame: The target frame name
+    ///     samples: The transform samples, oldest first
+    ///     kind: The transform type (static or dynamic)
+    pub fn send_transform_history(
+        &self,
+        from_frame: String,
+        to_frame: String,
+        samples: Vec<StampedIsometry>,
+        kind: TransformType,
+    ) -> PyResult<()> {
+        let core_samples: Vec<_> = samples.into_iter().map(|s| s.inner).collect();
+
+        self.runtime
+            .block_on(async {
+                self.inner
+                    .send_transform_history(&from_frame, &to_frame, &core_samples, kind.into())
+                    .await
+            })
+            .map_err(comms_err_to_pyerr)
+    }
+
     /// Request a transform from the server.
     ///
     /// Args:
@@ -325,6 +368,25 @@ impl TransformClient {
 
         Ok(StampedIsometry::from(result))
     }
+
+    /// Request the server's transform graph as a DOT string.
+    ///
+    /// Returns:
+    ///     A DOT format string representing the server's transform graph.
+    pub fn request_visualization(&self) -> PyResult<String> {
+        self.runtime
+            .block_on(async { self.inner.request_visualization().await })
+            .map_err(comms_err_to_pyerr)
+    }
+
+    /// Remove every frame and edge from the server's buffer.
+    ///
+    /// Raises if the server does not allow remote clearing.
+    pub fn clear_server(&self) -> PyResult<()> {
+        self.runtime
+            .block_on(async { self.inner.clear_server().await })
+            .map_err(comms_err_to_pyerr)
+    }
 }
 
 fn comms_err_to_pyerr(err: CommsError) -> PyErr {
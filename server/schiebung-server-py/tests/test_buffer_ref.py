@@ -0,0 +1,42 @@
+"""Tests for the BufferTreeRef returned by Server.buffer."""
+import threading
+import time
+
+import pytest
+
+from schiebung_server import Server, StampedIsometry, TransformClient, TransformType
+
+
+@pytest.fixture
+def server():
+    server = Server("schiebung_test", "test_buffer_ref", "stable_time", True)
+    handle = server.start()
+    yield server
+    handle.shutdown()
+    handle.join()
+
+
+def test_wait_for_frames_times_out(server):
+    start = time.monotonic()
+    with pytest.raises(ValueError, match="missing_frame"):
+        server.buffer.wait_for_frames(["missing_frame"], 0.1)
+    assert time.monotonic() - start < 5.0
+
+
+def test_wait_for_frames_releases_gil(server):
+    client = TransformClient()
+
+    def publish():
+        time.sleep(0.2)
+        client.send_transform(
+            "world",
+            "robot",
+            StampedIsometry([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType.Static,
+        )
+
+    # The publisher only runs if waiting does not hold the GIL.
+    publisher = threading.Thread(target=publish)
+    publisher.start()
+    server.buffer.wait_for_frames(["world", "robot"], 10.0)
+    publisher.join()
@@ -32,12 +32,30 @@ struct ServerConfig {
     /// Set to false if loading URDF via Rerun's built-in loader to avoid duplicates
     #[serde(default = "default_publish_static")]
     publish_static_transforms: bool,
+
+    /// Address to serve Prometheus metrics on (e.g. "0.0.0.0:9464")
+    /// Only used when built with the `metrics` feature
+    #[serde(default)]
+    metrics_address: Option<String>,
+
+    /// Maximum transform requests per client within `rate_limit_period`
+    /// seconds. Unset = no limit
+    #[serde(default)]
+    rate_limit_requests: Option<u32>,
+
+    /// Length of the rate limit window in seconds
+    #[serde(default = "default_rate_limit_period")]
+    rate_limit_period: f64,
 }
 
 fn default_publish_static() -> bool {
     true
 }
 
+fn default_rate_limit_period() -> f64 {
+    1.0
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logger
@@ -65,13 +83,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     log::info!("Starting server with config: {:?}", server_config);
 
     // Create and run the server
-    let server = Server::new(
-        &server_config.application_id,
-        &server_config.recording_id,
-        &server_config.timeline,
-        server_config.publish_static_transforms,
-    )
-    .await?;
+    let mut builder = Server::builder(&server_config.application_id)
+        .recording_id(&server_config.recording_id)
+        .timeline(&server_config.timeline)
+        .publish_static_transforms(server_config.publish_static_transforms)
+        .sink_from_env();
+    if let Some(max_requests) = server_config.rate_limit_requests {
+        builder = builder.rate_limit(max_requests, server_config.rate_limit_period);
+    }
+    let server = builder.build().await?;
+
+    #[cfg(feature = "metrics")]
+    let _metrics = match &server_config.metrics_address {
+        Some(addr) => Some(server.serve_metrics(addr.as_str()).await?),
+        None => None,
+    };
+    #[cfg(not(feature = "metrics"))]
+    if server_config.metrics_address.is_some() {
+        log::warn!("metrics_address is set, but the server was built without the metrics feature");
+    }
 
     log::info!("Server initialized, starting main loop...");
     server.run().await?;
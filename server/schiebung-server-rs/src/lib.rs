@@ -16,6 +16,23 @@
 //! }
 //! ```
 //!
+//! # Example (builder)
+//! ```no_run
+//! use schiebung_server::Server;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     // Record to a file instead of streaming to a viewer.
+//!     let server = Server::builder("schiebung")
+//!         .recording_id("session_001")
+//!         .save("session_001.rrd")
+//!         .build()
+//!         .await?;
+//!     server.run().await?;
+//!     Ok(())
+//! }
+//! ```
+//!
 //! # Example (non-blocking)
 //! ```no_run
 //! use schiebung_server::Server;
@@ -36,11 +53,15 @@
 //! }
 //! ```
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 use comms::server::TransformServer;
+use comms::ZenohConfig;
 use log::info;
 use rerun::RecordingStreamBuilder;
 use schiebung_rerun::RerunObserver;
-use std::sync::{Arc, RwLock};
+use std::path::PathBuf;
 use tokio::sync::oneshot;
 
 /// Handle to a running server, allowing shutdown and join.
@@ -79,6 +100,165 @@ impl ServerHandle {
     }
 }
 
+/// Where a [`Server`] sends its Rerun recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordingSink {
+    /// Spawn a Rerun viewer from `PATH` (or reuse one already listening on
+    /// the default port) and stream to it.
+    Spawn,
+    /// Stream to a running Rerun instance at this gRPC proxy address, e.g.
+    /// `"rerun+http://127.0.0.1:9876/proxy"`.
+    ConnectGrpc(String),
+    /// Write the recording to an `.rrd` file.
+    Save(PathBuf),
+}
+
+/// Builder for a [`Server`], created with [`Server::builder`].
+///
+/// Unlike [`Server::new`] it never reads environment variables: the
+/// recording sink defaults to [`RecordingSink::Spawn`] and is chosen with
+/// [`spawn`](ServerBuilder::spawn), [`connect_grpc`](ServerBuilder::connect_grpc)
+/// or [`save`](ServerBuilder::save).
+#[derive(Debug, Clone)]
+pub struct ServerBuilder {
+    application_id: String,
+    recording_id: Option<String>,
+    timeline: String,
+    publish_static_transforms: bool,
+    sink: RecordingSink,
+    zenoh_config: ZenohConfig,
+    allow_remote_clear: bool,
+    rate_limit: Option<(u32, f64)>,
+}
+
+impl ServerBuilder {
+    fn new(application_id: impl Into<String>) -> Self {
+        Self {
+            application_id: application_id.into(),
+            recording_id: None,
+            timeline: "stable_time".to_string(),
+            publish_static_transforms: true,
+            sink: RecordingSink::Spawn,
+            zenoh_config: ZenohConfig::default(),
+            allow_remote_clear: false,
+            rate_limit: None,
+        }
+    }
+
+    /// Recording ID for this session. Rerun picks a random one if unset.
+    pub fn recording_id(mut self, recording_id: impl Into<String>) -> Self {
+        self.recording_id = Some(recording_id.into());
+        self
+    }
+
+    /// Timeline to log transform timestamps to. Defaults to `"stable_time"`.
+    pub fn timeline(mut self, timeline: impl Into<String>) -> Self {
+        self.timeline = timeline.into();
+        self
+    }
+
+    /// Whether to log static transforms to Rerun. Defaults to `true`; set
+    /// to `false` if loading URDF via Rerun's built-in loader to avoid
+    /// duplicates.
+    pub fn publish_static_transforms(mut self, publish_static_transforms: bool) -> Self {
+        self.publish_static_transforms = publish_static_transforms;
+        self
+    }
+
+    /// Zenoh config for the underlying [`TransformServer`]. Defaults to
+    /// [`ZenohConfig::default`].
+    pub fn zenoh_config(mut self, config: ZenohConfig) -> Self {
+        self.zenoh_config = config;
+        self
+    }
+
+    /// Let clients empty the buffer remotely. Off by default, see
+    /// [`TransformServer::with_remote_clear`].
+    pub fn allow_remote_clear(mut self, allow: bool) -> Self {
+        self.allow_remote_clear = allow;
+        self
+    }
+
+    /// Answer at most `max_requests` transform requests per client every
+    /// `period_secs` seconds. Off by default, see
+    /// [`TransformServer::with_rate_limit`].
+    pub fn rate_limit(mut self, max_requests: u32, period_secs: f64) -> Self {
+        self.rate_limit = Some((max_requests, period_secs));
+        self
+    }
+
+    /// Stream to the Rerun instance at `RERUN_CONNECT_ADDR` if that
+    /// environment variable is set, and spawn a viewer otherwise. This is
+    /// what [`Server::new`] does.
+    pub fn sink_from_env(self) -> Self {
+        match std::env::var("RERUN_CONNECT_ADDR") {
+            Ok(addr) => self.connect_grpc(addr),
+            Err(_) => self.spawn(),
+        }
+    }
+
+    /// Spawn a Rerun viewer and stream to it. This is the default.
+    pub fn spawn(mut self) -> Self {
+        self.sink = RecordingSink::Spawn;
+        self
+    }
+
+    /// Stream to a running Rerun instance at the gRPC proxy address `addr`.
+    pub fn connect_grpc(mut self, addr: impl Into<String>) -> Self {
+        self.sink = RecordingSink::ConnectGrpc(addr.into());
+        self
+    }
+
+    /// Write the recording to the `.rrd` file at `path`.
+    pub fn save(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sink = RecordingSink::Save(path.into());
+        self
+    }
+
+    /// The sink the recording will be sent to.
+    pub fn sink(&self) -> &RecordingSink {
+        &self.sink
+    }
+
+    /// Open the zenoh session and the Rerun recording, and register the
+    /// Rerun observer on the server's buffer.
+    pub async fn build(self) -> Result<Server, CommsError> {
+        let mut inner = TransformServer::with_config(self.zenoh_config)
+            .await?
+            .with_remote_clear(self.allow_remote_clear);
+        if let Some((max_requests, period_secs)) = self.rate_limit {
+            inner = inner.with_rate_limit(max_requests, period_secs);
+        }
+
+        let mut builder = RecordingStreamBuilder::new(self.application_id);
+        if let Some(recording_id) = self.recording_id {
+            builder = builder.recording_id(recording_id);
+        }
+        let rec = match self.sink {
+            RecordingSink::Spawn => builder
+                .spawn()
+                .map_err(|e| CommsError::Config(format!("Failed to create Rerun stream: {}", e)))?,
+            RecordingSink::ConnectGrpc(addr) => builder
+                .connect_grpc_opts(addr)
+                .map_err(|e| CommsError::Config(format!("Failed to connect to Rerun: {}", e)))?,
+            RecordingSink::Save(path) => builder.save(&path).map_err(|e| {
+                CommsError::Config(format!(
+                    "Failed to save Rerun recording to {:?}: {}",
+                    path, e
+                ))
+            })?,
+        };
+        let observer = RerunObserver::new(rec, self.publish_static_transforms, self.timeline);
+        inner
+            .buffer()
+            .write()
+            .await
+            .register_observer(Box::new(observer));
+
+        Ok(Server { inner })
+    }
+}
+
 /// Centralized transform server with integrated Rerun visualization.
 ///
 /// This server combines the communication capabilities of `TransformServer`
@@ -92,6 +272,10 @@ pub struct Server {
 impl Server {
     /// Create a new Server with Rerun visualization.
     ///
+    /// Streams to the Rerun instance at `RERUN_CONNECT_ADDR` if that
+    /// environment variable is set and spawns a viewer otherwise. Use
+    /// [`Server::builder`] to choose the sink explicitly.
+    ///
     /// # Arguments
     /// * `application_id` - The application ID for Rerun (e.g., "schiebung", "my_robot_app")
     /// * `recording_id` - The recording ID for this session (e.g., "session_001", "run_2024_01_13")
@@ -104,38 +288,41 @@ impl Server {
         timeline: &str,
         publish_static_transforms: bool,
     ) -> Result<Self, CommsError> {
-        // Create base server
-        let inner = TransformServer::new().await?;
-
-        let builder = RecordingStreamBuilder::new(application_id).recording_id(recording_id);
-        let rec = if let Ok(addr_str) = std::env::var("RERUN_CONNECT_ADDR") {
-            builder
-                .connect_grpc_opts(addr_str)
-                .map_err(|e| CommsError::Config(format!("Failed to connect to Rerun: {}", e)))?
-        } else {
-            builder
-                .spawn()
-                .map_err(|e| CommsError::Config(format!("Failed to create Rerun stream: {}", e)))?
-        };
-        let observer =
-            RerunObserver::new(rec.clone(), publish_static_transforms, timeline.to_string());
-        inner
-            .buffer()
-            .write()
-            .map_err(|e| CommsError::MutexPoisoned(e.to_string()))?
-            .register_observer(Box::new(observer));
+        Self::builder(application_id)
+            .recording_id(recording_id)
+            .timeline(timeline)
+            .publish_static_transforms(publish_static_transforms)
+            .sink_from_env()
+            .build()
+            .await
+    }
 
-        Ok(Self { inner })
+    /// Start configuring a server with Rerun application ID `application_id`.
+    pub fn builder(application_id: impl Into<String>) -> ServerBuilder {
+        ServerBuilder::new(application_id)
     }
 
     /// Get a reference to the underlying buffer tree.
     ///
     /// This allows access to the transform buffer while the server is running
     /// in a background thread (via `start()`).
-    pub fn buffer(&self) -> Arc<RwLock<BufferTree>> {
+    pub fn buffer(&self) -> SharedBufferTree {
         self.inner.buffer()
     }
 
+    /// Serve Prometheus metrics for this server on `addr` (e.g.
+    /// `"0.0.0.0:9464"`) at `GET /metrics`.
+    ///
+    /// Requires the `metrics` feature. The endpoint runs in a background task
+    /// until the returned handle is dropped.
+    #[cfg(feature = "metrics")]
+    pub async fn serve_metrics(
+        &self,
+        addr: impl tokio::net::ToSocketAddrs,
+    ) -> Result<metrics::MetricsHandle, CommsError> {
+        metrics::serve_metrics(self.inner.clone(), addr).await
+    }
+
     /// Start the transform server in a background task.
     ///
     /// Returns a `ServerHandle` that can be used to shut down the server
@@ -149,13 +336,7 @@ impl Server {
             info!("Starting schiebung server with Rerun visualization (background)...");
 
             // Run the server until shutdown signal or completion
-            tokio::select! {
-                result = server.run() => result,
-                _ = async { shutdown_rx.await.ok() } => {
-                    info!("Server shutdown requested");
-                    Ok(())
-                }
-            }
+            server.run_with_shutdown(shutdown_rx).await
         });
 
         ServerHandle {
@@ -179,4 +360,4 @@ impl Server {
 // Re-export common types for convenience
 pub use comms::error::CommsError;
 pub use comms::TransformClient;
-pub use schiebung::BufferTree;
+pub use schiebung::{BufferTree, SharedBufferTree};
@@ -0,0 +1,149 @@
+//! Prometheus metrics endpoint (requires the `metrics` feature).
+//!
+//! Serves the counters from [`TransformServer::stats`] plus the current
+//! frame count in the Prometheus text exposition format on `GET /metrics`.
+//! The HTTP handling is deliberately minimal: one request per connection,
+//! no keep-alive, anything other than `GET /metrics` gets a 404.
+
+use comms::server::{ServerStats, TransformServer};
+use log::{error, info, warn};
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::task::JoinHandle;
+
+use crate::CommsError;
+
+const NOT_FOUND: &str = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+/// Handle to a running metrics endpoint. The endpoint stops when the handle
+/// is dropped or [`shutdown`](MetricsHandle::shutdown) is called.
+pub struct MetricsHandle {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MetricsHandle {
+    /// Address the endpoint is listening on. Useful when binding port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting scrapes.
+    pub fn shutdown(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for MetricsHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Start serving metrics for `server` on `addr` in a background task.
+pub async fn serve_metrics(
+    server: TransformServer,
+    addr: impl ToSocketAddrs,
+) -> Result<MetricsHandle, CommsError> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| CommsError::Config(format!("Failed to bind metrics endpoint: {}", e)))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| CommsError::Config(format!("Failed to bind metrics endpoint: {}", e)))?;
+    info!("Serving metrics on http://{}/metrics", local_addr);
+
+    let task = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let server = server.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &server).await {
+                            warn!("Metrics connection failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("Error accepting metrics connection: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(MetricsHandle { local_addr, task })
+}
+
+async fn handle_connection(mut stream: TcpStream, server: &TransformServer) -> std::io::Result<()> {
+    // Only the request line matters; read until the end of the headers.
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..n]);
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let frames = server.buffer().read().await.frame_count();
+            let body = render(&server.stats(), frames);
+            format!(
+                "HTTP/1.1 200 OK\r\n\
+                 Content-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => NOT_FOUND.to_string(),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Render `stats` and the frame count in the Prometheus text format.
+pub fn render(stats: &ServerStats, frames: usize) -> String {
+    let metrics = [
+        (
+            "schiebung_requests_served_total",
+            "counter",
+            "Transform and visualization queries answered.",
+            stats.requests_served,
+        ),
+        (
+            "schiebung_lookups_failed_total",
+            "counter",
+            "Transform queries answered with an error.",
+            stats.lookups_failed,
+        ),
+        (
+            "schiebung_transforms_ingested_total",
+            "counter",
+            "Transform samples stored in the buffer.",
+            stats.transforms_ingested,
+        ),
+        (
+            "schiebung_frames",
+            "gauge",
+            "Frames currently in the buffer.",
+            frames as u64,
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    out
+}
@@ -0,0 +1,71 @@
+use comms::ZenohConfig;
+use schiebung::types::{StampedIsometry, TransformType};
+use schiebung::TransformUpdate;
+use schiebung_server::{RecordingSink, Server, ServerBuilder};
+
+/// No multicast scouting, so building a server does not depend on the
+/// network it runs on.
+fn local_builder() -> ServerBuilder {
+    Server::builder("schiebung_test").zenoh_config(ZenohConfig {
+        multicast_scouting: false,
+        ..ZenohConfig::default()
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_build_save_writes_recording() {
+    let path =
+        std::env::temp_dir().join(format!("schiebung_builder_test_{}.rrd", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let builder = local_builder()
+        .recording_id("builder_test")
+        .timeline("test_time")
+        .save(&path);
+    assert_eq!(builder.sink(), &RecordingSink::Save(path.clone()));
+    let server = builder.build().await.expect("Failed to build server");
+    server
+        .buffer()
+        .write()
+        .await
+        .update(&[TransformUpdate::new(
+            "world",
+            "robot",
+            StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0),
+            TransformType::Static,
+        )])
+        .unwrap();
+    // Dropping the server drops the recording stream, which flushes it.
+    drop(server);
+
+    let size = std::fs::metadata(&path)
+        .expect("Recording file was not created")
+        .len();
+    std::fs::remove_file(&path).unwrap();
+    assert!(size > 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_build_connect_grpc() {
+    // The gRPC sink connects in the background, so no viewer is needed.
+    let builder = local_builder().connect_grpc("rerun+http://127.0.0.1:9876/proxy");
+    assert_eq!(
+        builder.sink(),
+        &RecordingSink::ConnectGrpc("rerun+http://127.0.0.1:9876/proxy".to_string())
+    );
+    assert!(builder.build().await.is_ok());
+
+    assert!(local_builder()
+        .connect_grpc("not an address")
+        .build()
+        .await
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore = "spawns a Rerun viewer, which needs the rerun executable on PATH"]
+async fn test_build_spawn() {
+    let builder = local_builder().save("unused.rrd").spawn();
+    assert_eq!(builder.sink(), &RecordingSink::Spawn);
+    assert!(builder.build().await.is_ok());
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "metrics")]
+
+use comms::server::TransformServer;
+use comms::{TransformClient, ZenohConfig};
+use schiebung::types::{StampedIsometry, TransformType};
+use schiebung_server::metrics::serve_metrics;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Own port so this test cannot talk to servers from the comms tests.
+const METRICS_TEST_ENDPOINT: &str = "tcp/127.0.0.1:17459";
+
+async fn scrape(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).await.expect("Failed to connect");
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    response
+}
+
+fn metric(response: &str, name: &str) -> u64 {
+    response
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.trim().parse().ok())
+        .unwrap_or_else(|| panic!("{} missing in:\n{}", name, response))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_metrics_endpoint_counts_traffic() {
+    let server = TransformServer::with_config(ZenohConfig {
+        listen: vec![METRICS_TEST_ENDPOINT.to_string()],
+        multicast_scouting: false,
+        ..ZenohConfig::default()
+    })
+    .await
+    .expect("Failed to init server");
+    let metrics = serve_metrics(server.clone(), "127.0.0.1:0")
+        .await
+        .expect("Failed to start metrics endpoint");
+    let server_handle = tokio::spawn(async move {
+        if let Err(e) = server.run().await {
+            eprintln!("Server error: {}", e);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let before = scrape(metrics.local_addr()).await;
+    assert_eq!(metric(&before, "schiebung_requests_served_total"), 0);
+    assert_eq!(metric(&before, "schiebung_transforms_ingested_total"), 0);
+    assert_eq!(metric(&before, "schiebung_frames"), 0);
+
+    let client = TransformClient::with_config(ZenohConfig {
+        connect: vec![METRICS_TEST_ENDPOINT.to_string()],
+        multicast_scouting: false,
+        ..ZenohConfig::default()
+    })
+    .await
+    .expect("Failed to create client");
+
+    // zenoh's put can race subscriber matching, so publish until it lands.
+    let transform = StampedIsometry::new([1.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0], 0);
+    let mut stored = false;
+    for _ in 0..20 {
+        client
+            .send_transform("world", "robot", transform.clone(), TransformType::Static)
+            .await
+            .expect("Failed to send transform");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if client.request_transform("world", "robot", 0).await.is_ok() {
+            stored = true;
+            break;
+        }
+    }
+    assert!(stored, "Transform never reached the server");
+    assert!(client
+        .request_transform("world", "camera", 0)
+        .await
+        .is_err());
+
+    let after = scrape(metrics.local_addr()).await;
+    let requests = metric(&after, "schiebung_requests_served_total");
+    let failed = metric(&after, "schiebung_lookups_failed_total");
+    assert!(metric(&after, "schiebung_transforms_ingested_total") >= 1);
+    assert!(requests >= 2);
+    assert!(failed >= 1);
+    assert_eq!(metric(&after, "schiebung_frames"), 2);
+
+    assert!(client
+        .request_transform("world", "camera", 0)
+        .await
+        .is_err());
+    let last = scrape(metrics.local_addr()).await;
+    assert_eq!(
+        metric(&last, "schiebung_requests_served_total"),
+        requests + 1
+    );
+    assert_eq!(metric(&last, "schiebung_lookups_failed_total"), failed + 1);
+
+    server_handle.abort();
+}
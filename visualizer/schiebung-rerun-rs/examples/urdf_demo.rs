@@ -1,7 +1,6 @@
 use nalgebra::UnitQuaternion;
 use rerun::RecordingStreamBuilder;
-use schiebung::{BufferTree, StampedIsometry, TransformType, TransformUpdate};
-use schiebung_rerun::RerunObserver;
+use schiebung::{StampedIsometry, TransformType, TransformUpdate};
 use std::env;
 use std::path::PathBuf;
 
@@ -18,31 +17,37 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Loading URDF from {}", urdf_path.display());
     let rec = RecordingStreamBuilder::new("urdf_demo").spawn()?;
 
-    let mut buffer = BufferTree::new();
-
-    // publish_static_transforms = true so the cube static below is logged
-    // through the observer on `tf_static`. The URDF's own fixed joints are
-    // NOT loaded into the buffer (no `UrdfLoader::load_into_buffer` call)
-    // because `rec.log_file_from_path` already populates rerun's transform
-    // tree for them; routing them through the observer too would double-log.
-    let observer = RerunObserver::new(rec.clone(), true, "stable_time".to_string());
-    buffer.register_observer(Box::new(observer));
-
-    rec.log_file_from_path(urdf_path.to_str().unwrap(), None, true)?;
+    // Loads the URDF's joints into the buffer as static edges and logs the
+    // file itself through rerun's URDF loader. publish_static = false
+    // because that loader already logs the joint origins; routing them
+    // through the observer too would double-log.
+    let mut buffer = schiebung_rerun::from_urdf(
+        urdf_path.to_str().unwrap(),
+        rec.clone(),
+        false,
+        "stable_time".to_string(),
+    )?;
 
     // Place a small static cube in the robot's workspace so we can visualize
-    // the distance from the wrist tip to it as the arm moves. Inserting it as
-    // Static into the buffer makes the observer publish `base_link ->
-    // target_cube` on the collapsed `tf_static` entity (named-frames graph),
-    // registering `target_cube` as a frame at this pose. The box visual below
-    // opts into that frame via `with_parent_frame`.
-    let cube_pose = StampedIsometry::from_secs([0.6, 0.4, 1.0], [0.0, 0.0, 0.0, 1.0], 0.0);
+    // the distance from the wrist tip to it as the arm moves. The buffer
+    // needs it for the lookup below; since statics are not published by the
+    // observer here, its frame is also logged to rerun directly as
+    // `base_link -> target_cube`. The box visual below opts into that frame
+    // via `with_parent_frame`.
+    let cube_translation = [0.6, 0.4, 1.0];
+    let cube_pose = StampedIsometry::from_secs(cube_translation, [0.0, 0.0, 0.0, 1.0], 0.0);
     buffer.update(&[TransformUpdate::new(
         "base_link",
         "target_cube",
         cube_pose,
         TransformType::Static,
     )])?;
+    rec.log_static(
+        "target_cube_frame",
+        &rerun::Transform3D::from_translation(cube_translation.map(|v| v as f32))
+            .with_parent_frame("base_link".to_string())
+            .with_child_frame("target_cube".to_string()),
+    )?;
 
     rec.log_static(
         "target_cube",
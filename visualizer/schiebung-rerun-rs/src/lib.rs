@@ -4,8 +4,12 @@
 use std::collections::HashMap;
 use std::sync::Mutex;
 
+use nalgebra::{Matrix3, Quaternion, Rotation3, UnitQuaternion, Vector3};
 use rerun::{RecordingStream, TimeColumn};
-use schiebung::{BufferObserver, TransformType, TransformUpdate};
+use schiebung::{
+    BufferObserver, BufferTree, FormatLoader, StampedIsometry, TfError, TransformType,
+    TransformUpdate, UrdfLoader,
+};
 
 /// Entity path that carries every dynamic transform. Matches ROS / rerun
 /// 0.32+ convention.
@@ -14,6 +18,63 @@ const DYNAMIC_ENTITY_PATH: &str = "tf";
 /// 0.32+ convention.
 const STATIC_ENTITY_PATH: &str = "tf_static";
 
+/// Axis convention poses are expressed in when they are logged.
+///
+/// The buffer always holds poses in ROS REP-103 (x forward, y left, z up,
+/// right-handed). Any other convention is reached by a fixed change of basis
+/// `C`: translations become `C * t` and rotations `C * R * Cᵀ`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinateConvention {
+    /// ROS REP-103: x forward, y left, z up, right-handed. No conversion.
+    #[default]
+    ZUpRightHanded,
+    /// y up, right-handed (e.g. glTF / OpenGL): the REP-103 frame turned
+    /// -90° about x, so `(x, y, z)` maps to `(x, z, -y)`.
+    YUpRightHanded,
+    /// y up, left-handed (e.g. Unity): x right, y up, z forward, so
+    /// `(x, y, z)` maps to `(-y, z, x)`.
+    YUpLeftHanded,
+}
+
+impl CoordinateConvention {
+    /// Change-of-basis matrix from REP-103 into this convention.
+    fn basis(self) -> Matrix3<f64> {
+        match self {
+            CoordinateConvention::ZUpRightHanded => Matrix3::identity(),
+            CoordinateConvention::YUpRightHanded => {
+                Matrix3::new(1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, 0.0)
+            }
+            CoordinateConvention::YUpLeftHanded => {
+                Matrix3::new(0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0)
+            }
+        }
+    }
+
+    /// Re-express a REP-103 pose in this convention. The stamp is kept.
+    pub fn convert(self, pose: &StampedIsometry) -> StampedIsometry {
+        let (translation, rotation) =
+            self.convert_parts(pose.translation_ref(), pose.rotation_ref());
+        StampedIsometry::new(translation, rotation, pose.stamp())
+    }
+
+    /// Re-express a REP-103 translation and `[x, y, z, w]` quaternion in
+    /// this convention.
+    fn convert_parts(self, translation: &[f64; 3], rotation: &[f64; 4]) -> ([f64; 3], [f64; 4]) {
+        if self == CoordinateConvention::ZUpRightHanded {
+            return (*translation, *rotation);
+        }
+        let basis = self.basis();
+        let t = basis * Vector3::from(*translation);
+        let [x, y, z, w] = *rotation;
+        let r = UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z));
+        let converted = Rotation3::from_matrix_unchecked(
+            basis * r.to_rotation_matrix().matrix() * basis.transpose(),
+        );
+        let q = UnitQuaternion::from_rotation_matrix(&converted);
+        ([t.x, t.y, t.z], [q.i, q.j, q.k, q.w])
+    }
+}
+
 /// Observer that logs transforms to a Rerun recording stream.
 ///
 /// Every `on_update` call from the buffer turns into at most two
@@ -36,6 +97,15 @@ const STATIC_ENTITY_PATH: &str = "tf_static";
 /// flag should be set to false; otherwise the static transforms will be logged
 /// twice.
 ///
+/// High-rate dynamic edges can be thinned out with
+/// [`with_motion_threshold`](RerunObserver::with_motion_threshold): a dynamic
+/// sample is then only logged once its frame has moved far enough from the
+/// last pose that was logged for it.
+///
+/// Poses are logged in ROS REP-103 by default; use
+/// [`with_coordinate_convention`](RerunObserver::with_coordinate_convention)
+/// to convert them into another axis convention first.
+///
 /// # Example
 ///
 /// ```no_run
@@ -63,6 +133,17 @@ pub struct RerunObserver {
     /// in full on every static-touching `on_update` because rerun-static
     /// replaces all component values on the entity on each write.
     static_state: Mutex<HashMap<(String, String), Row>>,
+    /// Minimum translation (meters) a dynamic frame must move before it is
+    /// logged again. `0.0` logs every sample.
+    min_translation_delta: f64,
+    /// Minimum rotation (radians) a dynamic frame must turn before it is
+    /// logged again. `0.0` logs every sample.
+    min_rotation_delta: f64,
+    /// Last logged dynamic pose per (parent, child), used for the motion
+    /// threshold. Only populated when a threshold is set.
+    last_logged: Mutex<HashMap<(String, String), Row>>,
+    /// Axis convention every pose is converted into before logging.
+    convention: CoordinateConvention,
 }
 
 impl RerunObserver {
@@ -85,8 +166,90 @@ impl RerunObserver {
             publish_static_transforms,
             timeline,
             static_state: Mutex::new(HashMap::new()),
+            min_translation_delta: 0.0,
+            min_rotation_delta: 0.0,
+            last_logged: Mutex::new(HashMap::new()),
+            convention: CoordinateConvention::default(),
         }
     }
+
+    /// Only log a dynamic frame once it has moved at least
+    /// `min_translation_delta` meters or turned at least `min_rotation_delta`
+    /// radians since the last pose logged for it.
+    ///
+    /// The first sample of every frame is always logged. Static transforms
+    /// are not affected.
+    pub fn with_motion_threshold(
+        mut self,
+        min_translation_delta: f64,
+        min_rotation_delta: f64,
+    ) -> Self {
+        self.min_translation_delta = min_translation_delta;
+        self.min_rotation_delta = min_rotation_delta;
+        self
+    }
+
+    /// Attach dynamic transforms to the Rerun timeline `timeline` instead of
+    /// the one given to [`new`](RerunObserver::new).
+    pub fn with_timeline(mut self, timeline: impl Into<String>) -> Self {
+        self.timeline = timeline.into();
+        self
+    }
+
+    /// Set whether static transforms are forwarded to Rerun, overriding the
+    /// `publish_static_transforms` flag given to [`new`](RerunObserver::new).
+    pub fn with_static(mut self, publish_static_transforms: bool) -> Self {
+        self.publish_static_transforms = publish_static_transforms;
+        self
+    }
+
+    /// Convert every pose from REP-103 into `convention` before logging it.
+    /// The motion threshold is evaluated on the converted poses.
+    pub fn with_coordinate_convention(mut self, convention: CoordinateConvention) -> Self {
+        self.convention = convention;
+        self
+    }
+
+    /// Name of the Rerun timeline dynamic transforms are logged on.
+    pub fn timeline(&self) -> &str {
+        &self.timeline
+    }
+
+    /// Whether static transforms are forwarded to Rerun.
+    pub fn publish_static_transforms(&self) -> bool {
+        self.publish_static_transforms
+    }
+
+    /// Axis convention poses are logged in.
+    pub fn coordinate_convention(&self) -> CoordinateConvention {
+        self.convention
+    }
+
+    /// Drop the dynamic rows that stay within the motion threshold of the
+    /// last logged pose of their frame, recording the ones that pass.
+    fn filter_moved(&self, rows: Vec<Row>) -> Vec<Row> {
+        if self.min_translation_delta <= 0.0 && self.min_rotation_delta <= 0.0 {
+            return rows;
+        }
+
+        let mut last_logged = self.last_logged.lock().unwrap();
+        rows.into_iter()
+            .filter(|row| {
+                let key = (row.parent.clone(), row.child.clone());
+                let moved = match last_logged.get(&key) {
+                    Some(last) => {
+                        translation_delta(last, row) >= self.min_translation_delta
+                            || rotation_delta(last, row) >= self.min_rotation_delta
+                    }
+                    None => true,
+                };
+                if moved {
+                    last_logged.insert(key, row.clone());
+                }
+                moved
+            })
+            .collect()
+    }
 }
 
 /// Single row of columnar data we collect before sending. Strings are owned
@@ -102,14 +265,16 @@ struct Row {
     stamp_ns: i64,
 }
 
-fn row_from(update: &TransformUpdate) -> Row {
-    let t = update.stamped_isometry.translation();
-    let r = update.stamped_isometry.rotation();
+fn row_from(update: &TransformUpdate, convention: CoordinateConvention) -> Row {
+    let (t, r) = convention.convert_parts(
+        update.stamped_isometry.translation_ref(),
+        update.stamped_isometry.rotation_ref(),
+    );
     Row {
         parent: update.from.clone(),
         child: update.to.clone(),
-        translation: [t[0] as f32, t[1] as f32, t[2] as f32],
-        quaternion: [r[0] as f32, r[1] as f32, r[2] as f32, r[3] as f32],
+        translation: t.map(|v| v as f32),
+        quaternion: r.map(|v| v as f32),
         stamp_ns: update.stamped_isometry.stamp(),
     }
 }
@@ -121,15 +286,16 @@ impl BufferObserver for RerunObserver {
 
         for update in updates {
             match update.kind {
-                TransformType::Dynamic => dynamic_rows.push(row_from(update)),
+                TransformType::Dynamic => dynamic_rows.push(row_from(update, self.convention)),
                 TransformType::Static => {
                     if self.publish_static_transforms {
-                        static_updates.push(row_from(update));
+                        static_updates.push(row_from(update, self.convention));
                     }
                 }
             }
         }
 
+        let dynamic_rows = self.filter_moved(dynamic_rows);
         if !dynamic_rows.is_empty() {
             self.send_dynamic(DYNAMIC_ENTITY_PATH, &dynamic_rows);
         }
@@ -180,6 +346,21 @@ impl RerunObserver {
     }
 }
 
+fn translation_delta(a: &Row, b: &Row) -> f64 {
+    let a = Vector3::from(a.translation.map(f64::from));
+    let b = Vector3::from(b.translation.map(f64::from));
+    (a - b).norm()
+}
+
+fn rotation_delta(a: &Row, b: &Row) -> f64 {
+    unit_quaternion(a).angle_to(&unit_quaternion(b))
+}
+
+fn unit_quaternion(row: &Row) -> UnitQuaternion<f64> {
+    let [x, y, z, w] = row.quaternion.map(f64::from);
+    UnitQuaternion::new_normalize(Quaternion::new(w, x, y, z))
+}
+
 /// Build the Transform3D and CoordinateFrame component columns for a batch
 /// of rows. Returns `None` if rerun's columnar serialization fails for
 /// either archetype (logged via `re_log` inside rerun).
@@ -213,3 +394,173 @@ fn build_columns(
 
     Some((tf_columns, frame_columns))
 }
+
+/// Load the URDF at `urdf_path` into a new [`BufferTree`], log the file to
+/// `rec`, and register a [`RerunObserver`] on the buffer.
+///
+/// Rerun's URDF loader logs the robot's geometry and joint origins, so the
+/// arm shows up in the viewer right away. The joints also land in the
+/// buffer as static edges (see [`UrdfLoader`]), so lookups between links
+/// work immediately. Pass `publish_static = false` unless the URDF's joints
+/// should also be logged through the observer; with `true` they are logged
+/// twice. Updates applied to the returned buffer are logged on `timeline`.
+///
+/// # Errors
+///
+/// [`TfError::LoaderError`] if the URDF cannot be loaded into the buffer or
+/// logged to `rec`.
+///
+/// # Example
+///
+/// ```no_run
+/// use rerun::RecordingStreamBuilder;
+///
+/// let rec = RecordingStreamBuilder::new("my_robot").spawn()?;
+/// let buffer = schiebung_rerun::from_urdf(
+///     "robot.urdf",
+///     rec,
+///     /* publish_static = */ false,
+///     "stable_time".to_string(),
+/// )?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn from_urdf(
+    urdf_path: &str,
+    rec: RecordingStream,
+    publish_static: bool,
+    timeline: String,
+) -> Result<BufferTree, TfError> {
+    let mut buffer = BufferTree::new();
+    UrdfLoader::new().load_into_buffer(urdf_path, &mut buffer)?;
+    rec.log_file_from_path(urdf_path, None, true).map_err(|e| {
+        TfError::LoaderError(format!(
+            "Failed to log URDF file '{}' to Rerun: {}",
+            urdf_path, e
+        ))
+    })?;
+    buffer.register_observer(Box::new(RerunObserver::new(rec, publish_static, timeline)));
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(child: &str, x: f32, stamp_ns: i64) -> Row {
+        Row {
+            parent: "world".to_string(),
+            child: child.to_string(),
+            translation: [x, 0.0, 0.0],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+            stamp_ns,
+        }
+    }
+
+    #[test]
+    fn test_sub_threshold_updates_are_suppressed() {
+        let observer = RerunObserver::new(RecordingStream::disabled(), true, "t".to_string())
+            .with_motion_threshold(0.1, 0.1);
+
+        let logged = observer.filter_moved(vec![
+            row("robot", 0.0, 0),
+            row("robot", 0.05, 1),
+            row("robot", 0.09, 2),
+            row("robot", 0.2, 3),
+            row("sensor", 0.0, 3),
+        ]);
+        let stamps: Vec<(String, i64)> = logged
+            .iter()
+            .map(|r| (r.child.clone(), r.stamp_ns))
+            .collect();
+        assert_eq!(
+            stamps,
+            vec![
+                ("robot".to_string(), 0),
+                ("robot".to_string(), 3),
+                ("sensor".to_string(), 3),
+            ]
+        );
+
+        // Deltas are measured against the last *logged* pose, not the last seen one.
+        assert!(observer
+            .filter_moved(vec![row("robot", 0.25, 4)])
+            .is_empty());
+        assert_eq!(observer.filter_moved(vec![row("robot", 0.35, 5)]).len(), 1);
+    }
+
+    #[test]
+    fn test_accessors_reflect_configuration() {
+        let observer = RerunObserver::new(
+            RecordingStream::disabled(),
+            false,
+            "stable_time".to_string(),
+        );
+        assert_eq!(observer.timeline(), "stable_time");
+        assert!(!observer.publish_static_transforms());
+
+        let observer = observer.with_timeline("sim_time").with_static(true);
+        assert_eq!(observer.timeline(), "sim_time");
+        assert!(observer.publish_static_transforms());
+    }
+
+    #[test]
+    fn test_no_threshold_logs_everything() {
+        let observer = RerunObserver::new(RecordingStream::disabled(), true, "t".to_string());
+        let logged = observer.filter_moved(vec![row("robot", 0.0, 0), row("robot", 0.0, 1)]);
+        assert_eq!(logged.len(), 2);
+    }
+
+    #[test]
+    fn test_from_urdf_loads_frames() {
+        let urdf_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../resources/test_robot.urdf"
+        );
+        let rrd_path =
+            std::env::temp_dir().join(format!("schiebung_from_urdf_{}.rrd", std::process::id()));
+        let rec = rerun::RecordingStreamBuilder::new("from_urdf_test")
+            .save(&rrd_path)
+            .unwrap();
+
+        let buffer = from_urdf(urdf_path, rec.clone(), false, "stable_time".to_string()).unwrap();
+        for frame in ["base_link", "shoulder_link", "wrist_3_link", "tool0"] {
+            assert!(buffer.has_frame(frame), "missing frame {}", frame);
+        }
+        assert!(buffer.lookup_latest_transform("base_link", "tool0").is_ok());
+
+        rec.flush_blocking().unwrap();
+        let _ = std::fs::remove_file(&rrd_path);
+
+        assert!(matches!(
+            from_urdf(
+                "does_not_exist.urdf",
+                RecordingStream::disabled(),
+                false,
+                "stable_time".to_string(),
+            ),
+            Err(TfError::LoaderError(_))
+        ));
+    }
+
+    #[test]
+    fn test_coordinate_convention_z_up_to_y_up() {
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        // 90° yaw about REP-103 up (+z), offset 1 forward, 2 left, 3 up.
+        let pose = StampedIsometry::new([1.0, 2.0, 3.0], [0.0, 0.0, h, h], 42);
+        let close = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9);
+
+        let same = CoordinateConvention::ZUpRightHanded.convert(&pose);
+        assert!(close(same.translation_ref(), &[1.0, 2.0, 3.0]));
+        assert!(close(same.rotation_ref(), &[0.0, 0.0, h, h]));
+
+        // Up becomes +y, so the yaw becomes a rotation about +y.
+        let y_up = CoordinateConvention::YUpRightHanded.convert(&pose);
+        assert_eq!(y_up.stamp(), 42);
+        assert!(close(y_up.translation_ref(), &[1.0, 3.0, -2.0]));
+        assert!(close(y_up.rotation_ref(), &[0.0, h, 0.0, h]));
+
+        let unity = CoordinateConvention::YUpLeftHanded.convert(&pose);
+        assert!(close(unity.translation_ref(), &[-2.0, 3.0, 1.0]));
+        assert!(close(unity.rotation_ref(), &[0.0, -h, 0.0, h]));
+    }
+}